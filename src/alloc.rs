@@ -136,6 +136,15 @@ impl<T: GCBox> Allocator<T> {
         self.count
     }
 
+    ///
+    /// Enable or disable GC, and returns whether GC was disabled before.
+    ///
+    pub fn set_gc_enabled(&mut self, enabled: bool) -> bool {
+        let disabled = !self.gc_enabled;
+        self.gc_enabled = enabled;
+        disabled
+    }
+
     ///
     /// Returns total active pages.
     ///
@@ -8,6 +8,7 @@ mod inst;
 mod interp;
 mod op;
 pub use builtins::*;
+pub(crate) use bytecodegen::FuncId;
 use bytecodegen::*;
 pub use compiler::*;
 pub use globals::*;
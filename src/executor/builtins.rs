@@ -1,11 +1,27 @@
 use super::*;
 
+mod array;
 mod class;
+mod comparable;
+mod enumerator;
+mod exception;
 mod file;
+mod float;
+mod gc;
+mod hash;
 mod integer;
+mod marshal;
+mod method;
+mod numeric;
 mod object;
+mod proc;
+mod range;
+mod string;
+mod symbol;
+mod thread;
 mod time;
 
+pub(super) use string::{string_concat, string_rem};
 pub use time::TimeInfo;
 
 //
@@ -53,14 +69,57 @@ pub fn init_builtins(globals: &mut Globals) {
         TIME_CLASS,
         globals.define_class_under_obj("Time").as_class()
     );
+    assert_eq!(
+        ARRAY_CLASS,
+        globals.define_class_under_obj("Array").as_class()
+    );
+    assert_eq!(
+        HASH_CLASS,
+        globals.define_class_under_obj("Hash").as_class()
+    );
+    assert_eq!(
+        PROC_CLASS,
+        globals.define_class_under_obj("Proc").as_class()
+    );
+    assert_eq!(
+        RANGE_CLASS,
+        globals.define_class_under_obj("Range").as_class()
+    );
+    exception::define_classes(globals);
+    assert_eq!(
+        METHOD_CLASS,
+        globals.define_class_under_obj("Method").as_class()
+    );
+    assert_eq!(
+        ENUMERATOR_CLASS,
+        globals.define_class_under_obj("Enumerator").as_class()
+    );
     globals.define_class_under_obj("Process");
+    let gc_class = globals.define_class_under_obj("GC").as_class();
     let file_class = globals.define_class_under_obj("File").as_class();
+    let marshal_class = globals.define_class_under_obj("Marshal").as_class();
+    let thread_class = globals.define_class_under_obj("Thread").as_class();
 
     object::init(globals);
     integer::init(globals);
+    numeric::init(globals);
+    float::init(globals);
+    comparable::init(globals);
+    exception::init(globals);
     class::init(globals);
+    array::init(globals);
+    hash::init(globals);
+    proc::init(globals);
+    method::init(globals);
+    enumerator::init(globals);
+    range::init(globals);
+    string::init(globals);
+    symbol::init(globals);
     time::init(globals);
     file::init(globals, file_class);
+    gc::init(globals, gc_class);
+    marshal::init(globals, marshal_class);
+    thread::init(globals, thread_class);
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -75,6 +134,16 @@ impl Arg {
     pub fn self_value(&self) -> Value {
         unsafe { *self.0.add(1) }
     }
+
+    ///
+    /// Get the block given to the method called with *len* arguments.
+    ///
+    /// Callers pass the block (or nil if not given) in the slot next to the arguments,
+    /// and the block can be called by `Interp::invoke_block()`.
+    ///
+    pub fn block(&self, len: usize) -> Value {
+        self[len]
+    }
 }
 
 impl std::ops::Index<usize> for Arg {
@@ -0,0 +1,1012 @@
+use super::comparable::{compare_values, to_ordering};
+use super::enumerator::new_enumerator;
+use crate::executor::op::{
+    add_values, cmp_eq_values, dig_values, hash_get, hash_insert, send_values,
+};
+use crate::*;
+use num::ToPrimitive;
+use std::cmp::Ordering;
+
+//
+// Array class
+//
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_singleton_func(ARRAY_CLASS, "new", new, -1);
+    globals.define_builtin_func(ARRAY_CLASS, "sum", sum, -1);
+    globals.define_builtin_func(ARRAY_CLASS, "max", max, -1);
+    globals.define_builtin_func(ARRAY_CLASS, "min", min, -1);
+    globals.define_builtin_func(ARRAY_CLASS, "minmax_by", minmax_by, 0);
+    globals.define_builtin_func(ARRAY_CLASS, "flat_map", flat_map, 0);
+    globals.define_builtin_func(ARRAY_CLASS, "each_cons", each_cons, 1);
+    globals.define_builtin_func(ARRAY_CLASS, "count", count, -1);
+    globals.define_builtin_func(ARRAY_CLASS, "dig", dig, -1);
+    globals.define_builtin_func(ARRAY_CLASS, "each", each, 0);
+    globals.define_builtin_func(ARRAY_CLASS, "inject", inject, -1);
+    globals.define_builtin_func(ARRAY_CLASS, "reduce", inject, -1);
+    globals.define_builtin_func(ARRAY_CLASS, "take", take, 1);
+    globals.define_builtin_func(ARRAY_CLASS, "drop", drop, 1);
+    globals.define_builtin_func(ARRAY_CLASS, "take_while", take_while, 0);
+    globals.define_builtin_func(ARRAY_CLASS, "drop_while", drop_while, 0);
+    globals.define_builtin_func(ARRAY_CLASS, "partition", partition, 0);
+    globals.define_builtin_func(ARRAY_CLASS, "group_by", group_by, 0);
+    globals.define_builtin_func(ARRAY_CLASS, "sort_by", sort_by, 0);
+}
+
+/// ### Array.new
+/// - new(size = 0, val = nil) -> Array
+/// - new(size) {|index| ... } -> Array
+///
+/// Every element refers to the same *val* object.
+/// As in CRuby, the block supersedes *val*.
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Array/s/new.html]
+extern "C" fn new(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    if len > 2 {
+        globals.set_error(MonorubyErr::wrong_arguments(2, len));
+        return None;
+    }
+    let size = if len == 0 {
+        0
+    } else {
+        match arg[0].as_fixnum() {
+            Some(i) => i,
+            None => {
+                globals.err_no_implict_conv(arg[0].class_id(), INTEGER_CLASS);
+                return None;
+            }
+        }
+    };
+    if size < 0 {
+        globals.err_argument("negative array size");
+        return None;
+    }
+    let block = arg.block(len);
+    if !block.is_nil() {
+        let mut ary = Vec::with_capacity(size as usize);
+        for i in 0..size {
+            ary.push(vm.invoke_block(globals, block, &[Value::new_integer(i)])?);
+        }
+        return Some(Value::new_array(ary));
+    }
+    let val = if len == 2 { arg[1] } else { Value::nil() };
+    Some(Value::new_array(vec![val; size as usize]))
+}
+
+/// ### Array#sum
+/// - sum(init = 0) -> object
+///
+/// As in CRuby, integers are summed up exactly, and floats following them are summed up
+/// by Kahan-Babuska summation. Other elements are summed up by `+`.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Array.html#I_SUM]
+extern "C" fn sum(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    if len > 1 {
+        globals.set_error(MonorubyErr::wrong_arguments(1, len));
+        return None;
+    }
+    let ary = arg.self_value().as_array().unwrap().clone();
+    let mut acc = if len == 0 {
+        Value::new_integer(0)
+    } else {
+        arg[0]
+    };
+    let mut i = 0;
+    let is_integer = |v: &Value| matches!(v.unpack(), RV::Integer(_) | RV::BigInt(_));
+    if is_integer(&acc) {
+        while i < ary.len() && is_integer(&ary[i]) {
+            acc = add_values(vm, globals, acc, ary[i])?;
+            i += 1;
+        }
+        if i < ary.len() && matches!(ary[i].unpack(), RV::Float(_)) {
+            let init = match acc.unpack() {
+                RV::Integer(n) => n as f64,
+                RV::BigInt(n) => n.to_f64().unwrap(),
+                _ => unreachable!(),
+            };
+            let (f, summed) = float_sum(init, &ary[i..]);
+            acc = Value::new_float(f);
+            i += summed;
+        }
+    }
+    for v in &ary[i..] {
+        acc = add_values(vm, globals, acc, *v)?;
+    }
+    Some(acc)
+}
+
+/// Sum up numerics at the beginning of *ary* and *init* by Kahan-Babuska summation.
+///
+/// Return the sum and the number of summed elements.
+fn float_sum(init: f64, ary: &[Value]) -> (f64, usize) {
+    let mut f = init;
+    let mut c = 0.0;
+    for (i, v) in ary.iter().enumerate() {
+        let x = match v.unpack() {
+            RV::Float(x) => x,
+            RV::Integer(x) => x as f64,
+            RV::BigInt(x) => x.to_f64().unwrap(),
+            // the compensation is discarded here, as in CRuby.
+            _ => return (f, i),
+        };
+        if f.is_nan() {
+            continue;
+        }
+        if x.is_nan() {
+            f = x;
+            continue;
+        }
+        if x.is_infinite() {
+            f = if f.is_infinite() && x.is_sign_negative() != f.is_sign_negative() {
+                f64::NAN
+            } else {
+                x
+            };
+            continue;
+        }
+        if f.is_infinite() {
+            continue;
+        }
+        let t = f + x;
+        if f.abs() >= x.abs() {
+            c += (f - t) + x;
+        } else {
+            c += (x - t) + f;
+        }
+        f = t;
+    }
+    (f + c, ary.len())
+}
+
+/// ### Array#max
+/// - max -> object | nil
+/// - max(n) -> Array
+/// - max {|a, b| ... } -> object | nil
+/// - max(n) {|a, b| ... } -> Array
+///
+/// Elements are compared by `<=>`, or by the block which returns the result of it.
+/// `max(n)` returns the *n* largest elements in descending order.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Array.html#I_MAX]
+extern "C" fn max(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    extremum_with_count(vm, globals, arg, len, Ordering::Greater)
+}
+
+/// ### Array#min
+/// - min -> object | nil
+/// - min(n) -> Array
+/// - min {|a, b| ... } -> object | nil
+/// - min(n) {|a, b| ... } -> Array
+///
+/// Elements are compared by `<=>`, or by the block which returns the result of it.
+/// `min(n)` returns the *n* smallest elements in ascending order.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Array.html#I_MIN]
+extern "C" fn min(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    extremum_with_count(vm, globals, arg, len, Ordering::Less)
+}
+
+/// Find the extremum, or the first *n* elements in the order of *ord* if a count is given.
+fn extremum_with_count(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+    ord: Ordering,
+) -> Option<Value> {
+    if len > 1 {
+        globals.set_error(MonorubyErr::wrong_arguments_range(0, 1, len));
+        return None;
+    }
+    let ary = arg.self_value();
+    let block = arg.block(len);
+    if len == 0 {
+        return extremum(vm, globals, ary, block, ord);
+    }
+    let n = match arg[0].as_fixnum() {
+        Some(n) => n,
+        None => {
+            globals.err_no_implict_conv(arg[0].class_id(), INTEGER_CLASS);
+            return None;
+        }
+    };
+    if n < 0 {
+        globals.err_argument(&format!("negative size ({})", n));
+        return None;
+    }
+    let ary = ary.as_array().unwrap().clone();
+    // a partial insertion sort, which keeps only the first *n* elements.
+    let mut res: Vec<Value> = vec![];
+    for v in ary {
+        let mut pos = res.len();
+        for (i, r) in res.iter().enumerate() {
+            if compare_by_block(vm, globals, block, v, *r)? == ord {
+                pos = i;
+                break;
+            }
+        }
+        if pos < n as usize {
+            res.insert(pos, v);
+            res.truncate(n as usize);
+        }
+    }
+    Some(Value::new_array(res))
+}
+
+/// Compare *lhs* and *rhs* by `<=>`, or by *block* if it is not nil.
+fn compare_by_block(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    block: Value,
+    lhs: Value,
+    rhs: Value,
+) -> Option<Ordering> {
+    if block.is_nil() {
+        compare_values(vm, globals, lhs, rhs)
+    } else {
+        let cmp = vm.invoke_block(globals, block, &[lhs, rhs])?;
+        to_ordering(globals, cmp, lhs, rhs)
+    }
+}
+
+/// Find the first element which is ordered as *ord* against all the others.
+///
+/// Return nil for an empty array.
+fn extremum(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    ary: Value,
+    block: Value,
+    ord: Ordering,
+) -> Option<Value> {
+    let ary = ary.as_array().unwrap().clone();
+    let mut iter = ary.into_iter();
+    let mut res = match iter.next() {
+        Some(v) => v,
+        None => return Some(Value::nil()),
+    };
+    for v in iter {
+        let cmp = compare_by_block(vm, globals, block, v, res)?;
+        if cmp == ord {
+            res = v;
+        }
+    }
+    Some(res)
+}
+
+/// ### Enumerable#minmax_by
+/// - minmax_by {|item| ... } -> [object, object]
+///
+/// Elements are compared by `<=>` of the values returned by the block.
+/// Return `[nil, nil]` for an empty array.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Enumerable.html#I_MINMAX_BY]
+extern "C" fn minmax_by(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    let ary = arg.self_value().as_array().unwrap().clone();
+    let mut iter = ary.into_iter();
+    let first = match iter.next() {
+        Some(v) => v,
+        None => return Some(Value::new_array(vec![Value::nil(), Value::nil()])),
+    };
+    let key = vm.invoke_block(globals, block, &[first])?;
+    let (mut min, mut min_key, mut max, mut max_key) = (first, key, first, key);
+    for v in iter {
+        let key = vm.invoke_block(globals, block, &[v])?;
+        if compare_values(vm, globals, key, min_key)? == Ordering::Less {
+            min = v;
+            min_key = key;
+        }
+        if compare_values(vm, globals, key, max_key)? == Ordering::Greater {
+            max = v;
+            max_key = key;
+        }
+    }
+    Some(Value::new_array(vec![min, max]))
+}
+
+/// ### Enumerable#flat_map
+/// - flat_map {|item| ... } -> Array
+///
+/// Arrays returned by the block are flattened by one level.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Enumerable.html#I_FLAT_MAP]
+extern "C" fn flat_map(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    let ary = arg.self_value().as_array().unwrap().clone();
+    let mut res = vec![];
+    for v in ary {
+        let v = vm.invoke_block(globals, block, &[v])?;
+        match v.as_array() {
+            Some(ary) => res.extend_from_slice(ary),
+            None => res.push(v),
+        }
+    }
+    Some(Value::new_array(res))
+}
+
+/// ### Enumerable#each_cons
+/// - each_cons(n) {|list| ... } -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Enumerable.html#I_EACH_CONS]
+extern "C" fn each_cons(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let n = match arg[0].as_fixnum() {
+        Some(n) => n,
+        None => {
+            globals.err_no_implict_conv(arg[0].class_id(), INTEGER_CLASS);
+            return None;
+        }
+    };
+    if n <= 0 {
+        globals.err_argument("invalid size");
+        return None;
+    }
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    let self_val = arg.self_value();
+    let ary = self_val.as_array().unwrap().clone();
+    for window in ary.windows(n as usize) {
+        vm.invoke_block(globals, block, &[Value::new_array(window.to_vec())])?;
+    }
+    Some(self_val)
+}
+
+/// ### Array#count
+/// - count -> Integer
+/// - count(item) -> Integer
+/// - count {|item| ... } -> Integer
+///
+/// Count the elements which are `==` to *item*, or for which the block returns true.
+/// The block is ignored if *item* is given.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Array.html#I_COUNT]
+extern "C" fn count(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    if len > 1 {
+        globals.set_error(MonorubyErr::wrong_arguments_range(0, 1, len));
+        return None;
+    }
+    let ary = arg.self_value().as_array().unwrap().clone();
+    let block = arg.block(len);
+    let mut count = 0;
+    if len == 1 {
+        for v in ary {
+            if cmp_eq_values(vm, globals, v, arg[0])?.to_bool() {
+                count += 1;
+            }
+        }
+    } else if !block.is_nil() {
+        for v in ary {
+            if vm.invoke_block(globals, block, &[v])?.to_bool() {
+                count += 1;
+            }
+        }
+    } else {
+        count = ary.len();
+    }
+    Some(Value::new_integer(count as i64))
+}
+
+/// ### Array#dig
+/// - dig(idx, *rest) -> object | nil
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Array/i/dig.html]
+extern "C" fn dig(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    if len == 0 {
+        globals.set_error(MonorubyErr::wrong_arguments_min(1, len));
+        return None;
+    }
+    let keys: Vec<Value> = (0..len).map(|i| arg[i]).collect();
+    dig_values(vm, globals, arg.self_value(), &keys)
+}
+
+/// ### Array#each
+/// - each {|item| ... } -> self
+/// - each -> Enumerator
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Array/i/each.html]
+extern "C" fn each(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let block = arg.block(len);
+    let self_val = arg.self_value();
+    if block.is_nil() {
+        let values = self_val.as_array().unwrap().clone();
+        return Some(new_enumerator(globals, values));
+    }
+    // the block may change the array while iterating.
+    let mut i = 0;
+    while let Some(v) = self_val.as_array().unwrap().get(i).cloned() {
+        vm.invoke_block(globals, block, &[v])?;
+        i += 1;
+    }
+    Some(self_val)
+}
+
+/// ### Enumerable#inject
+/// - inject(init, sym) -> object
+/// - inject(sym) -> object
+/// - inject(init) {|result, item| ... } -> object
+/// - inject {|result, item| ... } -> object
+/// - reduce(init, sym) -> object
+/// - reduce(sym) -> object
+/// - reduce(init) {|result, item| ... } -> object
+/// - reduce {|result, item| ... } -> object
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Enumerable.html#I_INJECT]
+extern "C" fn inject(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    if len > 2 {
+        globals.set_error(MonorubyErr::wrong_arguments_range(0, 2, len));
+        return None;
+    }
+    let block = arg.block(len);
+    // a single argument is the initial value if the block is given, or the method name if not.
+    let (init, name) = match len {
+        2 => (Some(arg[0]), Some(arg[1])),
+        1 if block.is_nil() => (None, Some(arg[0])),
+        1 => (Some(arg[0]), None),
+        _ if block.is_nil() => {
+            globals.err_no_block_given();
+            return None;
+        }
+        _ => (None, None),
+    };
+    let name = match name {
+        Some(name) => match name.unpack() {
+            RV::Symbol(id) => Some(id),
+            _ => {
+                let name = globals.val_inspect(name);
+                globals.set_error(MonorubyErr::typeerr(format!("{} is not a symbol", name)));
+                return None;
+            }
+        },
+        None => None,
+    };
+    let mut iter = arg.self_value().as_array().unwrap().clone().into_iter();
+    let mut res = match init.or_else(|| iter.next()) {
+        Some(v) => v,
+        None => return Some(Value::nil()),
+    };
+    for v in iter {
+        res = match name {
+            Some(name) => send_values(vm, globals, res, name, &[v])?,
+            None => vm.invoke_block(globals, block, &[res, v])?,
+        };
+    }
+    Some(res)
+}
+
+/// ### Array#take
+/// - take(n) -> Array
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Array/i/take.html]
+extern "C" fn take(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let n = expect_size(globals, arg[0], "take")?;
+    let ary = arg.self_value().as_array().unwrap().clone();
+    Some(Value::new_array(ary.into_iter().take(n).collect()))
+}
+
+/// ### Array#drop
+/// - drop(n) -> Array
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Array/i/drop.html]
+extern "C" fn drop(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let n = expect_size(globals, arg[0], "drop")?;
+    let ary = arg.self_value().as_array().unwrap().clone();
+    Some(Value::new_array(ary.into_iter().skip(n).collect()))
+}
+
+/// Convert *n* to the size for `take` or `drop`, which must be a non-negative Integer.
+fn expect_size(globals: &mut Globals, n: Value, method: &str) -> Option<usize> {
+    match n.as_fixnum() {
+        Some(n) if n < 0 => {
+            globals.err_argument(&format!("attempt to {} negative size", method));
+            None
+        }
+        Some(n) => Some(n as usize),
+        None => {
+            globals.err_no_implict_conv(n.class_id(), INTEGER_CLASS);
+            None
+        }
+    }
+}
+
+/// ### Array#take_while
+/// - take_while {|element| ... } -> Array
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Array/i/take_while.html]
+extern "C" fn take_while(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let ary = arg.self_value().as_array().unwrap().clone();
+    let n = count_while(vm, globals, arg, len, &ary)?;
+    Some(Value::new_array(ary[..n].to_vec()))
+}
+
+/// ### Array#drop_while
+/// - drop_while {|element| ... } -> Array
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Array/i/drop_while.html]
+extern "C" fn drop_while(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let ary = arg.self_value().as_array().unwrap().clone();
+    let n = count_while(vm, globals, arg, len, &ary)?;
+    Some(Value::new_array(ary[n..].to_vec()))
+}
+
+/// Count the leading elements of *ary* for which the block returns true.
+fn count_while(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+    ary: &[Value],
+) -> Option<usize> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    for (i, v) in ary.iter().enumerate() {
+        if !vm.invoke_block(globals, block, &[*v])?.to_bool() {
+            return Some(i);
+        }
+    }
+    Some(ary.len())
+}
+
+/// ### Enumerable#partition
+/// - partition {|item| ... } -> [[object], [object]]
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Enumerable/i/partition.html]
+extern "C" fn partition(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    let ary = arg.self_value().as_array().unwrap().clone();
+    let (mut t, mut f) = (vec![], vec![]);
+    for v in ary {
+        if vm.invoke_block(globals, block, &[v])?.to_bool() {
+            t.push(v);
+        } else {
+            f.push(v);
+        }
+    }
+    Some(Value::new_array(vec![
+        Value::new_array(t),
+        Value::new_array(f),
+    ]))
+}
+
+/// ### Enumerable#group_by
+/// - group_by {|obj| ... } -> Hash
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Enumerable/i/group_by.html]
+extern "C" fn group_by(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    let ary = arg.self_value().as_array().unwrap().clone();
+    let mut map = HashTable::default();
+    for v in ary {
+        let key = vm.invoke_block(globals, block, &[v])?;
+        match hash_get(vm, globals, &map, key)? {
+            Some(group) => group.as_array_mut().unwrap().push(v),
+            None => hash_insert(vm, globals, &mut map, key, Value::new_array(vec![v]))?,
+        }
+    }
+    Some(Value::new_hash(map))
+}
+
+/// ### Enumerable#sort_by
+/// - sort_by {|item| ... } -> [object]
+///
+/// The sort is stable, so elements with equal keys keep their order.
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Enumerable/i/sort_by.html]
+extern "C" fn sort_by(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    let ary = arg.self_value().as_array().unwrap().clone();
+    let mut decorated = vec![];
+    for v in ary {
+        decorated.push((vm.invoke_block(globals, block, &[v])?, v));
+    }
+    let sorted = merge_sort_by_key(vm, globals, decorated)?;
+    Some(Value::new_array(
+        sorted.into_iter().map(|(_, v)| v).collect(),
+    ))
+}
+
+///
+/// Sort pairs of a key and a value by the keys with a stable merge sort.
+///
+/// `slice::sort_by` can not be used, since the comparison may fail by calling `<=>` defined in Ruby.
+///
+fn merge_sort_by_key(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    mut v: Vec<(Value, Value)>,
+) -> Option<Vec<(Value, Value)>> {
+    if v.len() <= 1 {
+        return Some(v);
+    }
+    let right = v.split_off(v.len() / 2);
+    let left = merge_sort_by_key(vm, globals, v)?;
+    let right = merge_sort_by_key(vm, globals, right)?;
+    let mut res = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        // take the left one if the keys are equal to keep the order.
+        if compare_values(vm, globals, r.0, l.0)? == Ordering::Less {
+            res.push(right.next().unwrap());
+        } else {
+            res.push(left.next().unwrap());
+        }
+    }
+    res.extend(left);
+    res.extend(right);
+    Some(res)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_array_new() {
+        run_test("Array.new.inspect");
+        run_test("Array.new(3).inspect");
+        run_test("Array.new(0, 5).inspect");
+        run_test("Array.new(3, :a).inspect");
+        run_test("a = Array.new(2, 1.5); a[1] = 7; a.inspect");
+        // the fill value is shared among all elements.
+        run_test(r#"a = Array.new(2, "x"); a[0] << "y"; a[1]"#);
+        run_test(r#"a = Array.new(2, "x"); a[0].equal?(a[1])"#);
+        run_test("Array.new(4) { |i| i * i }.inspect");
+        run_test("Array.new(0) { |i| i }.inspect");
+        run_test(r#"a = Array.new(2) { "x" }; a[0] << "y"; a[1]"#);
+        run_test(r#"a = Array.new(2) { "x" }; a[0].equal?(a[1])"#);
+    }
+
+    #[test]
+    fn test_array_sum() {
+        run_test("[].sum");
+        run_test("[1, 2, 3].sum");
+        run_test("[1, 2, 3].sum(10)");
+        run_test("[4611686018427387903, 4611686018427387903].sum");
+        run_test("[100000000000000000000, 1, -100000000000000000000].sum");
+        run_test("[1, 2.5, 3].sum");
+        run_test("[0.1, 0.2, 0.3].sum");
+        run_test("[0.1, 0.2, 0.3].sum(0.0)");
+        run_test("[3, 0.1, 0.2, 0.3, 100000000000000000000].sum");
+        run_test("[1.0, Float::INFINITY].sum.to_s");
+        run_test("[Float::INFINITY, 0.0 - Float::INFINITY].sum.to_s");
+        run_test("[1, Float::NAN].sum.to_s");
+        run_test("[1.5].sum(100000000000000000000)");
+        run_test(r#"["a", "b"].sum("")"#);
+        run_test(r#"["a", "b"].sum("") == "ab""#);
+        run_test(r#"["b", "c"].sum("a")"#);
+        run_test(r#""a" + "b""#);
+    }
+
+    #[test]
+    fn test_array_max_min() {
+        run_test("[].max.inspect");
+        run_test("[3, 1, 4, 1, 5].max");
+        run_test("[3, 1, 4, 1, 5].min");
+        run_test("[3, 1.5, 100000000000000000000, -2].max");
+        run_test("[3, 1.5, 100000000000000000000, -2].min");
+        run_test(r#"["pear", "fig", "banana"].max"#);
+        run_test(r#"["pear", "fig", "banana"].min"#);
+        run_test("[:b, :c, :a].max");
+        run_test("[3, 1, 4, 1, 5].max { |a, b| b <=> a }");
+        run_test("[3, 1, 4, 1, 5].min { |a, b| b <=> a }");
+        run_test("[[1, 2, 3], [4], [5, 6]].max { |a, b| a.sum <=> b.sum }.sum");
+        run_test("[[1, 2, 3], [4], [5, 6]].min { |a, b| a.sum - b.sum }.sum");
+        run_test("[-3, 2, -1].max { |a, b| (a * a) <=> (b * b) }");
+        run_test(
+            r#"
+            class C
+              def set(x); @x = x; self; end
+              def x; @x; end
+              def <=>(o); @x <=> o.x; end
+            end
+            a = [C.new.set(2), C.new.set(7), C.new.set(-1)]
+            [a.max.x, a.min.x].inspect
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_array_max_min_count() {
+        run_test("[3, 1, 2].max(2) == [3, 2]");
+        run_test("[3, 1, 4, 1, 5].max(3).inspect");
+        run_test("[3, 1, 4, 1, 5].min(3).inspect");
+        run_test("[3, 1, 4].max(0).inspect");
+        run_test("[3, 1, 4].min(10).inspect");
+        run_test("[].max(2).inspect");
+        run_test(r#"["pear", "fig", "banana"].min(2).inspect"#);
+        run_test("[3, 1, 4, 1, 5].max(2) { |a, b| b <=> a }.inspect");
+        run_test("[-3, 2, -1].min(2) { |a, b| (a * a) <=> (b * b) }.inspect");
+        run_test("[3, 1, 2].max == 3");
+    }
+
+    #[test]
+    fn test_array_count() {
+        run_test("[1, 2, 2, 3, 2].count");
+        run_test("[1, 2, 2, 3, 2].count(2)");
+        run_test("[1, 2, 2, 3, 2].count(2.0)");
+        run_test(r#"["a", "b", "a"].count("a")"#);
+        run_test("[1, 2, 2, 3, 2].count(4)");
+        run_test("[1, 2, 2, 3, 2].count { |x| x > 1 }");
+        run_test("[].count { |x| true }");
+        run_test(
+            r#"
+            class C
+              def ==(o); true; end
+            end
+            [C.new, C.new].count(1)
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_array_minmax_by() {
+        run_test("[].minmax_by { |x| x }.inspect");
+        run_test("[3, 1, 4, 2].minmax_by { |x| x }.inspect");
+        run_test("[3, -5, 4, 2].minmax_by { |x| x * x }.inspect");
+        run_test(r#"["pear", "fig", "banana"].minmax_by { |s| s }.inspect"#);
+        run_test("[[1, 2, 3], [4], [5, 6]].minmax_by { |a| a.sum }.inspect");
+    }
+
+    #[test]
+    fn test_array_flat_map() {
+        run_test("[[1], [2]].flat_map { |x| x } == [1, 2]");
+        run_test("[1, 2, 3].flat_map { |x| [x, x * 10] }.inspect");
+        run_test("[1, 2].flat_map { |x| [[x]] }.inspect");
+        run_test("[1, 2].flat_map { |x| x * 2 }.inspect");
+        run_test("[].flat_map { |x| [x] }.inspect");
+    }
+
+    #[test]
+    fn test_array_each_cons() {
+        run_test(
+            r#"
+            $a = []; $i = 0
+            [1, 2, 3].each_cons(2) { |x| $a[$i] = x; $i += 1 }
+            $a == [[1, 2], [2, 3]]
+            "#,
+        );
+        run_test(
+            r#"
+            $a = []; $i = 0
+            [1, 2, 3, 4].each_cons(3) { |x| $a[$i] = x.sum; $i += 1 }
+            $a.inspect
+            "#,
+        );
+        run_test("$i = 0; [1, 2].each_cons(3) { |x| $i += 1 }; $i");
+        run_test("a = [1, 2]; a.each_cons(1) { |x| x }.equal?(a)");
+    }
+
+    #[test]
+    fn test_array_max_min_error() {
+        for code in [
+            r#"[1, "a"].max"#,
+            "[Object.new, Object.new].min",
+            "[1, 2].max { |a, b| nil }",
+            "[1, :a].minmax_by { |x| x }",
+            "[1, 2].each_cons(0) { |x| x }",
+            "[1, 2].max(-1)",
+            r#"[1, "a", 2].min(2)"#,
+        ] {
+            run_test_error(code, MonorubyErrKind::Argument(String::new()));
+        }
+    }
+
+    #[test]
+    fn test_array_sum_error() {
+        for code in [r#"["a", "b"].sum"#, r#"[1, "a"].sum"#] {
+            run_test_error(code, MonorubyErrKind::MethodNotFound(IdentId::_ADD));
+        }
+        run_test_error(r#"["a", 1].sum("")"#, MonorubyErrKind::Type(String::new()));
+    }
+
+    #[test]
+    fn test_array_dig() {
+        run_test("[[1, [2, 3]]].dig(0, 1, 0)");
+        run_test("[[1, [2, 3]]].dig(0, 1, -1)");
+        run_test("[1, 2].dig(-1)");
+        run_test("[[1, 2]].dig(5, 0).inspect");
+        run_test("[{a: [1, 2]}].dig(0, :a, 1)");
+        run_test("[[1, [2, 3]]].dig(0, 1).inspect");
+        run_test_error("[1].dig(0, 1)", MonorubyErrKind::Type(String::new()));
+    }
+
+    #[test]
+    fn test_array_each() {
+        run_test("$s = 0; [1, 2, 3].each { |x| $s += x }; $s");
+        run_test("a = [1]; a.each { |x| }.equal?(a)");
+        run_test("$s = 0; a = [1, 2]; a.each { |x| a[2] = 5 if x == 1; $s += x }; $s");
+        run_test("$s = 0; [[1, 2], [3, 4]].each { |a, b| $s += a * b }; $s");
+        run_test(
+            "$a = []; $i = 0; [[1, 2], 3].each { |a, b| $a[$i] = [a, b]; $i += 1 }; $a.inspect",
+        );
+        run_test("$a = []; $i = 0; [[1, 2, 3], [4]].each { |a, b| $a[$i] = [a, b]; $i += 1 }; $a.inspect");
+        run_test("$a = nil; [[1, 2]].each { |a| $a = a }; $a.inspect");
+        run_test("$s = 0; [1, 2, 3].each_cons(2) { |a, b| $s += a * b }; $s");
+    }
+
+    #[test]
+    fn test_array_inject() {
+        run_test("[1, 2, 3, 4].inject(:+)");
+        run_test("[1, 2, 3, 4].inject(10, :+)");
+        run_test("[1, 2, 3, 4].inject { |a, b| a + b }");
+        run_test("[1, 2, 3, 4].inject(10) { |a, b| a + b }");
+        run_test("[1, 2, 3, 4].reduce(:+) == [1, 2, 3, 4].reduce { |a, b| a + b }");
+        run_test("[1, 2, 3, 4].reduce(10, :+) == [1, 2, 3, 4].reduce(10) { |a, b| a + b }");
+        run_test("[].inject(:+).inspect");
+        run_test("[].inject(3, :+)");
+        run_test("[5].inject { |a, b| 0 }");
+        run_test("[1, 2, 3, 4].inject(:*)");
+        run_test("[1, 2.5, 100000000000000000000].inject(:+)");
+        run_test(r#"["a", "b", "c"].inject(:+)"#);
+        run_test("[3, 7, 2].inject { |a, b| a > b ? a : b }");
+        run_test("[7, 2, 3].inject(:ceildiv)");
+        run_test(r#"["a", "b"].inject("x") { |s, c| s << c }"#);
+        run_test(
+            r#"
+            class C
+              def set(x); @x = x; self; end
+              def x; @x; end
+              def plus(o); C.new.set(@x + o.x); end
+            end
+            [C.new.set(1), C.new.set(2), C.new.set(3)].inject(:plus).x
+            "#,
+        );
+        run_test_error("[1, 2].inject(1, 2)", MonorubyErrKind::Type(String::new()));
+    }
+
+    #[test]
+    fn test_array_take_drop() {
+        run_test("[1, 2, 3, 4].take(2).inspect");
+        run_test("[1, 2, 3, 4].take(0).inspect");
+        run_test("[1, 2, 3, 4].take(10).inspect");
+        run_test("[].take(1).inspect");
+        run_test("[1, 2, 3, 4].drop(2).inspect");
+        run_test("[1, 2, 3, 4].drop(0).inspect");
+        run_test("[1, 2, 3, 4].drop(10).inspect");
+        run_test("a = [1, 2]; a.take(2).equal?(a)");
+        run_test("a = [1, 2]; b = a.drop(0); b[0] = 5; a.inspect");
+        run_test_error("[1].take(-1)", MonorubyErrKind::Argument(String::new()));
+        run_test_error("[1].drop(-1)", MonorubyErrKind::Argument(String::new()));
+        run_test_error("[1].take(nil)", MonorubyErrKind::Type(String::new()));
+    }
+
+    #[test]
+    fn test_array_take_while_drop_while() {
+        run_test("[1, 2, 3, 1].take_while { |x| x < 3 }.inspect");
+        run_test("[1, 2, 3, 1].drop_while { |x| x < 3 }.inspect");
+        run_test("[1, 2, 3].take_while { |x| true }.inspect");
+        run_test("[1, 2, 3].drop_while { |x| true }.inspect");
+        run_test("[1, 2, 3].take_while { |x| nil }.inspect");
+        run_test("[1, 2, 3].drop_while { |x| false }.inspect");
+        run_test("$i = 0; [1, 2, 3, 4].take_while { |x| $i += 1; x < 2 }; $i");
+        run_test("[[1, 2], [3, 0], [4, 5]].take_while { |a, b| a < b }.inspect");
+    }
+
+    #[test]
+    fn test_array_partition() {
+        run_test("[1, 2, 3, 4, 5].partition { |x| x % 2 == 0 }.inspect");
+        run_test("[1, 2, 3].partition { |x| true }.inspect");
+        run_test("[1, 2, 3].partition { |x| nil }.inspect");
+        run_test("[].partition { |x| true }.inspect");
+        run_test("e, o = [1, 2, 3, 4].partition { |x| x % 2 == 0 }; e.sum * 10 + o.sum");
+    }
+
+    #[test]
+    fn test_array_group_by() {
+        run_test(
+            "[1, 2, 3, 4, 5, 6].group_by { |x| x % 3 } == {1 => [1, 4], 2 => [2, 5], 0 => [3, 6]}",
+        );
+        run_test("[1, 2, 3, 4, 5, 6].group_by { |x| x % 3 }.map { |k, v| k }.inspect");
+        run_test("[1, 2, 3, 4, 5, 6].group_by { |x| x % 3 }[0].inspect");
+        run_test(r#"["a", "bc", "d"].group_by { |s| s.start_with?("b") }[false].inspect"#);
+        run_test("[].group_by { |x| x } == {}");
+    }
+
+    #[test]
+    fn test_array_sort_by() {
+        run_test("[3, 1, 2].sort_by { |x| x }.inspect");
+        run_test("[3, 1, 2].sort_by { |x| -x }.inspect");
+        run_test("[].sort_by { |x| x }.inspect");
+        run_test(r#"["pear", "fig", "banana"].sort_by { |s| s }.inspect"#);
+        run_test("[1, 2, 3, 4, 5, 6, 7].sort_by { |x| (x * 5) % 7 }.inspect");
+        run_test("a = [2, 1]; a.sort_by { |x| x }; a.inspect");
+        run_test_error(
+            "[1, :a].sort_by { |x| x }",
+            MonorubyErrKind::Argument(String::new()),
+        );
+    }
+
+    #[test]
+    fn test_array_sort_by_stable() {
+        // CRuby does not guarantee the order of equal keys, so results are not compared with it.
+        let code = r#"
+            a = [[1, :a], [0, :b], [1, :c], [0, :d], [2, :e], [0, :f], [1, :g], [0, :h]]
+            a.sort_by { |k, name| k }
+            "#;
+        run_test_inspect(
+            code,
+            "[[0, :b], [0, :d], [0, :f], [0, :h], [1, :a], [1, :c], [1, :g], [2, :e]]",
+        );
+    }
+}
@@ -5,7 +5,52 @@ use crate::*;
 //
 
 pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_func(CLASS_CLASS, "new", new, -1);
     globals.define_builtin_func(CLASS_CLASS, "superclass", superclass, 0);
+    globals.define_builtin_func(CLASS_CLASS, "class_eval", class_eval, 0);
+    globals.define_builtin_func(CLASS_CLASS, "module_eval", class_eval, 0);
+}
+
+/// ### Class#new
+/// - new -> object
+///
+/// Instances of the builtin classes other than Object and Exception can not be created in this way.
+/// Exception classes (and their subclasses) accept an optional message.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Class.html#I_NEW]
+extern "C" fn new(_vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let class_id = arg.self_value().as_class();
+    if globals.is_subclass_of(class_id, EXCEPTION_CLASS) {
+        return new_exception(globals, class_id, arg, len);
+    }
+    if len != 0 {
+        globals.set_error(MonorubyErr::wrong_arguments(0, len));
+        return None;
+    }
+    let id: u32 = class_id.into();
+    let last_builtin: u32 = RANGE_CLASS.into();
+    if class_id != OBJECT_CLASS && id <= last_builtin
+        || [METHOD_CLASS, ENUMERATOR_CLASS].contains(&class_id)
+    {
+        let name = globals.get_ident_id("new");
+        globals.err_method_not_found(name);
+        return None;
+    }
+    Some(Value::new_object(class_id))
+}
+
+fn new_exception(globals: &mut Globals, class_id: ClassId, arg: Arg, len: usize) -> Option<Value> {
+    let msg = match len {
+        0 => class_id.get_name(globals),
+        1 if arg[0].is_nil() => class_id.get_name(globals),
+        1 => globals.val_tos(arg[0]),
+        _ => {
+            globals.set_error(MonorubyErr::wrong_arguments_range(0, 1, len));
+            return None;
+        }
+    };
+    let err = MonorubyErr::exception(class_id, msg);
+    Some(Value::new_exception(class_id, err))
 }
 
 /// ### Class#superclass
@@ -26,6 +71,32 @@ extern "C" fn superclass(
     Some(res)
 }
 
+/// ### Module#class_eval
+/// - class_eval {|mod| ... } -> object
+/// - module_eval {|mod| ... } -> object
+///
+/// The block is evaluated with `self` rebound to the receiver, and methods defined in the block
+/// belong to the receiver. Evaluating a String is not supported.
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Module/i/class_eval.html]
+extern "C" fn class_eval(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    let class_obj = arg.self_value();
+    globals.push_class_context(class_obj.as_class());
+    let res = vm.invoke_block_with_self(globals, block, class_obj, &[class_obj]);
+    globals.pop_class_context();
+    res
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -34,4 +105,69 @@ mod test {
     fn test_class() {
         run_test("Time.superclass");
     }
+
+    #[test]
+    fn test_class_new() {
+        run_test("Object.new.class.inspect");
+        run_test(
+            r#"
+            class Foo; end
+            Foo.new.class.inspect
+            "#,
+        );
+        run_test_error(
+            "Object.new(1)",
+            MonorubyErrKind::WrongArguments(String::new()),
+        );
+    }
+
+    #[test]
+    fn test_class_eval() {
+        run_test(
+            r#"
+            class Foo; end
+            Foo.class_eval do
+              def bar
+                7
+              end
+            end
+            Foo.new.bar
+            "#,
+        );
+        run_test(
+            r#"
+            class Foo; end
+            Foo.module_eval { |c| [self, c] }.inspect
+            "#,
+        );
+        run_test_error(
+            r#"
+            class Foo; end
+            Foo.class_eval { def baz; 1; end }
+            baz
+            "#,
+            MonorubyErrKind::MethodNotFound(IdentId::_ADD),
+        );
+    }
+
+    #[test]
+    fn test_exception_new() {
+        run_test("StandardError.new.message");
+        run_test("StandardError.new.class");
+        run_test(r#"RuntimeError.new("foo").message"#);
+        run_test(r#"ArgumentError.new("foo").to_s"#);
+        run_test(
+            r#"
+            class MyError < StandardError; end
+            [MyError.new.message, MyError.new("bar").message].inspect
+            "#,
+        );
+        run_test(
+            r#"begin; raise TypeError.new("baz"); rescue => e; [e.class, e.message].inspect; end"#,
+        );
+        run_test_error(
+            r#"StandardError.new("a", "b")"#,
+            MonorubyErrKind::WrongArguments(String::new()),
+        );
+    }
 }
@@ -0,0 +1,219 @@
+use crate::executor::op::find_user_operator;
+use crate::*;
+use num::ToPrimitive;
+use std::cmp::Ordering;
+
+//
+// Comparable module
+//
+// Since modules are not supported yet, the methods are defined on each numeric class.
+//
+
+pub(super) fn init(globals: &mut Globals) {
+    for class_id in [INTEGER_CLASS, FLOAT_CLASS] {
+        globals.define_builtin_func(class_id, "between?", between, 2);
+        globals.define_builtin_func(class_id, "clamp", clamp, 2);
+        globals.define_builtin_func(class_id, "<=>", cmp, 1);
+    }
+}
+
+/// ### Integer#<=>
+/// - self <=> other -> -1 | 0 | 1 | nil
+///
+/// Float#<=> is the same. Return nil if *other* is not a numeric.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Integer.html#I_--3C--3D--3E]
+extern "C" fn cmp(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let res = match numeric_ord(arg.self_value(), arg[0]) {
+        Some(ord) => Value::new_integer(ord as i64),
+        None => Value::nil(),
+    };
+    Some(res)
+}
+
+/// ### Comparable#clamp
+/// - clamp(min, max) -> object
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Comparable.html#I_CLAMP]
+extern "C" fn clamp(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let (val, min, max) = (arg.self_value(), arg[0], arg[1]);
+    if compare_numeric(globals, min, max)? == Ordering::Greater {
+        globals.err_argument("min argument must be less than or equal to max argument");
+        return None;
+    }
+    if compare_numeric(globals, val, min)? == Ordering::Less {
+        return Some(min);
+    }
+    if compare_numeric(globals, val, max)? == Ordering::Greater {
+        return Some(max);
+    }
+    Some(val)
+}
+
+/// ### Comparable#between?
+/// - between?(min, max) -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Comparable.html#I_BETWEEN--3F]
+extern "C" fn between(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let (val, min, max) = (arg.self_value(), arg[0], arg[1]);
+    let b = compare_numeric(globals, val, min)? != Ordering::Less
+        && compare_numeric(globals, val, max)? != Ordering::Greater;
+    Some(Value::bool(b))
+}
+
+///
+/// Compare *lhs* and *rhs* by `<=>`.
+///
+/// Numerics, strings and symbols are compared here, and `<=>` defined in Ruby is called for
+/// other objects.
+///
+pub(super) fn compare_values(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    lhs: Value,
+    rhs: Value,
+) -> Option<Ordering> {
+    if let Some(ord) = numeric_ord(lhs, rhs) {
+        return Some(ord);
+    }
+    let res = match (lhs.unpack(), rhs.unpack()) {
+        (RV::String(l), RV::String(r)) => return Some(l.cmp(r)),
+        (RV::Symbol(l), RV::Symbol(r)) => {
+            return Some(globals.get_ident_name(l).cmp(globals.get_ident_name(r)))
+        }
+        _ => {
+            let name = globals.get_ident_id("<=>");
+            match find_user_operator(globals, lhs, name) {
+                Some(func_id) => vm.invoke_method(globals, func_id, lhs, &[rhs])?,
+                None => Value::nil(),
+            }
+        }
+    };
+    to_ordering(globals, res, lhs, rhs)
+}
+
+///
+/// Convert *res*, which is the result of `<=>` or a comparison block for *lhs* and *rhs*,
+/// to Ordering.
+///
+/// Raise ArgumentError if *res* is not a numeric, e.g. nil for incomparable values.
+///
+pub(super) fn to_ordering(
+    globals: &mut Globals,
+    res: Value,
+    lhs: Value,
+    rhs: Value,
+) -> Option<Ordering> {
+    let ord = match res.unpack() {
+        RV::Integer(i) => Some(i.cmp(&0)),
+        RV::BigInt(b) => Some(b.sign().cmp(&num::bigint::Sign::NoSign)),
+        RV::Float(f) => f.partial_cmp(&0.0),
+        _ => None,
+    };
+    if ord.is_none() {
+        err_comparison_failed(globals, lhs, rhs);
+    }
+    ord
+}
+
+fn compare_numeric(globals: &mut Globals, lhs: Value, rhs: Value) -> Option<Ordering> {
+    let ord = numeric_ord(lhs, rhs);
+    if ord.is_none() {
+        err_comparison_failed(globals, lhs, rhs);
+    }
+    ord
+}
+
+/// Compare numerics *lhs* and *rhs*. Return None if either of them is not a numeric, or NaN.
+fn numeric_ord(lhs: Value, rhs: Value) -> Option<Ordering> {
+    match (lhs.unpack(), rhs.unpack()) {
+        (RV::Integer(lhs), RV::Integer(rhs)) => Some(lhs.cmp(&rhs)),
+        (RV::Integer(lhs), RV::BigInt(rhs)) => Some(BigInt::from(lhs).cmp(rhs)),
+        (RV::BigInt(lhs), RV::Integer(rhs)) => Some(lhs.cmp(&BigInt::from(rhs))),
+        (RV::BigInt(lhs), RV::BigInt(rhs)) => Some(lhs.cmp(rhs)),
+        (RV::Integer(lhs), RV::Float(rhs)) => (lhs as f64).partial_cmp(&rhs),
+        (RV::Float(lhs), RV::Integer(rhs)) => lhs.partial_cmp(&(rhs as f64)),
+        (RV::BigInt(lhs), RV::Float(rhs)) => lhs.to_f64().unwrap().partial_cmp(&rhs),
+        (RV::Float(lhs), RV::BigInt(rhs)) => lhs.partial_cmp(&rhs.to_f64().unwrap()),
+        (RV::Float(lhs), RV::Float(rhs)) => lhs.partial_cmp(&rhs),
+        _ => None,
+    }
+}
+
+fn err_comparison_failed(globals: &mut Globals, lhs: Value, rhs: Value) {
+    let rhs = match rhs.unpack() {
+        RV::Nil | RV::Bool(_) | RV::Integer(_) | RV::BigInt(_) | RV::Float(_) => {
+            globals.val_inspect(rhs)
+        }
+        _ => rhs.class_id().get_name(globals),
+    };
+    globals.err_argument(&format!(
+        "comparison of {} with {} failed",
+        lhs.class_id().get_name(globals),
+        rhs
+    ));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cmp() {
+        run_test("[1 <=> 2, 2 <=> 2, 3 <=> 2].inspect");
+        run_test("[1.5 <=> 2, 2 <=> 2.0, 100000000000000000000 <=> 2.5].inspect");
+        run_test("(1 <=> nil).inspect");
+        run_test(r#"(1.5 <=> "a").inspect"#);
+        run_test("(0.0 / 0.0 <=> 1).inspect");
+    }
+
+    #[test]
+    fn test_clamp() {
+        run_test("2.5.clamp(1, 2)");
+        run_test("(-2.5).clamp(-1.5, 2)");
+        run_test("15.clamp(1, 10)");
+        run_test("(-3).clamp(0, 5)");
+        run_test("7.clamp(0, 5.5)");
+        run_test("3.clamp(3, 3)");
+        run_test("4611686018427387903.clamp(0, 4611686018427387904)");
+        run_test("100000000000000000000.clamp(0, 4611686018427387903)");
+    }
+
+    #[test]
+    fn test_between() {
+        run_test("5.between?(1, 10)");
+        run_test("5.between?(5, 5)");
+        run_test("0.between?(1, 10)");
+        run_test("11.between?(1, 10)");
+        run_test("5.between?(1.5, 5.5)");
+        run_test("5.5.between?(1, 5)");
+        run_test("2.5.between?(2.5, 3.5)");
+        run_test("100000000000000000000.between?(1, 100000000000000000001)");
+    }
+
+    #[test]
+    fn test_incomparable() {
+        for code in [
+            r#"5.between?(1, "a")"#,
+            "5.between?(:a, 10)",
+            r#"5.clamp(1, "a")"#,
+        ] {
+            run_test_error(code, MonorubyErrKind::Argument(String::new()));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::*;
+
+//
+// Enumerator class
+//
+// Enumerators are buffered. The values to be yielded are collected into an Array
+// when the enumerator is made, and `next` takes them out one by one.
+//
+
+/// The name of the hidden instance variable which holds the values to be yielded.
+const BUFFER_VAR: &str = "/buffer";
+/// The name of the hidden instance variable which holds the position of the next value.
+const POS_VAR: &str = "/pos";
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_func(ENUMERATOR_CLASS, "next", next, 0);
+    globals.define_builtin_func(ENUMERATOR_CLASS, "peek", peek, 0);
+    globals.define_builtin_func(ENUMERATOR_CLASS, "rewind", rewind, 0);
+    globals.define_builtin_func(ENUMERATOR_CLASS, "size", size, 0);
+    globals.define_builtin_func(ENUMERATOR_CLASS, "to_a", to_a, 0);
+}
+
+/// Make an Enumerator which yields *values* in order.
+pub(super) fn new_enumerator(globals: &mut Globals, values: Vec<Value>) -> Value {
+    let e = Value::new_object(ENUMERATOR_CLASS);
+    let buffer = globals.get_ident_id(BUFFER_VAR);
+    e.rvalue_mut().set_var(buffer, Value::new_array(values));
+    set_pos(globals, e, 0);
+    e
+}
+
+fn buffer(globals: &mut Globals, e: Value) -> Value {
+    let name = globals.get_ident_id(BUFFER_VAR);
+    e.rvalue().get_var(name).unwrap()
+}
+
+fn pos(globals: &mut Globals, e: Value) -> usize {
+    let name = globals.get_ident_id(POS_VAR);
+    e.rvalue().get_var(name).unwrap().as_fixnum().unwrap() as usize
+}
+
+fn set_pos(globals: &mut Globals, e: Value, pos: usize) {
+    let name = globals.get_ident_id(POS_VAR);
+    e.rvalue_mut().set_var(name, Value::new_integer(pos as i64));
+}
+
+/// Get the next value of *e*, or raise StopIteration at the end.
+fn peek_value(globals: &mut Globals, e: Value) -> Option<Value> {
+    let pos = pos(globals, e);
+    match buffer(globals, e).as_array().unwrap().get(pos) {
+        Some(v) => Some(*v),
+        None => {
+            let err = MonorubyErr::exception(
+                STOP_ITERATION_CLASS,
+                "iteration reached an end".to_string(),
+            );
+            globals.set_error(err);
+            None
+        }
+    }
+}
+
+/// ### Enumerator#next
+/// - next -> object
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Enumerator.html#I_NEXT]
+extern "C" fn next(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let e = arg.self_value();
+    let v = peek_value(globals, e)?;
+    let pos = pos(globals, e);
+    set_pos(globals, e, pos + 1);
+    Some(v)
+}
+
+/// ### Enumerator#peek
+/// - peek -> object
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Enumerator.html#I_PEEK]
+extern "C" fn peek(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    peek_value(globals, arg.self_value())
+}
+
+/// ### Enumerator#rewind
+/// - rewind -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Enumerator.html#I_REWIND]
+extern "C" fn rewind(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    set_pos(globals, arg.self_value(), 0);
+    Some(arg.self_value())
+}
+
+/// ### Enumerator#size
+/// - size -> Integer
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Enumerator.html#I_SIZE]
+extern "C" fn size(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let len = buffer(globals, arg.self_value()).as_array().unwrap().len();
+    Some(Value::new_integer(len as i64))
+}
+
+/// ### Enumerable#to_a
+/// - to_a -> Array
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Enumerable.html#I_TO_A]
+extern "C" fn to_a(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let ary = buffer(globals, arg.self_value())
+        .as_array()
+        .unwrap()
+        .clone();
+    Some(Value::new_array(ary))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_enumerator() {
+        run_test("[1, 2, 3].each.class");
+        run_test("e = [1, 2, 3].each; [e.next, e.next, e.peek, e.next].inspect");
+        run_test("e = [1, 2, 3].each; [e.size, e.to_a].inspect");
+        run_test("e = [1, 2].each; e.next; e.next; e.rewind; e.next");
+        run_test(
+            r#"
+            e = [1, 2, 3].each
+            $a = 0
+            begin
+              while true
+                $a += e.next
+              end
+            rescue StopIteration => x
+              [$a, x.class, x.message].inspect
+            end
+            "#,
+        );
+        run_test("e = [].each; begin; e.peek; rescue IndexError => x; x.class; end");
+        run_test_error(
+            "Enumerator.new",
+            MonorubyErrKind::MethodNotFound(IdentId::_ADD),
+        );
+    }
+
+    #[test]
+    fn test_loop() {
+        run_test("e = [1, 2, 3].each; $a = 0; loop { $a += e.next }; $a");
+        run_test_error(
+            "loop { nil + 1 }",
+            MonorubyErrKind::MethodNotFound(IdentId::_ADD),
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use crate::*;
+
+//
+// Exception class
+//
+
+pub(super) fn define_classes(globals: &mut Globals) {
+    assert_eq!(
+        EXCEPTION_CLASS,
+        globals.define_class_under_obj("Exception").as_class()
+    );
+    for (class_id, name, super_class) in [
+        (STANDARD_ERROR_CLASS, "StandardError", EXCEPTION_CLASS),
+        (NAME_ERROR_CLASS, "NameError", STANDARD_ERROR_CLASS),
+        (NO_METHOD_ERROR_CLASS, "NoMethodError", NAME_ERROR_CLASS),
+        (ARGUMENT_ERROR_CLASS, "ArgumentError", STANDARD_ERROR_CLASS),
+        (
+            ZERO_DIVISION_ERROR_CLASS,
+            "ZeroDivisionError",
+            STANDARD_ERROR_CLASS,
+        ),
+        (RANGE_ERROR_CLASS, "RangeError", STANDARD_ERROR_CLASS),
+        (TYPE_ERROR_CLASS, "TypeError", STANDARD_ERROR_CLASS),
+        (RUNTIME_ERROR_CLASS, "RuntimeError", STANDARD_ERROR_CLASS),
+        (FROZEN_ERROR_CLASS, "FrozenError", RUNTIME_ERROR_CLASS),
+        (INDEX_ERROR_CLASS, "IndexError", STANDARD_ERROR_CLASS),
+        (
+            LOCAL_JUMP_ERROR_CLASS,
+            "LocalJumpError",
+            STANDARD_ERROR_CLASS,
+        ),
+        (STOP_ITERATION_CLASS, "StopIteration", INDEX_ERROR_CLASS),
+    ] {
+        assert_eq!(class_id, globals.define_class(name, super_class).as_class());
+    }
+}
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_func(EXCEPTION_CLASS, "message", message, 0);
+    globals.define_builtin_func(EXCEPTION_CLASS, "to_s", message, 0);
+}
+
+/// ### Exception#message
+/// - message -> String
+/// - to_s -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Exception.html#I_MESSAGE]
+extern "C" fn message(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let self_val = arg.self_value();
+    let msg = match self_val.as_exception() {
+        Some(err) => err.get_error_message(globals),
+        None => {
+            let err = MonorubyErr::typeerr(format!(
+                "{} is not an exception",
+                globals.val_inspect(self_val)
+            ));
+            globals.set_error(err);
+            return None;
+        }
+    };
+    Some(Value::new_string(msg.into_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_exception_message() {
+        run_test("begin; 1 / 0; rescue => e; e.message; end");
+        run_test("begin; 1 / 0; rescue => e; e.to_s; end");
+        run_test("begin; 1 / 0; rescue => e; e.class; end");
+        run_test("begin; 1 / 0; rescue => e; e.inspect; end");
+        run_test(r#"begin; 1 + "a"; rescue => e; [e.class, e.message].inspect; end"#);
+        run_test("begin; [1].fetch_nothing; rescue => e; e.class; end");
+        run_test("ZeroDivisionError.superclass");
+        run_test("NoMethodError.superclass.superclass");
+    }
+}
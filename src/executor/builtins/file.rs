@@ -6,6 +6,7 @@ use crate::*;
 
 pub(super) fn init(globals: &mut Globals, class_id: ClassId) {
     globals.define_builtin_singleton_func(class_id, "write", write, 2);
+    globals.define_builtin_func(class_id, "read", read, 0);
 }
 
 /// ### File.write
@@ -31,3 +32,26 @@ extern "C" fn write(
     file.write_all(&bytes).unwrap();
     Some(Value::new_integer(bytes.len() as i64))
 }
+
+/// ### IO#read
+/// - read -> String
+///
+/// Only the data section of a script (`DATA`) can be read for now.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/IO.html#I_READ]
+extern "C" fn read(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    match &mut arg.self_value().rvalue_mut().kind {
+        ObjKind::Bytes(bytes) => Some(Value::new_string(std::mem::take(bytes))),
+        _ => {
+            globals.set_error(MonorubyErr::unimplemented(
+                "only DATA can be read for now".to_string(),
+            ));
+            None
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::*;
+
+//
+// Float class
+//
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_func(FLOAT_CLASS, "to_s", to_s, 0);
+    globals.define_builtin_func(FLOAT_CLASS, "inspect", to_s, 0);
+    globals.set_class_constant(FLOAT_CLASS, "INFINITY", Value::new_float(f64::INFINITY));
+    globals.set_class_constant(FLOAT_CLASS, "NAN", Value::new_float(f64::NAN));
+}
+
+/// ### Float#to_s
+/// - to_s -> String
+/// - inspect -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Float.html#I_TO_S]
+extern "C" fn to_s(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let s = globals.val_tos(arg.self_value());
+    Some(Value::new_string(s.into_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_float_to_s() {
+        run_test("1.0.to_s");
+        run_test("0.1.to_s");
+        run_test("(0.1 + 0.2).to_s");
+        run_test("-2.5.inspect");
+        run_test("1e20.to_s");
+        run_test("1e15.to_s");
+        run_test("1e16.to_s");
+        run_test("123456789.125.to_s");
+        run_test("0.0001.to_s");
+        run_test("0.00001.to_s");
+        run_test("-1.5e-7.to_s");
+        run_test("1.7976931348623157e308.to_s");
+        run_test("(1.0 / 0).to_s");
+        run_test("(-1.0 / 0).to_s");
+        run_test("(0.0 / 0.0).to_s");
+        run_test("-0.0.to_s");
+        run_test(r##""#{2.5}""##);
+    }
+
+    #[test]
+    fn test_float_constants() {
+        run_test("Float::INFINITY");
+        run_test("Float::INFINITY.to_s");
+        run_test("Float::NAN.to_s");
+        run_test("1.0 / 0.0 == Float::INFINITY");
+        run_test("1 / 0.0 == Float::INFINITY");
+        run_test("(-1.0 / 0) == 0 - Float::INFINITY");
+        run_test("Float::INFINITY > 1.7976931348623157e308");
+        run_test("Float::INFINITY + 1 == Float::INFINITY");
+        run_test("(Float::INFINITY - Float::INFINITY).to_s");
+        run_test("(Float::INFINITY * 0).to_s");
+        run_test("(Float::NAN + 1).to_s");
+        run_test("(Float::NAN * 2.5).to_s");
+        run_test("Float::NAN == Float::NAN");
+        run_test("Float::NAN != Float::NAN");
+        run_test("Float::NAN < 1.0");
+        run_test("Float::NAN >= 1");
+        run_test("x = Float::NAN; x == x");
+    }
+
+    #[test]
+    fn test_float_constants_error() {
+        for code in ["Float::FOO", "Bar::Baz"] {
+            run_test_error(code, MonorubyErrKind::UninitConst(IdentId::_ADD));
+        }
+        run_test_error("Foo = 1; Foo::Bar", MonorubyErrKind::Type(String::new()));
+    }
+}
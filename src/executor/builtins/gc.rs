@@ -0,0 +1,100 @@
+use crate::*;
+
+//
+// GC class
+//
+
+pub(super) fn init(globals: &mut Globals, class_id: ClassId) {
+    globals.define_builtin_singleton_func(class_id, "disable", disable, 0);
+    globals.define_builtin_singleton_func(class_id, "enable", enable, 0);
+    globals.define_builtin_singleton_func(class_id, "count", count, 0);
+}
+
+/// ### GC.disable
+/// - disable -> bool
+///
+/// Return true if GC was already disabled.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/GC.html#S_DISABLE]
+extern "C" fn disable(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    _arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let disabled = ALLOC.with(|alloc| alloc.borrow_mut().set_gc_enabled(false));
+    Some(Value::bool(disabled))
+}
+
+/// ### GC.enable
+/// - enable -> bool
+///
+/// Return true if GC was disabled.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/GC.html#S_ENABLE]
+extern "C" fn enable(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    _arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let disabled = ALLOC.with(|alloc| alloc.borrow_mut().set_gc_enabled(true));
+    Some(Value::bool(disabled))
+}
+
+/// ### GC.count
+/// - count -> Integer
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/GC.html#S_COUNT]
+extern "C" fn count(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    _arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let count = ALLOC.with(|alloc| alloc.borrow().count());
+    Some(Value::new_integer(count as i64))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct EmptyRoot;
+
+    impl GC<RValue> for EmptyRoot {
+        fn mark(&self, _alloc: &mut Allocator<RValue>) {}
+    }
+
+    impl GCRoot<RValue> for EmptyRoot {
+        fn startup_flag(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_gc_disable() {
+        run_test("a = GC.disable; GC.enable; a");
+        run_test("GC.disable; a = GC.disable; GC.enable; a");
+        run_test("GC.disable; GC.enable");
+        run_test("GC.enable");
+        run_test("GC.count.class");
+    }
+
+    #[test]
+    fn test_gc_disable_prevents_collection() {
+        let mut alloc = Allocator::<RValue>::new();
+        assert!(!alloc.set_gc_enabled(false));
+        for i in 0..4000 {
+            alloc.alloc(RValue::new_float(i as f64));
+        }
+        alloc.check_gc(&EmptyRoot);
+        assert_eq!(0, alloc.count());
+        assert_eq!(0, alloc.free_count());
+
+        assert!(alloc.set_gc_enabled(true));
+        alloc.check_gc(&EmptyRoot);
+        assert_eq!(1, alloc.count());
+        assert_eq!(4000, alloc.free_count());
+    }
+}
@@ -0,0 +1,342 @@
+use crate::executor::op::{
+    dig_values, hash_get, hash_insert, HASH_DEFAULT_PROC_VAR, HASH_DEFAULT_VAR,
+};
+use crate::*;
+
+//
+// Hash class
+//
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_singleton_func(HASH_CLASS, "new", new, -1);
+    globals.define_builtin_func(HASH_CLASS, "dig", dig, -1);
+    globals.define_builtin_func(HASH_CLASS, "merge", merge, -1);
+    globals.define_builtin_func(HASH_CLASS, "merge!", merge_, -1);
+    globals.define_builtin_func(HASH_CLASS, "update", merge_, -1);
+    globals.define_builtin_func(HASH_CLASS, "each", each, 0);
+    globals.define_builtin_func(HASH_CLASS, "each_pair", each, 0);
+    globals.define_builtin_func(HASH_CLASS, "map", map, 0);
+    globals.define_builtin_func(HASH_CLASS, "select", select, 0);
+    globals.define_builtin_func(HASH_CLASS, "filter", select, 0);
+    globals.define_builtin_func(HASH_CLASS, "reject", reject, 0);
+    globals.define_builtin_func(HASH_CLASS, "transform_values", transform_values, 0);
+}
+
+/// ### Hash.new
+/// - new(ifnone = nil) -> Hash
+/// - new {|hash, key| ... } -> Hash
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Hash/s/new.html]
+extern "C" fn new(_vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let block = arg.block(len);
+    if len > 1 || (len == 1 && !block.is_nil()) {
+        let max = if block.is_nil() { 1 } else { 0 };
+        globals.set_error(MonorubyErr::wrong_arguments_range(0, max, len));
+        return None;
+    }
+    let hash = Value::new_hash(HashTable::default());
+    if !block.is_nil() {
+        let name = globals.get_ident_id(HASH_DEFAULT_PROC_VAR);
+        hash.rvalue_mut().set_var(name, block);
+    } else if len == 1 {
+        let name = globals.get_ident_id(HASH_DEFAULT_VAR);
+        hash.rvalue_mut().set_var(name, arg[0]);
+    }
+    Some(hash)
+}
+
+/// ### Hash#dig
+/// - dig(key, *rest) -> object | nil
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Hash/i/dig.html]
+extern "C" fn dig(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    if len == 0 {
+        globals.set_error(MonorubyErr::wrong_arguments_min(1, len));
+        return None;
+    }
+    let keys: Vec<Value> = (0..len).map(|i| arg[i]).collect();
+    dig_values(vm, globals, arg.self_value(), &keys)
+}
+
+/// ### Hash#merge
+/// - merge(*others) -> Hash
+/// - merge(*others) {|key, self_val, other_val| ... } -> Hash
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Hash/i/merge.html]
+extern "C" fn merge(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let map = arg.self_value().as_hash().unwrap().clone();
+    let target = Value::new_hash(map);
+    let others: Vec<Value> = (0..len).map(|i| arg[i]).collect();
+    merge_into(vm, globals, target, &others, arg.block(len))?;
+    Some(target)
+}
+
+/// ### Hash#merge!
+/// - merge!(*others) -> self
+/// - merge!(*others) {|key, self_val, other_val| ... } -> self
+/// - update(*others) -> self
+/// - update(*others) {|key, self_val, other_val| ... } -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Hash/i/merge=21.html]
+extern "C" fn merge_(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let self_val = arg.self_value();
+    if self_val.is_frozen() {
+        globals.err_frozen(self_val);
+        return None;
+    }
+    let others: Vec<Value> = (0..len).map(|i| arg[i]).collect();
+    merge_into(vm, globals, self_val, &others, arg.block(len))?;
+    Some(self_val)
+}
+
+///
+/// Merge the Hashes *others* into the Hash *target* in order.
+///
+/// If *block* is not nil, the value of a key in both is decided by `block.call(key, old, new)`.
+///
+fn merge_into(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    target: Value,
+    others: &[Value],
+    block: Value,
+) -> Option<()> {
+    for other in others {
+        let entries: Vec<(Value, Value)> = match other.as_hash() {
+            Some(map) => map.iter().map(|(k, v)| (k.0, *v)).collect(),
+            None => {
+                globals.err_no_implict_conv(other.class_id(), HASH_CLASS);
+                return None;
+            }
+        };
+        for (k, mut v) in entries {
+            if !block.is_nil() {
+                if let Some(old) = hash_get(vm, globals, target.as_hash().unwrap(), k)? {
+                    v = vm.invoke_block(globals, block, &[k, old, v])?;
+                }
+            }
+            hash_insert(vm, globals, target.as_hash_mut().unwrap(), k, v)?;
+        }
+    }
+    Some(())
+}
+
+/// ### Hash#each
+/// - each {|key, value| ... } -> self
+/// - each_pair {|key, value| ... } -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Hash/i/each.html]
+extern "C" fn each(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let block = expect_block(globals, arg, len)?;
+    for (k, val) in entries(arg.self_value()) {
+        vm.invoke_block(globals, block, &[Value::new_array(vec![k.0, val])])?;
+    }
+    Some(arg.self_value())
+}
+
+/// ### Hash#map
+/// - map {|key, value| ... } -> [object]
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Enumerable/i/map.html]
+extern "C" fn map(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let block = expect_block(globals, arg, len)?;
+    let mut v = vec![];
+    for (k, val) in entries(arg.self_value()) {
+        v.push(vm.invoke_block(globals, block, &[Value::new_array(vec![k.0, val])])?);
+    }
+    Some(Value::new_array(v))
+}
+
+/// ### Hash#select
+/// - select {|key, value| ... } -> Hash
+/// - filter {|key, value| ... } -> Hash
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Hash/i/filter.html]
+extern "C" fn select(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    filter_entries(vm, globals, arg, len, true)
+}
+
+/// ### Hash#reject
+/// - reject {|key, value| ... } -> Hash
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Hash/i/reject.html]
+extern "C" fn reject(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    filter_entries(vm, globals, arg, len, false)
+}
+
+/// ### Hash#transform_values
+/// - transform_values {|value| ... } -> Hash
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Hash/i/transform_values.html]
+extern "C" fn transform_values(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let block = expect_block(globals, arg, len)?;
+    let mut map = HashTable::default();
+    for (k, val) in entries(arg.self_value()) {
+        map.insert(k, vm.invoke_block(globals, block, &[val])?);
+    }
+    Some(Value::new_hash(map))
+}
+
+fn expect_block(globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    Some(block)
+}
+
+/// Copy the entries of the Hash *hash*, so that blocks can modify it while iterating.
+fn entries(hash: Value) -> Vec<(HashKey, Value)> {
+    hash.as_hash()
+        .unwrap()
+        .iter()
+        .map(|(k, v)| (*k, *v))
+        .collect()
+}
+
+/// Make a Hash of the entries for which the block returns *keep*.
+fn filter_entries(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+    keep: bool,
+) -> Option<Value> {
+    let block = expect_block(globals, arg, len)?;
+    let mut map = HashTable::default();
+    for (k, val) in entries(arg.self_value()) {
+        if vm.invoke_block(globals, block, &[k.0, val])?.to_bool() == keep {
+            map.insert(k, val);
+        }
+    }
+    Some(Value::new_hash(map))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hash_dig() {
+        run_test("{a: {b: [1, 2]}}.dig(:a, :b, 1)");
+        run_test("{a: 1}.dig(:b, :c).inspect");
+        run_test(r#"{"k" => [5, 6]}.dig("k", 0)"#);
+        run_test("{1 => [[7]]}.dig(1, 0, 0)");
+        run_test_error("{a: 1}.dig(:a, :b)", MonorubyErrKind::Type(String::new()));
+    }
+
+    #[test]
+    fn test_hash_merge() {
+        run_test("{a: 1, b: 2}.merge({b: 3, c: 4}) == {a: 1, b: 3, c: 4}");
+        run_test("{a: 1}.merge({b: 2}, {a: 3}) == {a: 3, b: 2}");
+        run_test("{a: 1}.merge == {a: 1}");
+        run_test("h = {a: 1}; h.merge({a: 2}); h[:a]");
+        run_test("h = {a: 1}; h.merge({a: 2}).equal?(h)");
+        run_test("{a: 1, b: 2}.merge({b: 3, c: 4}) { |k, o, n| o + n } == {a: 1, b: 5, c: 4}");
+        run_test("{a: 1}.merge({a: 2}) { |k, o, n| o * 10 + n }[:a]");
+        run_test("{a: 1}.merge({a: 2}) { |k, o, n| k }[:a] == :a");
+        run_test("$c = 0; {a: 1}.merge({b: 2}) { |k, o, n| $c += 1 }; $c");
+    }
+
+    #[test]
+    fn test_hash_merge_() {
+        run_test("h = {a: 1}; h.merge!({a: 2, b: 3}); h == {a: 2, b: 3}");
+        run_test("h = {a: 1}; h.merge!({b: 2}).equal?(h)");
+        run_test("h = {a: 1}; h.update({a: 2}) { |k, o, n| o - n }.equal?(h)");
+        run_test("h = {a: 1}; h.update({a: 2}) { |k, o, n| o - n }; h[:a]");
+        run_test("h = {a: 1, b: 2}; h.merge!({b: 10}, {b: 20}) { |k, o, n| o + n }; h[:b]");
+        run_test_error("{a: 1}.merge(1)", MonorubyErrKind::Type(String::new()));
+        run_test_error(
+            "{a: 1}.freeze.merge!({})",
+            MonorubyErrKind::Frozen(String::new()),
+        );
+    }
+
+    #[test]
+    fn test_hash_map() {
+        run_test("{a: 1, b: 2}.map { |k, v| [k, v * 2] }.inspect");
+        run_test("{a: 1, b: 2}.map { |pair| pair }.inspect");
+        run_test("{}.map { |k, v| k }.inspect");
+        run_test(r#"{"x" => 1, "y" => 2}.map { |k, v| k + "!" }.inspect"#);
+    }
+
+    #[test]
+    fn test_hash_select_reject() {
+        run_test("{a: 1, b: 2, c: 3}.select { |k, v| v != 2 } == {a: 1, c: 3}");
+        run_test("{a: 1, b: 2, c: 3}.filter { |k, v| k == :b } == {b: 2}");
+        run_test("{a: 1, b: 2, c: 3}.reject { |k, v| v != 2 } == {b: 2}");
+        run_test("{a: 1, b: 2}.reject { |k, v| false } == {a: 1, b: 2}");
+        run_test("{c: 3, a: 1, b: 2}.select { |k, v| v > 1 }.map { |k, v| k }.inspect");
+        run_test("h = {a: 1}; h.select { |k, v| true }.equal?(h)");
+    }
+
+    #[test]
+    fn test_hash_transform_values() {
+        run_test("{a: 1, b: 2}.transform_values { |v| v * 2 } == {a: 2, b: 4}");
+        run_test("{b: 1, a: 2}.transform_values { |v| v * 10 }.map { |k, v| k }.inspect");
+        run_test(r#"{"k" => 1}.transform_values { |v| v + 0.5 }["k"]"#);
+        run_test("h = {a: 1}; h.transform_values { |v| v * 2 }; h[:a]");
+    }
+
+    #[test]
+    fn test_hash_each() {
+        run_test("$s = 0; {a: 1, b: 2}.each { |k, v| $s += v }; $s");
+        run_test("$a = []; $i = 0; {a: 1, b: 2}.each { |k, v| $a[$i] = k; $i += 1 }; $a.inspect");
+        run_test(
+            "$a = []; $i = 0; {a: 1, b: 2}.each_pair { |pair| $a[$i] = pair; $i += 1 }; $a.inspect",
+        );
+        run_test("h = {a: 1}; h.each { |k, v| }.equal?(h)");
+    }
+
+    #[test]
+    fn test_hash_new() {
+        run_test("Hash.new == {}");
+        run_test("Hash.new[:a].inspect");
+        run_test("Hash.new(5)[:x]");
+        run_test("h = Hash.new(5); h[:x]; h == {}");
+        run_test("h = Hash.new(5); h[:x] = 1; h[:x] + h[:y]");
+        run_test("h = Hash.new(0); h[:a] = h[:a] + 1; h[:a] = h[:a] + 1; h[:b] = h[:b] + 1; h == {a: 2, b: 1}");
+        run_test(r#"h = Hash.new("x"); h[1].equal?(h[2])"#);
+        run_test("Hash.new(1).dig(:a)");
+    }
+
+    #[test]
+    fn test_hash_new_block() {
+        run_test("h = Hash.new { |hash, k| hash[k] = k * 2 }; h[3] + h[4]");
+        run_test("h = Hash.new { |hash, k| hash[k] = k * 2 }; h[3]; h == {3 => 6}");
+        run_test("h = Hash.new { |hash, k| k }; h[1]; h == {}");
+        run_test("h = Hash.new { |hash, k| k * 10 }; h[1] = 5; h[1] + h[2]");
+        run_test(
+            r#"
+            fib = Hash.new { |h, n| h[n] = n < 2 ? n : h[n - 1] + h[n - 2] }
+            fib[80]
+            "#,
+        );
+        run_test_error(
+            "Hash.new(1) { |h, k| k }",
+            MonorubyErrKind::Argument(String::new()),
+        );
+        run_test_error("Hash.new(1, 2)", MonorubyErrKind::Argument(String::new()));
+    }
+}
@@ -1,4 +1,6 @@
+use super::enumerator::new_enumerator;
 use crate::*;
+use num::{BigInt, Integer, Signed, Zero};
 
 //
 // Integer class
@@ -6,6 +8,11 @@ use crate::*;
 
 pub(super) fn init(globals: &mut Globals) {
     globals.define_builtin_func(INTEGER_CLASS, "chr", chr, 0);
+    globals.define_builtin_func(INTEGER_CLASS, "ceildiv", ceildiv, 1);
+    globals.define_builtin_func(INTEGER_CLASS, "size", size, 0);
+    globals.define_builtin_func(INTEGER_CLASS, "times", times, 0);
+    globals.define_builtin_func(INTEGER_CLASS, "bit_count", bit_count, 0);
+    globals.define_builtin_func(INTEGER_CLASS, "popcount", bit_count, 0);
 }
 
 /// ### Integer#chr
@@ -25,3 +32,196 @@ extern "C" fn chr(_vm: &mut Interp, globals: &mut Globals, arg: Arg, _len: usize
     globals.err_char_out_of_range(arg.self_value());
     return None;
 }
+
+/// ### Integer#ceildiv
+/// - ceildiv(other) -> Integer
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Integer.html#I_CEILDIV]
+extern "C" fn ceildiv(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let (lhs, rhs) = (arg.self_value(), arg[0]);
+    let (lhs, rhs) = match (lhs.unpack(), rhs.unpack()) {
+        (RV::Integer(lhs), RV::Integer(rhs)) => {
+            if rhs.is_zero() {
+                globals.err_divide_by_zero();
+                return None;
+            }
+            // i64::MIN.ceildiv(-1) overflows, and falls back to BigInt.
+            if lhs.checked_div(rhs).is_some() {
+                return Some(Value::new_integer(lhs.div_ceil(rhs)));
+            }
+            (BigInt::from(lhs), BigInt::from(rhs))
+        }
+        (RV::Integer(lhs), RV::BigInt(rhs)) => (BigInt::from(lhs), rhs.clone()),
+        (RV::BigInt(lhs), RV::Integer(rhs)) => (lhs.clone(), BigInt::from(rhs)),
+        (RV::BigInt(lhs), RV::BigInt(rhs)) => (lhs.clone(), rhs.clone()),
+        _ => {
+            globals.err_cant_coerce(rhs, INTEGER_CLASS);
+            return None;
+        }
+    };
+    if rhs.is_zero() {
+        globals.err_divide_by_zero();
+        return None;
+    }
+    Some(Value::new_bigint(-(-lhs).div_floor(&rhs)))
+}
+
+/// ### Integer#size
+/// - size -> Integer
+///
+/// Bignums are measured in 64-bit words, as in CRuby on 64-bit platforms.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Integer.html#I_SIZE]
+extern "C" fn size(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let size = match arg.self_value().unpack() {
+        RV::BigInt(b) => b.bits().div_ceil(64) * 8,
+        _ => 8,
+    };
+    Some(Value::new_integer(size as i64))
+}
+
+/// ### Integer#times
+/// - times {|n| ... } -> self
+/// - times -> Enumerator
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Integer.html#I_TIMES]
+extern "C" fn times(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let count = match arg.self_value().unpack() {
+        RV::Integer(i) => i,
+        RV::BigInt(b) if b.is_negative() => 0,
+        // practically never finishes.
+        _ => i64::MAX,
+    };
+    let block = arg.block(len);
+    if block.is_nil() {
+        // the values of the buffered enumerator are allocated at once.
+        let mut values = vec![];
+        if values.try_reserve(count.max(0) as usize).is_err() {
+            globals.set_error(MonorubyErr::range(format!(
+                "{} times is too many to enumerate",
+                globals.val_tos(arg.self_value())
+            )));
+            return None;
+        }
+        values.extend((0..count).map(Value::new_integer));
+        return Some(new_enumerator(globals, values));
+    }
+    for i in 0..count {
+        vm.invoke_block(globals, block, &[Value::new_integer(i)])?;
+    }
+    Some(arg.self_value())
+}
+
+/// ### Integer#bit_count
+/// - bit_count -> Integer
+/// - popcount -> Integer
+///
+/// Return the number of set bits in the absolute value of *self*. This method is not in CRuby.
+extern "C" fn bit_count(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let count = match arg.self_value().unpack() {
+        RV::Integer(i) => i.unsigned_abs().count_ones() as i64,
+        RV::BigInt(b) => b.iter_u64_digits().map(|d| d.count_ones() as i64).sum(),
+        _ => unreachable!(),
+    };
+    Some(Value::new_integer(count))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_size() {
+        run_test("1.size");
+        run_test("(-1).size");
+        run_test("4611686018427387903.size");
+        run_test("18446744073709551616.size");
+        run_test("(-18446744073709551616).size");
+        run_test("340282366920938463463374607431768211456.size");
+    }
+
+    #[test]
+    fn test_times() {
+        run_test("$sum = 0; 5.times { |i| $sum += i }; $sum");
+        run_test("5.times { |i| i * 2 }");
+        run_test("$c = 0; 3.times { $c += 1 }; $c");
+        run_test("$c = 0; 3.times { |i, j| $c += i; j }; $c");
+        run_test("$c = 0; 0.times { |i| $c += 1 }; (-3).times { |i| $c += 1 }; $c");
+        run_test("3.times.class");
+        run_test("3.times.to_a.inspect");
+        run_test("(-1).times.to_a.inspect");
+        run_test(
+            r#"
+            e = 3.times
+            a = [e.next, e.next, e.next]
+            begin
+              e.next
+            rescue StopIteration => x
+              [a, x.class].inspect
+            end
+            "#,
+        );
+        run_test_error(
+            "18446744073709551616.times",
+            MonorubyErrKind::Range(String::new()),
+        );
+        run_test("$s = 0; 3.times { |i| 4.times { |j| $s += i * j } }; $s");
+        run_test(
+            r#"
+            def each_twice; 2.times { |i| yield i }; end
+            $t = 0
+            each_twice { |x| $t += x + 1 }
+            $t
+            "#,
+        );
+        run_test(
+            r#"
+            def f(n); $t = 0; n.times { |i| $t += i }; $t; end
+            f(10) + f(100)
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_bit_count() {
+        run_test_inspect("7.bit_count", "3");
+        run_test_inspect("0.bit_count", "0");
+        run_test_inspect("(-7).popcount", "3");
+        run_test_inspect("(-9223372036854775808).bit_count", "1");
+        run_test_inspect("4611686018427387903.bit_count", "62");
+        // 2 ** 100 + 2 ** 64 + 1
+        run_test_inspect(
+            "(1267650600228229401496703205376 + 18446744073709551616 + 1).bit_count",
+            "3",
+        );
+        // 2 ** 128 - 1
+        run_test_inspect("340282366920938463463374607431768211455.bit_count", "128");
+    }
+
+    #[test]
+    fn test_ceildiv() {
+        run_test("7.ceildiv(2)");
+        run_test("(-7).ceildiv(2)");
+        run_test("7.ceildiv(-2)");
+        run_test("(-7).ceildiv(-2)");
+        run_test("6.ceildiv(3)");
+        run_test("100000000000000000000.ceildiv(3)");
+        run_test("(-100000000000000000000).ceildiv(7)");
+        run_test("5.ceildiv(100000000000000000000)");
+    }
+}
@@ -0,0 +1,269 @@
+use crate::*;
+use num::BigInt;
+
+//
+// Marshal class
+//
+// Values are serialized in a simple tagged format, which is not compatible with CRuby's.
+// Each value starts with a tag byte, and lengths and numbers are in little endian.
+//
+
+const TAG_NIL: u8 = b'0';
+const TAG_TRUE: u8 = b'T';
+const TAG_FALSE: u8 = b'F';
+const TAG_INTEGER: u8 = b'i';
+const TAG_BIGNUM: u8 = b'l';
+const TAG_FLOAT: u8 = b'f';
+const TAG_STRING: u8 = b'"';
+const TAG_SYMBOL: u8 = b':';
+const TAG_ARRAY: u8 = b'[';
+const TAG_HASH: u8 = b'{';
+
+pub(super) fn init(globals: &mut Globals, class_id: ClassId) {
+    globals.define_builtin_singleton_func(class_id, "dump", dump, 1);
+    globals.define_builtin_singleton_func(class_id, "load", load, 1);
+}
+
+/// ### Marshal.dump
+/// - dump(obj) -> String
+///
+/// Only nil, booleans, integers, floats, strings, symbols, arrays and hashes are supported.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Marshal.html#M_DUMP]
+extern "C" fn dump(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let mut buf = vec![];
+    dump_value(globals, &mut buf, &mut vec![], arg[0])?;
+    Some(Value::new_string(buf))
+}
+
+/// ### Marshal.load
+/// - load(port) -> object
+///
+/// Only a String which is made by `Marshal.dump` is supported as *port*.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Marshal.html#M_LOAD]
+extern "C" fn load(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let bytes = match arg[0].unpack() {
+        RV::String(b) => b.clone(),
+        _ => {
+            globals.err_no_implict_conv(arg[0].class_id(), STRING_CLASS);
+            return None;
+        }
+    };
+    let mut reader = Reader {
+        bytes: &bytes,
+        pos: 0,
+    };
+    let val = match reader.read_value(globals) {
+        Some(val) if reader.pos == bytes.len() => val,
+        _ => {
+            globals.set_error(MonorubyErr::argumenterr(
+                "marshal data is broken".to_string(),
+            ));
+            return None;
+        }
+    };
+    Some(val)
+}
+
+fn dump_len(buf: &mut Vec<u8>, len: usize) {
+    buf.extend_from_slice(&(len as u32).to_le_bytes());
+}
+
+fn dump_bytes(buf: &mut Vec<u8>, tag: u8, bytes: &[u8]) {
+    buf.push(tag);
+    dump_len(buf, bytes.len());
+    buf.extend_from_slice(bytes);
+}
+
+/// Serialize *val* into *buf*. *parents* holds the arrays and hashes being serialized,
+/// which are used to detect cycles.
+fn dump_value(
+    globals: &mut Globals,
+    buf: &mut Vec<u8>,
+    parents: &mut Vec<u64>,
+    val: Value,
+) -> Option<()> {
+    match val.unpack() {
+        RV::Nil => buf.push(TAG_NIL),
+        RV::Bool(true) => buf.push(TAG_TRUE),
+        RV::Bool(false) => buf.push(TAG_FALSE),
+        RV::Integer(i) => {
+            buf.push(TAG_INTEGER);
+            buf.extend_from_slice(&i.to_le_bytes());
+        }
+        RV::BigInt(b) => dump_bytes(buf, TAG_BIGNUM, &b.to_signed_bytes_le()),
+        RV::Float(f) => {
+            buf.push(TAG_FLOAT);
+            buf.extend_from_slice(&f.to_le_bytes());
+        }
+        RV::String(b) => dump_bytes(buf, TAG_STRING, b),
+        RV::Symbol(id) => {
+            let name = globals.get_ident_name(id).as_bytes().to_vec();
+            dump_bytes(buf, TAG_SYMBOL, &name);
+        }
+        RV::Object(rvalue) => {
+            if parents.contains(&val.get()) {
+                globals.set_error(MonorubyErr::argumenterr(
+                    "can't dump a cyclic structure".to_string(),
+                ));
+                return None;
+            }
+            parents.push(val.get());
+            match &rvalue.kind {
+                ObjKind::Array(v) => {
+                    buf.push(TAG_ARRAY);
+                    dump_len(buf, v.len());
+                    for elem in v.iter() {
+                        dump_value(globals, buf, parents, *elem)?;
+                    }
+                }
+                ObjKind::Hash(map) => {
+                    buf.push(TAG_HASH);
+                    dump_len(buf, map.len());
+                    for (k, v) in map.iter() {
+                        dump_value(globals, buf, parents, k.0)?;
+                        dump_value(globals, buf, parents, *v)?;
+                    }
+                }
+                _ => {
+                    let class_name = val.class_id().get_name(globals);
+                    globals.set_error(MonorubyErr::typeerr(format!(
+                        "no _dump_data is defined for class {}",
+                        class_name
+                    )));
+                    return None;
+                }
+            }
+            parents.pop();
+        }
+    }
+    Some(())
+}
+
+/// A cursor on serialized bytes. Each method returns None if the bytes are broken.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        Some(self.read_bytes(1)?[0])
+    }
+
+    fn read_u64(&mut self) -> Option<[u8; 8]> {
+        self.read_bytes(8)?.try_into().ok()
+    }
+
+    fn read_len(&mut self) -> Option<usize> {
+        let len = u32::from_le_bytes(self.read_bytes(4)?.try_into().ok()?);
+        Some(len as usize)
+    }
+
+    fn read_chunk(&mut self) -> Option<&'a [u8]> {
+        let len = self.read_len()?;
+        self.read_bytes(len)
+    }
+
+    fn read_value(&mut self, globals: &mut Globals) -> Option<Value> {
+        let val = match self.read_u8()? {
+            TAG_NIL => Value::nil(),
+            TAG_TRUE => Value::bool(true),
+            TAG_FALSE => Value::bool(false),
+            TAG_INTEGER => Value::new_integer(i64::from_le_bytes(self.read_u64()?)),
+            TAG_BIGNUM => Value::new_bigint(BigInt::from_signed_bytes_le(self.read_chunk()?)),
+            TAG_FLOAT => Value::new_float(f64::from_le_bytes(self.read_u64()?)),
+            TAG_STRING => Value::new_string(self.read_chunk()?.to_vec()),
+            TAG_SYMBOL => {
+                let name = std::str::from_utf8(self.read_chunk()?).ok()?;
+                Value::new_symbol(globals.get_ident_id(name))
+            }
+            TAG_ARRAY => {
+                let len = self.read_len()?;
+                let mut v = vec![];
+                for _ in 0..len {
+                    v.push(self.read_value(globals)?);
+                }
+                Value::new_array(v)
+            }
+            TAG_HASH => {
+                let len = self.read_len()?;
+                let mut map = HashTable::default();
+                for _ in 0..len {
+                    let k = self.read_value(globals)?;
+                    // String keys are frozen as Ruby does.
+                    if let RV::String(_) = k.unpack() {
+                        k.freeze();
+                    }
+                    let v = self.read_value(globals)?;
+                    map.insert(HashKey::new(k), v);
+                }
+                Value::new_hash(map)
+            }
+            _ => return None,
+        };
+        Some(val)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_marshal() {
+        run_test(
+            r#"
+            a = [1, -2.5, "str", :sym, nil, true, false, {1 => [2, "x"], "k" => :v}, 1180591620717411303424, -1180591620717411303424, []]
+            Marshal.load(Marshal.dump(a)) == a
+            "#,
+        );
+        run_test(r#"h = {"a" => [1, {b: 2.0}]}; Marshal.load(Marshal.dump(h)) == h"#);
+        run_test("Marshal.load(Marshal.dump(18446744073709551616)) == 18446744073709551616");
+        run_test("Marshal.load(Marshal.dump(:hello)).equal?(:hello)");
+        run_test(
+            r#"
+            a = [[1, "s"], {1 => [2]}]
+            b = Marshal.load(Marshal.dump(a))
+            b[0][0] = 3
+            b[1][1][0] = 4
+            [a, b] == [[[1, "s"], {1 => [2]}], [[3, "s"], {1 => [4]}]]
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_marshal_error() {
+        for code in [
+            "Marshal.dump(Object.new)",
+            "Marshal.dump(1..2)",
+            "Marshal.load(1)",
+        ] {
+            run_test_error(code, MonorubyErrKind::Type(String::new()));
+        }
+        for code in [
+            "a = [1]; a[1] = a; Marshal.dump(a)",
+            r#"Marshal.load("[")"#,
+            r#"Marshal.load(Marshal.dump([1]) + "0")"#,
+        ] {
+            run_test_error(code, MonorubyErrKind::Argument(String::new()));
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::*;
+
+//
+// Method class
+//
+// A Method object is a Proc whose class is changed to Method. Its body is the method,
+// and `self` of the block is the receiver.
+//
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_func(OBJECT_CLASS, "method", method, 1);
+    globals.define_builtin_func(METHOD_CLASS, "call", call, -1);
+    globals.define_builtin_func(METHOD_CLASS, "arity", arity, 0);
+    globals.define_builtin_func(METHOD_CLASS, "to_proc", to_proc, 0);
+}
+
+/// ### Object#method
+/// - method(name) -> Method
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_METHOD]
+extern "C" fn method(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let name = match arg[0].unpack() {
+        RV::Symbol(id) => id,
+        RV::String(b) => globals.get_ident_id(String::from_utf8_lossy(b).as_ref()),
+        _ => {
+            let err = MonorubyErr::typeerr(format!(
+                "{} is not a symbol nor a string",
+                globals.val_inspect(arg[0])
+            ));
+            globals.set_error(err);
+            return None;
+        }
+    };
+    let self_val = arg.self_value();
+    let class_id = self_val.class_id();
+    let func_id = match globals.get_method_inner(class_id, name) {
+        Some(func_id) => func_id,
+        None => {
+            let err = MonorubyErr::nameerr(format!(
+                "undefined method `{}' for class `{}'",
+                globals.get_ident_name(name),
+                class_id.get_name(globals)
+            ));
+            globals.set_error(err);
+            return None;
+        }
+    };
+    let mut method = Value::new_proc(func_id, self_val, Value::nil());
+    method.change_class(METHOD_CLASS);
+    Some(method)
+}
+
+/// ### Method#call
+/// - call(*args) -> object
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Method.html#I_CALL]
+extern "C" fn call(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let data = arg.self_value().as_proc().unwrap().clone();
+    globals.check_arity(data.func_id, len)?;
+    let args: Vec<Value> = (0..len).map(|i| arg[i]).collect();
+    vm.invoke_method(globals, data.func_id, data.self_value, &args)
+}
+
+/// ### Method#arity
+/// - arity -> Integer
+///
+/// Return -(the number of required parameters + 1) if the method takes optional parameters.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Method.html#I_ARITY]
+extern "C" fn arity(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let func_id = arg.self_value().as_proc().unwrap().func_id;
+    let func = &globals.func[func_id];
+    let (arity, min_arity) = (func.arity(), func.min_arity());
+    let res = if arity == min_arity {
+        arity as i64
+    } else {
+        -(min_arity as i64) - 1
+    };
+    Some(Value::new_integer(res))
+}
+
+/// ### Method#to_proc
+/// - to_proc -> Proc
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Method.html#I_TO_PROC]
+extern "C" fn to_proc(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let self_val = arg.self_value();
+    let data = self_val.as_proc().unwrap();
+    Some(Value::new_proc(
+        data.func_id,
+        data.self_value,
+        data.outer_block,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_method() {
+        run_test(
+            r#"
+            class Foo
+              def setup
+                @x = 10
+                self
+              end
+              def add(a, b)
+                @x + a + b
+              end
+            end
+            m = Foo.new.setup.method(:add)
+            [m.class, m.arity, m.call(1, 2)].inspect
+            "#,
+        );
+        run_test(r#"m = 3.method("chr"); m.call"#);
+        run_test(
+            r#"
+            def double(x)
+              x * 2
+            end
+            [1, 2, 3].sort_by(&method(:double)).inspect
+            "#,
+        );
+        run_test(
+            r#"
+            def opt(a, b = 1)
+            end
+            method(:opt).arity
+            "#,
+        );
+        run_test_error(
+            "def foo(a); end; method(:foo).call",
+            MonorubyErrKind::WrongArguments(String::new()),
+        );
+        run_test_error("1.method(:nothing)", MonorubyErrKind::Name(String::new()));
+        run_test_error("Method.new", MonorubyErrKind::MethodNotFound(IdentId::_ADD));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::executor::op::rem_values;
+use crate::*;
+use num::{BigInt, Signed, ToPrimitive, Zero};
+
+//
+// Numeric class
+//
+// Since Numeric class is not defined yet, the methods are defined on each numeric class.
+//
+
+pub(super) fn init(globals: &mut Globals) {
+    for class_id in [INTEGER_CLASS, FLOAT_CLASS] {
+        globals.define_builtin_func(class_id, "%", rem, 1);
+        globals.define_builtin_func(class_id, "modulo", rem, 1);
+        globals.define_builtin_func(class_id, "remainder", remainder, 1);
+        globals.define_builtin_func(class_id, "abs", abs, 0);
+        globals.define_builtin_func(class_id, "magnitude", abs, 0);
+    }
+}
+
+/// ### Integer#%
+/// - self % other -> Integer | Float
+/// - modulo(other) -> Integer | Float
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Integer.html#I_--25]
+extern "C" fn rem(vm: &mut Interp, globals: &mut Globals, arg: Arg, _len: usize) -> Option<Value> {
+    rem_values(vm, globals, arg.self_value(), arg[0])
+}
+
+/// ### Integer#remainder
+/// - remainder(other) -> Integer | Float
+///
+/// Unlike Integer#%, the sign of the result follows the receiver.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Integer.html#I_REMAINDER]
+extern "C" fn remainder(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let (lhs, rhs) = (arg.self_value(), arg[0]);
+    let v = match (lhs.unpack(), rhs.unpack()) {
+        (RV::Integer(lhs), RV::Integer(rhs)) => {
+            if rhs.is_zero() {
+                globals.err_divide_by_zero();
+                return None;
+            }
+            Value::new_integer(lhs.checked_rem(rhs).unwrap_or(0))
+        }
+        (RV::Integer(lhs), RV::BigInt(rhs)) => Value::new_bigint(BigInt::from(lhs) % rhs),
+        (RV::BigInt(lhs), RV::Integer(rhs)) => {
+            if rhs.is_zero() {
+                globals.err_divide_by_zero();
+                return None;
+            }
+            Value::new_bigint(lhs % BigInt::from(rhs))
+        }
+        (RV::BigInt(lhs), RV::BigInt(rhs)) => Value::new_bigint(lhs % rhs),
+        (RV::Integer(lhs), RV::Float(rhs)) => Value::new_float(lhs as f64 % rhs),
+        (RV::BigInt(lhs), RV::Float(rhs)) => Value::new_float(lhs.to_f64().unwrap() % rhs),
+        (RV::Float(lhs), RV::Integer(rhs)) => Value::new_float(lhs % rhs as f64),
+        (RV::Float(lhs), RV::BigInt(rhs)) => Value::new_float(lhs % rhs.to_f64().unwrap()),
+        (RV::Float(lhs), RV::Float(rhs)) => Value::new_float(lhs % rhs),
+        _ => {
+            globals.err_cant_coerce(rhs, lhs.class_id());
+            return None;
+        }
+    };
+    Some(v)
+}
+
+/// ### Integer#abs
+/// - abs -> Integer | Float
+/// - magnitude -> Integer | Float
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Integer.html#I_ABS]
+extern "C" fn abs(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let v = match arg.self_value().unpack() {
+        RV::Integer(i) => match i.checked_abs() {
+            Some(i) => Value::new_integer(i),
+            None => Value::new_bigint(BigInt::from(i).abs()),
+        },
+        RV::BigInt(b) => Value::new_bigint(b.abs()),
+        RV::Float(f) => Value::new_float(f.abs()),
+        _ => unreachable!(),
+    };
+    Some(v)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_modulo() {
+        run_test("(-7) % 3");
+        run_test("7 % -3");
+        run_test("(-7).modulo(3)");
+        run_test("7.modulo(-3)");
+        run_test("(-7.5) % 2");
+        run_test("7.5.modulo(-2)");
+        run_test("(-100000000000000000000) % 3");
+        run_test("3 % -100000000000000000000");
+    }
+
+    #[test]
+    fn test_abs() {
+        run_test("(-5).abs");
+        run_test("5.abs");
+        run_test("(-2.5).abs");
+        run_test("-0.0.abs");
+        run_test("(-100000000000000000000).magnitude");
+        run_test("(-9223372036854775807 - 1).abs");
+        run_test("x = -7; x.abs + x.abs.abs");
+    }
+
+    #[test]
+    fn test_remainder() {
+        run_test("(-7).remainder(3)");
+        run_test("7.remainder(-3)");
+        run_test("(-7.5).remainder(2)");
+        run_test("7.5.remainder(-2)");
+        run_test("(-100000000000000000000).remainder(3)");
+        run_test("3.remainder(-100000000000000000000)");
+    }
+}
@@ -7,11 +7,43 @@ use crate::*;
 pub(super) fn init(globals: &mut Globals) {
     globals.define_builtin_func(OBJECT_CLASS, "puts", puts, -1);
     globals.define_builtin_func(OBJECT_CLASS, "print", print, -1);
+    globals.define_builtin_func(OBJECT_CLASS, "p", p, -1);
+    globals.define_builtin_func(OBJECT_CLASS, "display", display, 0);
+    globals.define_builtin_func(OBJECT_CLASS, "format", format, -1);
+    globals.define_builtin_func(OBJECT_CLASS, "sprintf", format, -1);
+    globals.define_builtin_func(OBJECT_CLASS, "eval", eval, 1);
+    globals.define_builtin_func(OBJECT_CLASS, "sleep", sleep, 1);
+    globals.define_builtin_func(OBJECT_CLASS, "raise", raise, -1);
     globals.define_builtin_func(OBJECT_CLASS, "assert", assert, 2);
+    globals.define_builtin_func(OBJECT_CLASS, "at_exit", at_exit, 0);
+    globals.define_builtin_func(OBJECT_CLASS, "loop", loop_, 0);
     globals.define_builtin_func(OBJECT_CLASS, "respond_to?", respond_to, 1);
     globals.define_builtin_func(OBJECT_CLASS, "inspect", inspect, 0);
+    globals.define_builtin_func(OBJECT_CLASS, "hash", hash, 0);
+    globals.define_builtin_func(OBJECT_CLASS, "==", op_eq, 1);
+    globals.define_builtin_func(OBJECT_CLASS, "!=", op_ne, 1);
+    globals.define_builtin_func(OBJECT_CLASS, "eql?", eql, 1);
+    globals.define_builtin_func(OBJECT_CLASS, "equal?", equal, 1);
+    globals.define_builtin_func(OBJECT_CLASS, "freeze", freeze, 0);
+    globals.define_builtin_func(OBJECT_CLASS, "frozen?", frozen, 0);
+    globals.define_builtin_func(OBJECT_CLASS, "deep_dup", deep_dup, 0);
+    globals.define_builtin_func(OBJECT_CLASS, "itself", itself, 0);
+    globals.define_builtin_func(OBJECT_CLASS, "tap", tap, 0);
+    globals.define_builtin_func(OBJECT_CLASS, "instance_eval", instance_eval, 0);
     globals.define_builtin_func(OBJECT_CLASS, "class", class, 0);
     globals.define_builtin_func(OBJECT_CLASS, "singleton_class", singleton_class, 0);
+    globals.define_builtin_func(
+        OBJECT_CLASS,
+        "instance_variable_defined?",
+        instance_variable_defined,
+        1,
+    );
+    globals.define_builtin_func(
+        OBJECT_CLASS,
+        "remove_instance_variable",
+        remove_instance_variable,
+        1,
+    );
 }
 
 /// Kernel#puts
@@ -48,6 +80,177 @@ extern "C" fn print(
     Some(Value::nil())
 }
 
+/// Object#display
+/// - display(out = $stdout) -> nil
+///
+/// Only writing to stdout is supported.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_DISPLAY]
+extern "C" fn display(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    globals
+        .stdout
+        .write(&arg.self_value().to_bytes(globals))
+        .unwrap();
+    Some(Value::nil())
+}
+
+/// Kernel#p
+/// - p(*arg) -> object | nil
+///
+/// Returns the argument itself so that `p` can be put in the middle of an expression.
+/// Several arguments are returned as an Array.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Kernel.html#M_P]
+extern "C" fn p(_vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    for offset in 0..len {
+        let s = globals.val_inspect(arg[offset]);
+        globals.stdout.write(s.as_bytes()).unwrap();
+        globals.stdout.write(b"\n").unwrap();
+    }
+    Some(match len {
+        0 => Value::nil(),
+        1 => arg[0],
+        _ => Value::new_array((0..len).map(|i| arg[i]).collect()),
+    })
+}
+
+/// Kernel#format
+/// - format(format, *arg) -> String
+/// - sprintf(format, *arg) -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Kernel.html#M_FORMAT]
+extern "C" fn format(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    if len == 0 {
+        globals.err_argument("too few arguments");
+        return None;
+    }
+    let fmt = globals.val_tos(arg[0]);
+    let args: Vec<Value> = (1..len).map(|i| arg[i]).collect();
+    let res = super::string::format_by_args(globals, &fmt, &args)?;
+    Some(Value::new_string(res.into_bytes()))
+}
+
+/// Kernel#eval
+/// - eval(expr) -> object
+///
+/// Bindings, file names and line numbers are not supported.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Kernel.html#M_EVAL]
+extern "C" fn eval(vm: &mut Interp, globals: &mut Globals, arg: Arg, _len: usize) -> Option<Value> {
+    let code = match arg[0].unpack() {
+        RV::String(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+        _ => {
+            globals.err_no_implict_conv(arg[0].class_id(), STRING_CLASS);
+            return None;
+        }
+    };
+    vm.eval_script(globals, code)
+}
+
+/// Kernel#sleep
+/// - sleep(sec) -> Integer
+///
+/// Sleeping forever without *sec* is not supported.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Kernel.html#M_SLEEP]
+extern "C" fn sleep(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let secs = match arg[0].unpack() {
+        RV::Integer(i) => i as f64,
+        RV::Float(f) => f,
+        _ => {
+            let class_name = arg[0].class_id().get_name(globals);
+            globals.set_error(MonorubyErr::typeerr(format!(
+                "can't convert {} into time interval",
+                class_name
+            )));
+            return None;
+        }
+    };
+    if secs < 0.0 {
+        globals.err_argument("time interval must not be negative");
+        return None;
+    }
+    let duration = match std::time::Duration::try_from_secs_f64(secs) {
+        Ok(duration) => duration,
+        Err(_) => {
+            globals.set_error(MonorubyErr::range(format!("{} out of Time range", secs)));
+            return None;
+        }
+    };
+    let now = std::time::Instant::now();
+    std::thread::sleep(duration);
+    Some(Value::new_integer(
+        now.elapsed().as_secs_f64().round() as i64
+    ))
+}
+
+/// Kernel#raise
+/// - raise -> ()
+/// - raise(message) -> ()
+/// - raise(error_type, message = nil) -> ()
+///
+/// Raise RuntimeError if *message* is a String.
+/// *error_type* is an exception class or an exception object to be raised again.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Kernel.html#M_RAISE]
+extern "C" fn raise(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    if len > 2 {
+        globals.set_error(MonorubyErr::wrong_arguments_range(0, 2, len));
+        return None;
+    }
+    if len == 0 {
+        let err = MonorubyErr::exception(RUNTIME_ERROR_CLASS, "unhandled exception".to_string());
+        globals.set_error(err);
+        return None;
+    }
+    let (class_id, default_msg) = match arg[0].unpack() {
+        RV::String(b) if len == 1 => (RUNTIME_ERROR_CLASS, String::from_utf8_lossy(b).to_string()),
+        _ => match (arg[0].is_class(), arg[0].as_exception()) {
+            (Some(class_id), _) if globals.is_subclass_of(class_id, EXCEPTION_CLASS) => {
+                (class_id, class_id.get_name(globals))
+            }
+            (_, Some(err)) if len == 1 => {
+                let err = err.clone();
+                globals.set_error(err);
+                return None;
+            }
+            (_, Some(err)) => (err.class_id().unwrap(), err.get_error_message(globals)),
+            _ => {
+                let err = MonorubyErr::typeerr("exception class/object expected".to_string());
+                globals.set_error(err);
+                return None;
+            }
+        },
+    };
+    let msg = if len == 2 && !arg[1].is_nil() {
+        globals.val_tos(arg[1])
+    } else {
+        default_msg
+    };
+    globals.set_error(MonorubyErr::exception(class_id, msg));
+    None
+}
+
 extern "C" fn assert(
     _vm: &mut Interp,
     _globals: &mut Globals,
@@ -95,6 +298,213 @@ extern "C" fn inspect(
     Some(Value::new_string(s.into_bytes()))
 }
 
+/// Object#hash
+/// - hash -> Integer
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_HASH]
+extern "C" fn hash(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let h = arg.self_value().calc_hash();
+    Some(Value::new_integer((h >> 2) as i64))
+}
+
+/// Object#==
+/// - self == other -> bool
+///
+/// User objects are compared by their identities.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_--3D--3D]
+extern "C" fn op_eq(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(Value::bool(Value::eq(arg.self_value(), arg[0])))
+}
+
+/// BasicObject#!=
+/// - self != other -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/BasicObject.html#I_--21--3D]
+extern "C" fn op_ne(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(Value::bool(!Value::eq(arg.self_value(), arg[0])))
+}
+
+/// Object#eql?
+/// - eql?(other) -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_EQL--3F]
+extern "C" fn eql(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(Value::bool(Value::eq(arg.self_value(), arg[0])))
+}
+
+/// Object#equal?
+/// - equal?(other) -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_EQUAL--3F]
+extern "C" fn equal(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(Value::bool(arg.self_value() == arg[0]))
+}
+
+/// Object#freeze
+/// - freeze -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_FREEZE]
+extern "C" fn freeze(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    arg.self_value().freeze();
+    Some(arg.self_value())
+}
+
+/// Object#frozen?
+/// - frozen? -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_FROZEN--3F]
+extern "C" fn frozen(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(Value::bool(arg.self_value().is_frozen()))
+}
+
+/// Object#deep_dup
+/// - deep_dup -> object
+///
+/// Return a copy of `self` with nested arrays, hashes and strings copied recursively.
+/// The copies are not frozen even if the originals are. This method is not in CRuby.
+extern "C" fn deep_dup(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(arg.self_value().deep_copy())
+}
+
+/// Object#itself
+/// - itself -> object
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_ITSELF]
+extern "C" fn itself(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(arg.self_value())
+}
+
+/// Object#tap
+/// - tap {|x| ... } -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_TAP]
+extern "C" fn tap(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    vm.invoke_block(globals, block, &[arg.self_value()])?;
+    Some(arg.self_value())
+}
+
+/// BasicObject#instance_eval
+/// - instance_eval {|obj| ... } -> object
+///
+/// The block is evaluated with `self` rebound to the receiver. Evaluating a String is not supported.
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/BasicObject/i/instance_eval.html]
+extern "C" fn instance_eval(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    let self_val = arg.self_value();
+    vm.invoke_block_with_self(globals, block, self_val, &[self_val])
+}
+
+/// Kernel#loop
+/// - loop { ... } -> nil
+///
+/// Call the block repeatedly until StopIteration is raised in it.
+/// Unlike CRuby, nil is returned instead of the result of StopIteration.
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Kernel/m/loop.html]
+extern "C" fn loop_(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    loop {
+        if vm.invoke_block(globals, block, &[]).is_none() {
+            let err = globals.take_error().unwrap();
+            match err.class_id() {
+                Some(class_id) if globals.is_subclass_of(class_id, STOP_ITERATION_CLASS) => {
+                    return Some(Value::nil());
+                }
+                _ => {
+                    globals.set_error(err);
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Kernel#at_exit
+/// - at_exit { ... } -> Proc
+///
+/// The blocks are called in reverse order of registration after the toplevel finishes.
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Kernel/m/at_exit.html]
+extern "C" fn at_exit(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    globals.push_at_exit(block);
+    Some(block)
+}
+
 extern "C" fn class(
     _vm: &mut Interp,
     globals: &mut Globals,
@@ -113,6 +523,75 @@ extern "C" fn singleton_class(
     Some(arg.self_value().get_singleton(globals))
 }
 
+/// Object#instance_variable_defined?
+/// - instance_variable_defined?(var) -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_INSTANCE_VARIABLE_DEFINED--3F]
+extern "C" fn instance_variable_defined(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let name = expect_ivar_name(globals, arg[0])?;
+    let defined = match arg.self_value().as_rvalue() {
+        Some(rvalue) => rvalue.get_var(name).is_some(),
+        None => false,
+    };
+    Some(Value::bool(defined))
+}
+
+/// Object#remove_instance_variable
+/// - remove_instance_variable(name) -> object
+///
+/// Return the value of the removed instance variable.
+/// NameError is raised if it is not defined.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Object.html#I_REMOVE_INSTANCE_VARIABLE]
+extern "C" fn remove_instance_variable(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let name = expect_ivar_name(globals, arg[0])?;
+    let self_val = arg.self_value();
+    if self_val.is_frozen() {
+        globals.err_frozen(self_val);
+        return None;
+    }
+    match self_val.rvalue_mut().remove_var(name) {
+        Some(val) => Some(val),
+        None => {
+            let msg = format!(
+                "instance variable {} not defined",
+                globals.get_ident_name(name)
+            );
+            globals.set_error(MonorubyErr::nameerr(msg));
+            None
+        }
+    }
+}
+
+/// Get the name of an instance variable from *val*, which is a Symbol or a String.
+fn expect_ivar_name(globals: &mut Globals, val: Value) -> Option<IdentId> {
+    let name = match val.unpack() {
+        RV::Symbol(id) => id,
+        RV::String(b) => globals.get_ident_id(String::from_utf8_lossy(b).as_ref()),
+        _ => {
+            let msg = format!("{} is not a symbol nor a string", globals.val_inspect(val));
+            globals.set_error(MonorubyErr::typeerr(msg));
+            return None;
+        }
+    };
+    let s = globals.get_ident_name(name);
+    if s.len() < 2 || !s.starts_with('@') || s.starts_with("@@") {
+        globals.err_invalid_ivar_name(name);
+        return None;
+    }
+    Some(name)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -130,4 +609,430 @@ mod test {
         run_test("puts Time.singleton_class");
         run_test(r#"File.write("/tmp/foo", "woo")"#);
     }
+
+    #[test]
+    fn test_display() {
+        run_test("42.display");
+        run_test(r#""woo".display"#);
+        run_test("x = 1.5.display; x");
+        run_test(":sym.display; 5");
+        let code = r#"42.display; "woo".display; :sym.display; 1.5.display; nil.display"#;
+        for jit in [false, true] {
+            let mut globals = Globals::new(1);
+            let out = SharedBuffer::default();
+            globals.stdout = std::io::BufWriter::new(Box::new(out.clone()));
+            globals.compile_script(code.to_string(), "").unwrap();
+            if jit {
+                Interp::jit_exec_toplevel(&mut globals).unwrap();
+            } else {
+                Interp::eval_toplevel(&mut globals).unwrap();
+            }
+            assert_eq!(b"42woosym1.5", out.0.borrow().as_slice());
+        }
+    }
+
+    /// A buffer to capture the output written to `Globals::stdout`.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_p() {
+        run_test("p");
+        run_test("p(5) + 1");
+        run_test("p(2.5).class");
+        run_test(r#"p("woo").class"#);
+        run_test("x = p(:sym); x");
+        run_test("p(1, 2).inspect");
+        run_test(r#"p(1.5, "a", :b, nil).inspect"#);
+        run_test("[1, 2].flat_map { |x| [x, x * 10] }.tap { |a| p a }.sum");
+        run_test("[1, 2].flat_map { |x| [x, x * 10] }.tap { |a| p(a, a.sum) }.inspect");
+        run_test("p([3, 4].flat_map { |x| [x] }).sum");
+    }
+
+    #[test]
+    fn test_tap() {
+        run_test("5.tap { |x| x * 2 } + 1");
+        run_test("$s = 0; 3.tap { |x| $s = x * 10 }.tap { |x| $s += x }; $s");
+        run_test(r#"a = "x"; a.tap { |s| s << "y" }.equal?(a)"#);
+        run_test("[1, 2].tap { |a| a[0] = 5 }.inspect");
+    }
+
+    #[test]
+    fn test_instance_eval() {
+        run_test("5.instance_eval { self + 1 }");
+        run_test("5.instance_eval { |x| x * 2 }");
+        run_test(
+            r#"
+            class Foo
+              def a
+                @a
+              end
+            end
+            f = Foo.new
+            g = Foo.new
+            f.instance_eval { @a = 42 }
+            [f.a, g.a].inspect
+            "#,
+        );
+        run_test_error("1.instance_eval", MonorubyErrKind::LocalJump(String::new()));
+    }
+
+    #[test]
+    fn test_at_exit() {
+        run_test("at_exit { 1 }; 2");
+        run_test_inspect(
+            r#"$a = ""; at_exit { $a << "1" }; at_exit { $a << "2"; at_exit { $a << "3" } }; $a << "0""#,
+            r#""0231""#,
+        );
+        run_test_error(
+            "at_exit { nil + 1 }; 2",
+            MonorubyErrKind::MethodNotFound(IdentId::_ADD),
+        );
+    }
+
+    #[test]
+    fn test_hash_eql() {
+        run_test(r#""abc".hash == "abc".hash"#);
+        run_test(r#""abc".hash == "abd".hash"#);
+        run_test("100000000000000000000.hash == 100000000000000000000.hash");
+        run_test("1.5.hash == 1.5.hash");
+        run_test(":sym.hash == :sym.hash");
+        run_test("1.eql?(1)");
+        run_test("1.eql?(1.0)");
+        run_test(r#""woo".eql?("woo")"#);
+        run_test(":a.eql?(:b)");
+        run_test("{ 0.0 => 1 }[-0.0]");
+        run_test("h = {}; h[-0.0] = 1; h[0.0] = 2; h[0.0]");
+        run_test("0.0.hash == -0.0.hash");
+    }
+
+    #[test]
+    fn test_user_hash_key() {
+        let class_def = r#"
+            class Pt
+              def set(x, y); @x = x; @y = y; self; end
+              def x; @x; end
+              def y; @y; end
+              def hash; @x * 31 + @y; end
+              def eql?(o); @x == o.x && @y == o.y; end
+            end
+            class Id
+              def eql?(o); true; end
+            end
+        "#;
+        for code in [
+            r#"
+            h = {}
+            h[Pt.new.set(1, 2)] = :a
+            h[Pt.new.set(3, 4)] = :b
+            h[Pt.new.set(1, 2)] = :c
+            [h[Pt.new.set(1, 2)], h[Pt.new.set(3, 4)], h[Pt.new.set(2, 1)]].inspect
+            "#,
+            "{ Pt.new.set(5, 6) => 7 }[Pt.new.set(5, 6)]",
+            "h = { Pt.new.set(0, 31) => 1 }; h[Pt.new.set(1, 0)].inspect",
+            "h = {}; h[Id.new] = 1; h[Id.new].inspect",
+            "a = Id.new; h = { a => 1 }; h[a]",
+        ] {
+            run_test(&format!("{}{}", class_def, code));
+        }
+    }
+
+    #[test]
+    fn test_eval() {
+        run_test(r#"eval("1 + 2")"#);
+        run_test(r#"eval("x = 5; x * 2") + 1"#);
+        run_test(r#"eval("def foo; 42; end"); foo"#);
+        run_test(r#"eval("eval('100')")"#);
+        run_test(r#"a = 0; i = 0; while i < 10; a = a + eval("j = 2; j * 3"); i = i + 1; end; a"#);
+    }
+
+    #[test]
+    fn test_sleep() {
+        // Sleeping is not deterministic, so the result is not compared with ruby.
+        let mut globals = Globals::new(1);
+        globals
+            .compile_script("sleep(0.05)".to_string(), std::path::Path::new(""))
+            .unwrap();
+        let now = std::time::Instant::now();
+        let val = Interp::eval_toplevel(&mut globals.clone()).unwrap();
+        assert!(now.elapsed() >= std::time::Duration::from_millis(50));
+        assert_eq!(Some(0), val.as_fixnum());
+        let now = std::time::Instant::now();
+        let val = Interp::jit_exec_toplevel(&mut globals).unwrap();
+        assert!(now.elapsed() >= std::time::Duration::from_millis(50));
+        assert_eq!(Some(0), val.as_fixnum());
+
+        run_test_error("sleep(-1)", MonorubyErrKind::Argument(String::new()));
+        run_test_error("sleep(Float::NAN)", MonorubyErrKind::Range(String::new()));
+        run_test_error(
+            "sleep(Float::INFINITY)",
+            MonorubyErrKind::Range(String::new()),
+        );
+        run_test_error("sleep(1e300)", MonorubyErrKind::Range(String::new()));
+    }
+
+    #[test]
+    fn test_freeze() {
+        run_test(r#""woo".frozen?"#);
+        run_test(r#""woo".freeze.frozen?"#);
+        run_test(r#"a = "woo"; a.freeze; a.frozen?"#);
+        run_test("1.frozen?");
+        run_test(":sym.frozen?");
+        run_test("nil.frozen?");
+        run_test("1.5.freeze");
+    }
+
+    #[test]
+    fn test_object_eq() {
+        run_test("class Foo; end; Foo.new == Foo.new");
+        run_test("class Foo; end; Foo.new != Foo.new");
+        run_test("class Foo; end; a = Foo.new; a == a");
+        run_test("class Foo; end; a = Foo.new; a != a");
+        run_test("class Foo; end; a = Foo.new; b = a; a.==(b)");
+        run_test("class Foo; end; a = Foo.new; a.!=(Foo.new)");
+        run_test("class Foo; end; Foo.new == 1");
+        run_test("self == self");
+        run_test("3.==(3)");
+        run_test(r#""a".!=("a")"#);
+    }
+
+    #[test]
+    fn test_itself() {
+        run_test("5.itself");
+        run_test("5.itself == 5");
+        run_test("2.5.itself");
+        run_test(r#"a = "woo"; a.itself.equal?(a)"#);
+        run_test(r#"a = "woo".freeze; a.itself.frozen?"#);
+    }
+
+    #[test]
+    fn test_frozen_constants() {
+        for (frozen, expected) in [(false, false), (true, true)] {
+            let mut globals = Globals::new(1);
+            globals.frozen_constants = frozen;
+            globals
+                .compile_script(r#"C = "woo"; C.frozen?"#.to_string(), "")
+                .unwrap();
+            let val = Interp::eval_toplevel(&mut globals).unwrap();
+            assert_eq!(Value::bool(expected), val);
+        }
+        // elements of Arrays and Hashes are frozen recursively.
+        for code in [
+            "A = [1, [2]]; A[1][0] = 3",
+            "A = [1, [2]]; A[0] = 3",
+            "H = { a: [1] }; H[:a][0] = 2",
+            r#"A = [{ b: "x" }]; A[0][:b] << "y""#,
+        ] {
+            let mut globals = Globals::new(1);
+            globals.frozen_constants = true;
+            run_test_error_with(globals, code, MonorubyErrKind::Frozen(String::new()));
+        }
+        let mut globals = Globals::new(1);
+        globals.frozen_constants = true;
+        globals
+            .compile_script("a = [1]; A = [a, a]; A[1].frozen?".to_string(), "")
+            .unwrap();
+        assert_eq!(
+            Value::bool(true),
+            Interp::eval_toplevel(&mut globals).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_frozen_string_literal() {
+        run_test(r#"a = "woo"; a.equal?(a)"#);
+        run_test(r#"a = "woo"; b = "woo"; a.equal?(b)"#);
+        for (code, expected) in [
+            (r#""woo".frozen?"#, true),
+            (r#"def f; "woo"; end; f.frozen?"#, true),
+            (r#""woo#{1}".frozen?"#, false),
+        ] {
+            for jit in [false, true] {
+                let mut globals = Globals::new(1);
+                globals.enable_frozen();
+                globals.compile_script(code.to_string(), "").unwrap();
+                let val = if jit {
+                    Interp::jit_exec_toplevel(&mut globals)
+                } else {
+                    Interp::eval_toplevel(&mut globals)
+                }
+                .unwrap();
+                assert_eq!(Value::bool(expected), val);
+            }
+        }
+        // frozen string literals are not allocated each time they are evaluated.
+        let code = r#"i = 0; while i < 1000; a = "woo"; i += 1; end"#;
+        for (frozen, jit) in [(false, false), (false, true), (true, false), (true, true)] {
+            let mut globals = Globals::new(1);
+            if frozen {
+                globals.enable_frozen();
+            }
+            globals.compile_script(code.to_string(), "").unwrap();
+            let allocated = || ALLOC.with(|alloc| alloc.borrow().total_allocated());
+            let before = allocated();
+            if jit {
+                Interp::jit_exec_toplevel(&mut globals).unwrap();
+            } else {
+                Interp::eval_toplevel(&mut globals).unwrap();
+            }
+            let count = allocated() - before;
+            if frozen {
+                assert!(count < 10, "{} objects are allocated", count);
+            } else {
+                assert!(count >= 1000, "{} objects are allocated", count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_raise() {
+        run_test("begin; raise; rescue => e; [e.class, e.message].inspect; end");
+        run_test(r#"begin; raise "oops"; rescue => e; [e.class, e.message].inspect; end"#);
+        run_test(
+            r#"begin; raise ArgumentError, "bad"; rescue => e; [e.class, e.message].inspect; end"#,
+        );
+        run_test("begin; raise TypeError; rescue => e; [e.class, e.message].inspect; end");
+        run_test(
+            r#"
+            def f(x); if x > 2 then raise IndexError, "too big" end; x; end
+            begin; f(1) + f(3); rescue => e; e.inspect; end
+            "#,
+        );
+        run_test(
+            r#"
+            begin
+              begin; 1 / 0; rescue => e; raise e; end
+            rescue => e2
+              [e2.class, e2.message].inspect
+            end
+            "#,
+        );
+        run_test(
+            r#"
+            begin
+              begin; 1 / 0; rescue => e; raise e, "again"; end
+            rescue => e2
+              [e2.class, e2.message].inspect
+            end
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_raise_error() {
+        for (code, msg) in [
+            ("raise", "unhandled exception (RuntimeError)"),
+            (r#"raise ArgumentError, "bad""#, "bad (ArgumentError)"),
+            (
+                r#"begin; raise Exception, "fatal"; rescue; 1; end"#,
+                "fatal (Exception)",
+            ),
+            ("raise 1", "exception class/object expected (TypeError)"),
+        ] {
+            run_test_error_message(code, msg);
+        }
+    }
+
+    #[test]
+    fn test_deep_dup() {
+        for (code, expected) in [
+            (
+                r#"
+                a = [1, [2, "x"], {1 => ["y"]}]
+                b = a.deep_dup
+                b[1][0] = 3
+                b[2][1][0] = 4
+                b[2][5] = 6
+                [a, b]
+                "#,
+                r#"[[1, [2, "x"], {1=>["y"]}], [1, [3, "x"], {1=>[4], 5=>6}]]"#,
+            ),
+            (
+                r#"
+                a = [1]
+                a[1] = a
+                b = a.deep_dup
+                [b.equal?(a), b[1].equal?(b)]
+                "#,
+                "[false, true]",
+            ),
+            (
+                r#"
+                s = "x"
+                a = [s, s].freeze
+                b = a.deep_dup
+                [b.frozen?, b[0].equal?(s), b[0].equal?(b[1])]
+                "#,
+                "[false, false, true]",
+            ),
+            ("class C; def set; @a = [1]; self; end; def a; @a; end; end; c = C.new.set; c.deep_dup.a.equal?(c.a)", "false"),
+            ("1.deep_dup", "1"),
+        ] {
+            run_test_inspect(code, expected);
+        }
+    }
+
+    #[test]
+    fn test_instance_variable() {
+        run_test(
+            r#"
+            class C
+              def set; @x = 42; self; end
+            end
+            o = C.new
+            a = o.instance_variable_defined?(:@x)
+            o.set
+            b = o.instance_variable_defined?(:@x)
+            c = o.instance_variable_defined?("@x")
+            d = o.remove_instance_variable(:@x)
+            e = o.instance_variable_defined?(:@x)
+            [a, b, c, d, e].inspect
+            "#,
+        );
+        run_test(
+            r#"
+            class C
+              def memo; @memo = (@memo || 0) + 1; end
+            end
+            o = C.new
+            o.memo; o.memo
+            o.remove_instance_variable("@memo")
+            o.memo
+            "#,
+        );
+        run_test("1.instance_variable_defined?(:@a)");
+        run_test(
+            "begin; Object.new.remove_instance_variable(:@a); rescue NameError => e; e.message; end",
+        );
+    }
+
+    #[test]
+    fn test_instance_variable_error() {
+        for code in [
+            "Object.new.instance_variable_defined?(:a)",
+            "Object.new.instance_variable_defined?(:@@a)",
+            "Object.new.remove_instance_variable(:@a)",
+        ] {
+            run_test_error(code, MonorubyErrKind::Name(String::new()));
+        }
+        run_test_error(
+            "Object.new.remove_instance_variable(1)",
+            MonorubyErrKind::Type(String::new()),
+        );
+        run_test_error(
+            "1.remove_instance_variable(:@a)",
+            MonorubyErrKind::Frozen(String::new()),
+        );
+    }
 }
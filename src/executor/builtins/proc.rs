@@ -0,0 +1,33 @@
+use crate::*;
+
+//
+// Proc class
+//
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_func(PROC_CLASS, "to_proc", to_proc, 0);
+}
+
+/// ### Proc#to_proc
+/// - to_proc -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Proc.html#I_TO_PROC]
+extern "C" fn to_proc(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(arg.self_value())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_proc_to_proc() {
+        run_test("pr = :+.to_proc; pr.to_proc.equal?(pr)");
+        run_test("pr = :+.to_proc; [1, 2, 3].inject(&pr)");
+    }
+}
@@ -0,0 +1,173 @@
+use crate::*;
+
+//
+// Range class
+//
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_func(RANGE_CLASS, "to_s", to_s, 0);
+    globals.define_builtin_func(RANGE_CLASS, "inspect", inspect, 0);
+    globals.define_builtin_func(RANGE_CLASS, "begin", begin, 0);
+    globals.define_builtin_func(RANGE_CLASS, "end", end, 0);
+    globals.define_builtin_func(RANGE_CLASS, "exclude_end?", exclude_end, 0);
+    globals.define_builtin_func(RANGE_CLASS, "each", each, 0);
+}
+
+/// ### Range#to_s
+/// - to_s -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Range.html#I_TO_S]
+extern "C" fn to_s(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let s = globals.val_tos(arg.self_value());
+    Some(Value::new_string(s.into_bytes()))
+}
+
+/// ### Range#inspect
+/// - inspect -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Range.html#I_INSPECT]
+extern "C" fn inspect(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let s = globals.val_inspect(arg.self_value());
+    Some(Value::new_string(s.into_bytes()))
+}
+
+/// ### Range#begin
+/// - begin -> object
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Range.html#I_BEGIN]
+extern "C" fn begin(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(arg.self_value().as_range().unwrap().start)
+}
+
+/// ### Range#end
+/// - end -> object
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Range.html#I_END]
+extern "C" fn end(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(arg.self_value().as_range().unwrap().end)
+}
+
+/// ### Range#exclude_end?
+/// - exclude_end? -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Range.html#I_EXCLUDE_END--3F]
+extern "C" fn exclude_end(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(Value::bool(
+        arg.self_value().as_range().unwrap().exclude_end,
+    ))
+}
+
+/// ### Range#each
+/// - each {|item| ... } -> self
+///
+/// Only ranges of integers are supported. A range without an end never finishes.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Range.html#I_EACH]
+extern "C" fn each(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let self_val = arg.self_value();
+    let RangeInfo {
+        start,
+        end,
+        exclude_end,
+    } = self_val.as_range().unwrap().clone();
+    let start = match start.unpack() {
+        RV::Integer(i) => i,
+        _ => {
+            let class_name = start.class_id().get_name(globals);
+            globals.set_error(MonorubyErr::typeerr(format!(
+                "can't iterate from {}",
+                class_name
+            )));
+            return None;
+        }
+    };
+    let end = match end.unpack() {
+        RV::Integer(i) if exclude_end => i,
+        RV::Integer(i) => i + 1,
+        // `as` saturates at the bounds of i64, so huge floats never finish as bignums do.
+        RV::Float(f) if exclude_end && f.fract() == 0.0 => f as i64,
+        RV::Float(f) => (f.floor() as i64).saturating_add(1),
+        RV::BigInt(b) if b.sign() == num::bigint::Sign::Minus => i64::MIN,
+        // practically never finishes.
+        _ => i64::MAX,
+    };
+    let block = arg.block(len);
+    for i in start..end {
+        vm.invoke_block(globals, block, &[Value::new_integer(i)])?;
+    }
+    Some(self_val)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        run_test("(0..300).to_s");
+        run_test("(0...300).to_s");
+        run_test("(1..3).inspect");
+        run_test("(1...3).inspect");
+        run_test("(1..nil).inspect");
+        run_test(r#"("a".."c").inspect"#);
+        run_test(r#"("a".."c").to_s"#);
+        run_test("(1..3) == (1..3)");
+        run_test("(1..3) == (1...3)");
+        run_test("(1..3) != (1..4)");
+        run_test("r = 1..3; r.begin + r.end");
+        run_test("(1...3).exclude_end?");
+    }
+
+    #[test]
+    fn test_range_each() {
+        run_test("$s = 0; (1...4).each { |i| $s += i }; $s");
+        run_test("$s = 0; (1..4).each { |i| $s += i }; $s");
+        run_test("(1...4).each { |i| i }.to_s");
+        run_test("$s = 0; (3..1).each { |i| $s += 1 }; $s");
+        run_test("a = 2; b = 5; $s = 0; (a..b).each { |i| $s += i }; $s");
+        run_test("(1..2.5).each { |i| $t = i }; $t");
+        run_test("$s = 0; (-2...2).each { |i| (0..i).each { |j| $s += j } }; $s");
+        run_test(
+            r#"
+            def sum(r); $t = 0; r.each { |i| $t += i }; $t; end
+            sum(0..10) + sum(0...10)
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_range_error() {
+        for code in ["1..:a", r#"1..."a""#] {
+            run_test_error(code, MonorubyErrKind::Argument(String::new()));
+        }
+        run_test_error(
+            "(1.5..3).each { |i| i }",
+            MonorubyErrKind::Type(String::new()),
+        );
+    }
+}
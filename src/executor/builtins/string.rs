@@ -0,0 +1,988 @@
+use crate::*;
+use num::{BigInt, FromPrimitive, One, Signed, Zero};
+
+//
+// String class
+//
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_func(STRING_CLASS, "%", rem, 1);
+    globals.define_builtin_func(STRING_CLASS, "start_with?", start_with, -1);
+    globals.define_builtin_func(STRING_CLASS, "end_with?", end_with, -1);
+    globals.define_builtin_func(STRING_CLASS, "include?", include, 1);
+    globals.define_builtin_func(STRING_CLASS, "strip", strip, 0);
+    globals.define_builtin_func(STRING_CLASS, "lstrip", lstrip, 0);
+    globals.define_builtin_func(STRING_CLASS, "rstrip", rstrip, 0);
+    globals.define_builtin_func(STRING_CLASS, "chomp", chomp, -1);
+    globals.define_builtin_func(STRING_CLASS, "chop", chop, 0);
+    globals.define_builtin_func(STRING_CLASS, "replace", replace, 1);
+    globals.define_builtin_func(STRING_CLASS, "concat", concat, -1);
+    globals.define_builtin_func(STRING_CLASS, "<<", concat, 1);
+    globals.define_builtin_func(STRING_CLASS, "prepend", prepend, -1);
+    globals.define_builtin_func(STRING_CLASS, "to_sym", to_sym, 0);
+    globals.define_builtin_func(STRING_CLASS, "intern", to_sym, 0);
+    globals.define_builtin_func(STRING_CLASS, "ljust", ljust, -1);
+    globals.define_builtin_func(STRING_CLASS, "rjust", rjust, -1);
+    globals.define_builtin_func(STRING_CLASS, "center", center, -1);
+}
+
+/// String#%
+/// - self % args -> String
+///
+/// If *args* is an Array, its elements are used as the arguments.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_--25]
+extern "C" fn rem(_vm: &mut Interp, globals: &mut Globals, arg: Arg, _len: usize) -> Option<Value> {
+    string_rem(globals, arg.self_value(), arg[0])
+}
+
+///
+/// Format *arg* with the format string *fmt*. If *arg* is an Array, its elements are formatted.
+///
+/// This is also used by `%` operator for String receivers.
+///
+pub(crate) fn string_rem(globals: &mut Globals, fmt: Value, arg: Value) -> Option<Value> {
+    let fmt = globals.val_tos(fmt);
+    let res = match arg.as_array() {
+        Some(args) => format_by_args(globals, &fmt, args)?,
+        None => format_by_args(globals, &fmt, &[arg])?,
+    };
+    Some(Value::new_string(res.into_bytes()))
+}
+
+/// String#start_with?
+/// - start_with?(*strs) -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_START_WITH--3F]
+extern "C" fn start_with(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let s = expect_bytes(globals, arg.self_value())?;
+    for i in 0..len {
+        if s.starts_with(&expect_bytes(globals, arg[i])?) {
+            return Some(Value::bool(true));
+        }
+    }
+    Some(Value::bool(false))
+}
+
+/// String#end_with?
+/// - end_with?(*strs) -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_END_WITH--3F]
+extern "C" fn end_with(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let s = expect_bytes(globals, arg.self_value())?;
+    for i in 0..len {
+        if s.ends_with(&expect_bytes(globals, arg[i])?) {
+            return Some(Value::bool(true));
+        }
+    }
+    Some(Value::bool(false))
+}
+
+/// String#include?
+/// - include?(substr) -> bool
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_INCLUDE--3F]
+extern "C" fn include(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let s = expect_bytes(globals, arg.self_value())?;
+    let sub = expect_bytes(globals, arg[0])?;
+    let b = sub.is_empty() || s.windows(sub.len()).any(|w| w == sub.as_slice());
+    Some(Value::bool(b))
+}
+
+/// String#strip
+/// - strip -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_STRIP]
+extern "C" fn strip(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let s = expect_bytes(globals, arg.self_value())?;
+    Some(Value::new_string(trim_end(trim_start(&s)).to_vec()))
+}
+
+/// String#lstrip
+/// - lstrip -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_LSTRIP]
+extern "C" fn lstrip(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let s = expect_bytes(globals, arg.self_value())?;
+    Some(Value::new_string(trim_start(&s).to_vec()))
+}
+
+/// String#rstrip
+/// - rstrip -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_RSTRIP]
+extern "C" fn rstrip(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let s = expect_bytes(globals, arg.self_value())?;
+    Some(Value::new_string(trim_end(&s).to_vec()))
+}
+
+/// String#chomp
+/// - chomp(rs = $/) -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_CHOMP]
+extern "C" fn chomp(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    if len > 1 {
+        globals.set_error(MonorubyErr::wrong_arguments(1, len));
+        return None;
+    }
+    let s = expect_bytes(globals, arg.self_value())?;
+    let res = if len == 0 {
+        chomp_newline(&s)
+    } else {
+        let rs = expect_bytes(globals, arg[0])?;
+        match rs.as_slice() {
+            b"\n" => chomp_newline(&s),
+            // paragraph mode: remove all the trailing newlines.
+            b"" => {
+                let mut s = s.as_slice();
+                while s.ends_with(b"\n") {
+                    s = &s[..s.len() - 1];
+                    if s.ends_with(b"\r") {
+                        s = &s[..s.len() - 1];
+                    }
+                }
+                s
+            }
+            rs => s.strip_suffix(rs).unwrap_or(&s),
+        }
+    };
+    Some(Value::new_string(res.to_vec()))
+}
+
+/// String#chop
+/// - chop -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_CHOP]
+extern "C" fn chop(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let s = expect_bytes(globals, arg.self_value())?;
+    let len = if s.ends_with(b"\r\n") {
+        s.len() - 2
+    } else {
+        match std::str::from_utf8(&s) {
+            Ok(str) => str.char_indices().last().map_or(0, |(i, _)| i),
+            Err(_) => s.len().saturating_sub(1),
+        }
+    };
+    Some(Value::new_string(s[..len].to_vec()))
+}
+
+/// String#replace
+/// - replace(other) -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_REPLACE]
+extern "C" fn replace(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let self_val = arg.self_value();
+    let other = expect_bytes(globals, arg[0])?;
+    *bytes_mut(globals, &self_val)? = other;
+    Some(self_val)
+}
+
+/// String#concat
+/// - concat(*arguments) -> self
+/// - self << other -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_CONCAT]
+extern "C" fn concat(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let args: Vec<Value> = (0..len).map(|i| arg[i]).collect();
+    string_concat(globals, arg.self_value(), &args)
+}
+
+///
+/// Append *args* to the string *self_val*. Integers are appended as codepoints.
+///
+/// This is also used by `<<` operator for String receivers.
+///
+pub(crate) fn string_concat(
+    globals: &mut Globals,
+    self_val: Value,
+    args: &[Value],
+) -> Option<Value> {
+    // Collect all the arguments first, because *self_val* may appear in *args*.
+    let mut tail = vec![];
+    for val in args {
+        match val.unpack() {
+            RV::Integer(i) => match u32::try_from(i).ok().and_then(char::from_u32) {
+                Some(c) => tail.extend_from_slice(c.to_string().as_bytes()),
+                None => {
+                    globals.err_char_out_of_range(*val);
+                    return None;
+                }
+            },
+            _ => tail.extend(expect_bytes(globals, *val)?),
+        }
+    }
+    bytes_mut(globals, &self_val)?.extend(tail);
+    Some(self_val)
+}
+
+/// String#prepend
+/// - prepend(*arguments) -> self
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_PREPEND]
+extern "C" fn prepend(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    let self_val = arg.self_value();
+    let mut head = vec![];
+    for i in 0..len {
+        head.extend(expect_bytes(globals, arg[i])?);
+    }
+    let bytes = bytes_mut(globals, &self_val)?;
+    head.extend_from_slice(bytes);
+    *bytes = head;
+    Some(self_val)
+}
+
+/// String#to_sym
+/// - to_sym -> Symbol
+/// - intern -> Symbol
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_INTERN]
+extern "C" fn to_sym(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let name = globals.val_tos(arg.self_value());
+    Some(Value::new_symbol(globals.get_ident_id(&name)))
+}
+
+/// String#ljust
+/// - ljust(width, padding = ' ') -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_LJUST]
+extern "C" fn ljust(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    justify(globals, arg, len, |_| 0)
+}
+
+/// String#rjust
+/// - rjust(width, padding = ' ') -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_RJUST]
+extern "C" fn rjust(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    justify(globals, arg, len, |n| n)
+}
+
+/// String#center
+/// - center(width, padding = ' ') -> String
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/String.html#I_CENTER]
+extern "C" fn center(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    justify(globals, arg, len, |n| n / 2)
+}
+
+///
+/// Pad the receiver to the width of `arg[0]` characters with `arg[1]`, or spaces by default.
+///
+/// *left_len* gives the number of the characters padded on the left from the total number.
+/// The padding starts from its beginning on both sides, and a copy of the receiver is returned
+/// if it is already as wide as the width.
+///
+fn justify(
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+    left_len: fn(usize) -> usize,
+) -> Option<Value> {
+    if !(1..=2).contains(&len) {
+        globals.set_error(MonorubyErr::wrong_arguments_range(1, 2, len));
+        return None;
+    }
+    let s = expect_bytes(globals, arg.self_value())?;
+    let width = match arg[0].as_fixnum() {
+        Some(width) => width,
+        None => {
+            globals.err_no_implict_conv(arg[0].class_id(), INTEGER_CLASS);
+            return None;
+        }
+    };
+    let pad = if len == 2 {
+        expect_bytes(globals, arg[1])?
+    } else {
+        b" ".to_vec()
+    };
+    if pad.is_empty() {
+        globals.err_argument("zero width padding");
+        return None;
+    }
+    let s_len = char_len(&s);
+    if width <= s_len as i64 {
+        return Some(Value::new_string(s));
+    }
+    let total = width as usize - s_len;
+    let left = left_len(total);
+    let mut res = padding(&pad, left);
+    res.extend_from_slice(&s);
+    res.extend(padding(&pad, total - left));
+    Some(Value::new_string(res))
+}
+
+/// The number of characters in *s*, or bytes if it is not valid UTF-8.
+fn char_len(s: &[u8]) -> usize {
+    match std::str::from_utf8(s) {
+        Ok(s) => s.chars().count(),
+        Err(_) => s.len(),
+    }
+}
+
+/// Repeat *pad* up to *len* characters.
+fn padding(pad: &[u8], len: usize) -> Vec<u8> {
+    match std::str::from_utf8(pad) {
+        Ok(pad) => pad
+            .chars()
+            .cycle()
+            .take(len)
+            .collect::<String>()
+            .into_bytes(),
+        Err(_) => pad.iter().cycle().take(len).cloned().collect(),
+    }
+}
+
+/// Get the mutable contents of the string *val*, raising FrozenError if it is frozen.
+fn bytes_mut<'a>(globals: &mut Globals, val: &'a Value) -> Option<&'a mut Vec<u8>> {
+    if val.is_frozen() {
+        globals.err_frozen(*val);
+        return None;
+    }
+    match &mut val.rvalue_mut().kind {
+        ObjKind::Bytes(bytes) => Some(bytes),
+        _ => unreachable!(),
+    }
+}
+
+fn is_space(b: &u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r')
+}
+
+fn trim_start(s: &[u8]) -> &[u8] {
+    let pos = s.iter().position(|b| !is_space(b)).unwrap_or(s.len());
+    &s[pos..]
+}
+
+/// Trailing null characters are also removed.
+fn trim_end(s: &[u8]) -> &[u8] {
+    let pos = s
+        .iter()
+        .rposition(|b| !is_space(b) && *b != 0)
+        .map_or(0, |pos| pos + 1);
+    &s[..pos]
+}
+
+/// Remove a trailing `\r\n`, `\n` or `\r`.
+fn chomp_newline(s: &[u8]) -> &[u8] {
+    s.strip_suffix(b"\r\n")
+        .or_else(|| s.strip_suffix(b"\n"))
+        .or_else(|| s.strip_suffix(b"\r"))
+        .unwrap_or(s)
+}
+
+fn expect_bytes(globals: &mut Globals, val: Value) -> Option<Vec<u8>> {
+    match val.unpack() {
+        RV::String(bytes) => Some(bytes.to_vec()),
+        _ => {
+            globals.err_no_implict_conv(val.class_id(), STRING_CLASS);
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct FormatSpec {
+    /// `-` flag: left-justify.
+    left: bool,
+    /// `0` flag: pad numerics with zeros.
+    zero: bool,
+    /// `+` flag: show `+` for non-negative numerics.
+    plus: bool,
+    /// ` ` flag: show a space for non-negative numerics.
+    space: bool,
+    /// `#` flag: alternate form.
+    alt: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl FormatSpec {
+    fn sign(&self, negative: bool) -> &'static str {
+        if negative {
+            "-"
+        } else if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        }
+    }
+
+    fn pad(&self, sign: &str, body: &str, numeric: bool) -> String {
+        let len = sign.chars().count() + body.chars().count();
+        if len >= self.width {
+            return format!("{}{}", sign, body);
+        }
+        let fill = self.width - len;
+        if self.left {
+            format!("{}{}{}", sign, body, " ".repeat(fill))
+        } else if self.zero && numeric && self.precision.is_none() {
+            format!("{}{}{}", sign, "0".repeat(fill), body)
+        } else {
+            format!("{}{}{}", " ".repeat(fill), sign, body)
+        }
+    }
+}
+
+///
+/// Format *args* according to the format string *fmt*, in the manner of Kernel#sprintf.
+///
+/// Supported conversions are `%d %i %u %f %e %E %g %G %s %p %x %X %o %b %B %c %%`,
+/// with `-`, `0`, `+`, ` ` and `#` flags, width and precision.
+///
+pub(super) fn format_by_args(globals: &mut Globals, fmt: &str, args: &[Value]) -> Option<String> {
+    let mut res = String::new();
+    let mut chars = fmt.chars().peekable();
+    let mut args = args.iter();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            res.push(ch);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            res.push('%');
+            continue;
+        }
+        let mut spec = FormatSpec::default();
+        loop {
+            match chars.peek() {
+                Some('-') => spec.left = true,
+                Some('0') => spec.zero = true,
+                Some('+') => spec.plus = true,
+                Some(' ') => spec.space = true,
+                Some('#') => spec.alt = true,
+                _ => break,
+            }
+            chars.next();
+        }
+        spec.width = read_number(&mut chars).unwrap_or(0);
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            spec.precision = Some(read_number(&mut chars).unwrap_or(0));
+        }
+        let conv = match chars.next() {
+            Some(conv) => conv,
+            None => {
+                globals.err_argument("incomplete format specifier; use %% (double %) instead");
+                return None;
+            }
+        };
+        let val = match args.next() {
+            Some(val) => *val,
+            None => {
+                globals.err_argument("too few arguments");
+                return None;
+            }
+        };
+        let formatted = match conv {
+            'd' | 'i' | 'u' => {
+                let i = expect_integer(globals, val)?;
+                let digits = i.abs().to_str_radix(10);
+                spec.pad(
+                    spec.sign(i.is_negative()),
+                    &zero_fill(digits, spec.precision),
+                    true,
+                )
+            }
+            'x' | 'X' | 'o' | 'b' | 'B' => {
+                let i = expect_integer(globals, val)?;
+                let radix = match conv {
+                    'o' => 8,
+                    'b' | 'B' => 2,
+                    _ => 16,
+                };
+                let prefix = match conv {
+                    _ if !spec.alt || i.is_zero() => "",
+                    'o' => "0",
+                    'b' => "0b",
+                    'B' => "0B",
+                    'x' => "0x",
+                    _ => "0X",
+                };
+                let res = if i.is_negative() && !spec.plus && !spec.space {
+                    let digits = twos_complement(&i, radix);
+                    let digits = if spec.zero && !spec.left {
+                        twos_complement_fill(digits, spec.width - prefix.len().min(spec.width))
+                    } else {
+                        digits
+                    };
+                    spec.pad(prefix, &digits, false)
+                } else {
+                    let digits = zero_fill(i.abs().to_str_radix(radix), spec.precision);
+                    let sign = format!("{}{}", spec.sign(i.is_negative()), prefix);
+                    spec.pad(&sign, &digits, true)
+                };
+                if conv == 'X' {
+                    res.to_uppercase()
+                } else {
+                    res
+                }
+            }
+            'f' | 'e' | 'E' | 'g' | 'G' => {
+                let f = expect_float(globals, val)?;
+                let sign = spec.sign(f.is_sign_negative() && !f.is_nan());
+                let body = if f.is_nan() {
+                    "NaN".to_string()
+                } else if f.is_infinite() {
+                    "Inf".to_string()
+                } else {
+                    let f = f.abs();
+                    match conv {
+                        'f' => format!("{:.*}", spec.precision.unwrap_or(6), f),
+                        'e' | 'E' => format_exp(f, spec.precision.unwrap_or(6), conv == 'E'),
+                        _ => format_general(f, spec.precision.unwrap_or(6), spec.alt, conv == 'G'),
+                    }
+                };
+                spec.pad(sign, &body, f.is_finite())
+            }
+            'c' => {
+                let c = match val.unpack() {
+                    RV::Integer(i) => match u32::try_from(i).ok().and_then(char::from_u32) {
+                        Some(c) => c.to_string(),
+                        None => {
+                            globals.err_char_out_of_range(val);
+                            return None;
+                        }
+                    },
+                    _ => globals.val_tos(val).chars().take(1).collect(),
+                };
+                spec.pad("", &c, false)
+            }
+            's' | 'p' => {
+                let s = if conv == 's' {
+                    globals.val_tos(val)
+                } else {
+                    globals.val_inspect(val)
+                };
+                let s = match spec.precision {
+                    Some(prec) => s.chars().take(prec).collect(),
+                    None => s,
+                };
+                spec.pad("", &s, false)
+            }
+            _ => {
+                globals.err_argument(&format!("malformed format string - %{}", conv));
+                return None;
+            }
+        };
+        res += &formatted;
+    }
+    Some(res)
+}
+
+fn read_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<usize> {
+    let mut n = None;
+    while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+        n = Some(n.unwrap_or(0) * 10 + d as usize);
+        chars.next();
+    }
+    n
+}
+
+fn expect_integer(globals: &mut Globals, val: Value) -> Option<BigInt> {
+    match val.unpack() {
+        RV::Integer(i) => Some(BigInt::from(i)),
+        RV::BigInt(b) => Some(b.clone()),
+        RV::Float(f) => match BigInt::from_f64(f.trunc()) {
+            Some(b) => Some(b),
+            None => {
+                globals.err_argument(&format!("{} can not be converted to Integer", f));
+                None
+            }
+        },
+        RV::String(s) => match String::from_utf8_lossy(s).trim().parse::<BigInt>() {
+            Ok(b) => Some(b),
+            Err(_) => {
+                globals.err_argument(&format!(
+                    "invalid value for Integer(): {}",
+                    globals.val_inspect(val)
+                ));
+                None
+            }
+        },
+        _ => {
+            globals.err_no_implict_conv(val.class_id(), INTEGER_CLASS);
+            None
+        }
+    }
+}
+
+fn expect_float(globals: &mut Globals, val: Value) -> Option<f64> {
+    match val.unpack() {
+        RV::Integer(i) => Some(i as f64),
+        RV::BigInt(b) => Some(num::ToPrimitive::to_f64(b).unwrap_or(f64::INFINITY)),
+        RV::Float(f) => Some(f),
+        RV::String(s) => match String::from_utf8_lossy(s).trim().parse::<f64>() {
+            Ok(f) => Some(f),
+            Err(_) => {
+                globals.err_argument(&format!(
+                    "invalid value for Float(): {}",
+                    globals.val_inspect(val)
+                ));
+                None
+            }
+        },
+        _ => {
+            globals.err_no_implict_conv(val.class_id(), FLOAT_CLASS);
+            None
+        }
+    }
+}
+
+/// Pad *digits* with leading zeros up to *precision* digits.
+fn zero_fill(digits: String, precision: Option<usize>) -> String {
+    match precision {
+        Some(prec) if prec > digits.len() => {
+            format!("{}{}", "0".repeat(prec - digits.len()), digits)
+        }
+        _ => digits,
+    }
+}
+
+///
+/// Two's complement notation of a negative integer *i*, as in `"%x" % -255 #=> "..f01"`.
+///
+fn twos_complement(i: &BigInt, radix: u32) -> String {
+    let digit_bits = match radix {
+        2 => 1,
+        8 => 3,
+        _ => 4,
+    };
+    let bits = (i.bits() / digit_bits + 2) * digit_bits;
+    let n: BigInt = (BigInt::one() << bits) + i;
+    debug_assert!(!n.is_zero());
+    let max = std::char::from_digit(radix - 1, radix).unwrap();
+    let s = n.to_str_radix(radix);
+    format!("..{}{}", max, s.trim_start_matches(max))
+}
+
+///
+/// Widen a two's complement notation *digits* to *width* by repeating its leading digit,
+/// as in `"%08b" % -11 #=> "..110101"`.
+///
+fn twos_complement_fill(digits: String, width: usize) -> String {
+    let len = digits.chars().count();
+    if len >= width {
+        return digits;
+    }
+    let max = digits.chars().nth(2).unwrap();
+    format!("..{}{}", max.to_string().repeat(width - len), &digits[2..])
+}
+
+///
+/// Scientific notation of a non-negative *f*, as in `"%e" % 12345.678 #=> "1.234568e+04"`.
+///
+fn format_exp(f: f64, precision: usize, upper: bool) -> String {
+    let s = format!("{:.*e}", precision, f);
+    let (mantissa, exp) = s.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    let e = if upper { 'E' } else { 'e' };
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{}{}{}{:02}", mantissa, e, sign, exp.abs())
+}
+
+///
+/// General notation of a non-negative *f*, which chooses `%e` or `%f` by its exponent.
+///
+/// Trailing zeros are removed unless *alt* is set.
+///
+fn format_general(f: f64, precision: usize, alt: bool, upper: bool) -> String {
+    let precision = precision.max(1);
+    let exp = if f == 0.0 {
+        0
+    } else {
+        let s = format!("{:.*e}", precision - 1, f);
+        s.split_once('e').unwrap().1.parse::<i32>().unwrap()
+    };
+    let s = if exp < -4 || exp >= precision as i32 {
+        format_exp(f, precision - 1, upper)
+    } else {
+        format!("{:.*}", (precision as i32 - 1 - exp) as usize, f)
+    };
+    if alt {
+        return s;
+    }
+    let (mantissa, exp) = match s.find(['e', 'E']) {
+        Some(pos) => s.split_at(pos),
+        None => (s.as_str(), ""),
+    };
+    let mantissa = if mantissa.contains('.') {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        mantissa
+    };
+    format!("{}{}", mantissa, exp)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_string_rem() {
+        run_test(r#""%d apples" % 5"#);
+        run_test(r#""[%5d][%-5d][%05d]" % 42"#);
+        run_test(r#""%.3f" % 3.14159"#);
+        run_test(r#""%8.2f|" % -2.5"#);
+        run_test(r#""%x" % 255"#);
+        run_test(r#""%X" % 255"#);
+        run_test(r#""%x" % -255"#);
+        run_test(r#""%s!" % :sym"#);
+        run_test(r#""%-6s|" % "abc""#);
+        run_test(r#""%p" % "abc""#);
+        run_test(r#""%c" % 97"#);
+        run_test(r#"a = 100; "%%%d%%" % a"#);
+        run_test(r#""%d-%s" % [1, "a"]"#);
+        run_test(r#""%s=%d" % ["x", 1]"#);
+        run_test(r#""[%5d][%-6s][%08.3f]" % [42, :ab, -3.14159]"#);
+        run_test(r#"a = [255, 255]; "%x|%#o" % a"#);
+        run_test(r#""%s" % [[1, 2]]"#);
+        run_test(r#""none" % []"#);
+    }
+
+    #[test]
+    fn test_start_with() {
+        run_test(r#""monoruby".start_with?("mono")"#);
+        run_test(r#""monoruby".start_with?("ruby")"#);
+        run_test(r#""monoruby".start_with?("ruby", "mon")"#);
+        run_test(r#""monoruby".start_with?"#);
+        run_test(r#""monoruby".start_with?("")"#);
+        run_test(r#""mono".start_with?("monoruby")"#);
+    }
+
+    #[test]
+    fn test_end_with() {
+        run_test(r#""monoruby".end_with?("ruby")"#);
+        run_test(r#""monoruby".end_with?("mono")"#);
+        run_test(r#""monoruby".end_with?("mono", "by")"#);
+        run_test(r#""ruby".end_with?("monoruby")"#);
+    }
+
+    #[test]
+    fn test_include() {
+        run_test(r#""monoruby".include?("noru")"#);
+        run_test(r#""monoruby".include?("java")"#);
+        run_test(r#""monoruby".include?("")"#);
+        run_test(r#""".include?("a")"#);
+    }
+
+    #[test]
+    fn test_strip() {
+        run_test(r#"" \t monoruby \n ".strip"#);
+        run_test(r#"" \t monoruby \n ".lstrip"#);
+        run_test(r#"" \t monoruby \n".rstrip"#);
+        run_test(r#""   ".strip"#);
+        run_test(r#""monoruby".strip"#);
+    }
+
+    #[test]
+    fn test_chomp() {
+        run_test(r#""monoruby\n".chomp"#);
+        run_test(r#""monoruby\r\n".chomp"#);
+        run_test(r#""monoruby\r".chomp"#);
+        run_test(r#""monoruby\n\n".chomp"#);
+        run_test(r#""monoruby".chomp"#);
+        run_test(r#""monoruby".chomp("ruby")"#);
+        run_test(r#""monoruby".chomp("java")"#);
+        run_test(r#""monoruby\r\n".chomp("\n")"#);
+        run_test(r#""monoruby\n\r\n\n".chomp("")"#);
+    }
+
+    #[test]
+    fn test_chop() {
+        run_test(r#""monoruby".chop"#);
+        run_test(r#""monoruby\r\n".chop"#);
+        run_test(r#""monoruby\n\r".chop"#);
+        run_test(r#""rubyあ".chop"#);
+        run_test(r#""".chop"#);
+    }
+
+    #[test]
+    fn test_justify() {
+        run_test(r#""mono".ljust(8)"#);
+        run_test(r#""mono".rjust(8)"#);
+        run_test(r#""mono".center(8)"#);
+        run_test(r#""mono".center(9)"#);
+        run_test(r#""mono".ljust(11, "123")"#);
+        run_test(r#""mono".rjust(11, "123")"#);
+        run_test(r#""mono".center(11, "123")"#);
+        run_test(r#""あい".center(7, "*=")"#);
+        run_test(r#""mono".ljust(8, "あい")"#);
+        run_test(r#""monoruby".ljust(4)"#);
+        run_test(r#""monoruby".rjust(8, "-")"#);
+        run_test(r#""monoruby".center(-1)"#);
+        run_test(r#"a = "mono"; a.ljust(2).equal?(a)"#);
+    }
+
+    #[test]
+    fn test_justify_error() {
+        run_test_error(
+            r#""mono".ljust(8, "")"#,
+            MonorubyErrKind::Argument(String::new()),
+        );
+        for code in [r#""mono".rjust"#, r#""mono".center(8, "a", "b")"#] {
+            run_test_error(code, MonorubyErrKind::WrongArguments(String::new()));
+        }
+        run_test_error(
+            r#""mono".center("8")"#,
+            MonorubyErrKind::Type(String::new()),
+        );
+    }
+
+    #[test]
+    fn test_replace() {
+        run_test(r#"a = "mono"; a.replace("ruby"); a"#);
+        run_test(r#"a = "mono"; b = a; a.replace("ruby"); b"#);
+        run_test(r#"a = "mono"; a.replace("ruby").equal?(a)"#);
+    }
+
+    #[test]
+    fn test_concat() {
+        run_test(r#"a = "mono"; a.concat("ruby"); a"#);
+        run_test(r#"a = "mono"; a.concat("ru", "by", 33); a"#);
+        run_test(r#"a = "mono"; a.concat; a"#);
+        run_test(r#"a = "mono"; a.concat(a, a); a"#);
+        run_test(r#"a = "mono"; a << "ruby" << 12354; a"#);
+        run_test(r#"a = "mono"; b = "ruby"; a << b; a"#);
+        run_test(r#"a = "mono"; (a << 0x21).equal?(a)"#);
+    }
+
+    #[test]
+    fn test_prepend() {
+        run_test(r#"a = "ruby"; a.prepend("mono"); a"#);
+        run_test(r#"a = "by"; a.prepend("mo", "no", "ru"); a"#);
+    }
+
+    #[test]
+    fn test_modify_frozen() {
+        for code in [
+            r#"a = "mono".freeze; a.replace("ruby")"#,
+            r#"a = "mono".freeze; a.concat("ruby")"#,
+            r#"a = "mono".freeze; a << "ruby""#,
+            r#"a = "mono".freeze; a.prepend("ruby")"#,
+        ] {
+            run_test_error(code, MonorubyErrKind::Frozen(String::new()));
+        }
+        run_test(r#"a = "mono".freeze; begin; a << "ruby"; rescue => e; e.message; end"#);
+    }
+
+    #[test]
+    fn test_format() {
+        run_test(r#"format("%s=%d", "x", 1)"#);
+        run_test(r#"sprintf("[%6.2f][%-3s][%3s]", 3.14159, "a", :b)"#);
+        run_test(r#"format("%.5d|%x", 42, 4294967296)"#);
+    }
+
+    #[test]
+    fn test_format_radix() {
+        run_test(r#""%b" % 10"#);
+        run_test(r#""%b" % -11"#);
+        run_test(r#""%08b" % -11"#);
+        run_test(r#""%+b" % -11"#);
+        run_test(r#""%#b" % 10"#);
+        run_test(r#""%#B" % 10"#);
+        run_test(r#""%o" % 8"#);
+        run_test(r#""%#o" % 8"#);
+        run_test(r#""%o" % -8"#);
+        run_test(r#""%#x" % 255"#);
+        run_test(r#""%#X" % 255"#);
+        run_test(r#""%#010x" % 255"#);
+        run_test(r#""% x" % -255"#);
+        run_test(r#"format("%+d|% d|%+d", 5, 5, -5)"#);
+    }
+
+    #[test]
+    fn test_format_float() {
+        run_test(r#""%e" % 12345.678"#);
+        run_test(r#""%E" % 0.000123"#);
+        run_test(r#""%.2e" % -1.5"#);
+        run_test(r#""%12.3e|" % 3.0"#);
+        run_test(r#""%g" % 1234567.0"#);
+        run_test(r#""%g" % 123456.0"#);
+        run_test(r#""%g" % 0.0001"#);
+        run_test(r#""%g" % 0.00001"#);
+        run_test(r#""%G" % 1.0e-10"#);
+        run_test(r#""%#g" % 1.5"#);
+        run_test(r#""%.3g" % 3.14159"#);
+        run_test(r#""%g" % 0"#);
+        run_test(r#""%+.1f|" % 2.25"#);
+        run_test(r#""% .3f" % 3.14159"#);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::executor::op::send_values;
+use crate::*;
+
+//
+// Symbol class
+//
+
+/// A name which never appears in scripts. It is interned into a copy of the identifier table
+/// to find the next id.
+const SENTINEL_NAME: &str = "\0all_symbols\0";
+
+/// The name of the hidden method which is the body of the procs made by `Symbol#to_proc`.
+/// It is not a valid method name, so it can not be called from Ruby.
+const PROC_BODY_NAME: &str = "/to_proc_body";
+
+pub(super) fn init(globals: &mut Globals) {
+    globals.define_builtin_singleton_func(SYMBOL_CLASS, "all_symbols", all_symbols, 0);
+    globals.define_builtin_func(SYMBOL_CLASS, "length", length, 0);
+    globals.define_builtin_func(SYMBOL_CLASS, "size", length, 0);
+    globals.define_builtin_func(SYMBOL_CLASS, "upcase", upcase, 0);
+    globals.define_builtin_func(SYMBOL_CLASS, "<=>", cmp, 1);
+    globals.define_builtin_func(SYMBOL_CLASS, "to_proc", to_proc, 0);
+    globals.define_builtin_func(SYMBOL_CLASS, PROC_BODY_NAME, to_proc_body, -1);
+}
+
+fn symbol_name(globals: &Globals, val: Value) -> &str {
+    match val.unpack() {
+        RV::Symbol(id) => globals.get_ident_name(id),
+        _ => unreachable!(),
+    }
+}
+
+/// ### Symbol.all_symbols
+/// - all_symbols -> [Symbol]
+///
+/// Ids of the identifier table are numbered from 1 in the order of interning,
+/// so all symbols are the ids below the next one.
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Symbol/s/all_symbols.html]
+extern "C" fn all_symbols(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    _arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let next_id = globals.id_store.clone().get_ident_id(SENTINEL_NAME).get();
+    let v = (1..next_id)
+        .map(|id| Value::new_symbol(IdentId::from(id)))
+        .collect();
+    Some(Value::new_array(v))
+}
+
+/// ### Symbol#length
+/// - length -> Integer
+/// - size -> Integer
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Symbol.html#I_LENGTH]
+extern "C" fn length(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let len = symbol_name(globals, arg.self_value()).chars().count();
+    Some(Value::new_integer(len as i64))
+}
+
+/// ### Symbol#upcase
+/// - upcase -> Symbol
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Symbol.html#I_UPCASE]
+extern "C" fn upcase(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let name = symbol_name(globals, arg.self_value()).to_uppercase();
+    Some(Value::new_symbol(globals.get_ident_id(&name)))
+}
+
+/// ### Symbol#<=>
+/// - self <=> other -> -1 | 0 | 1 | nil
+///
+/// Symbols are compared by their names. Return nil if *other* is not a Symbol.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Symbol.html#I_--3C--3D--3E]
+extern "C" fn cmp(_vm: &mut Interp, globals: &mut Globals, arg: Arg, _len: usize) -> Option<Value> {
+    let rhs = match arg[0].unpack() {
+        RV::Symbol(_) => symbol_name(globals, arg[0]),
+        _ => return Some(Value::nil()),
+    };
+    let ord = symbol_name(globals, arg.self_value()).cmp(rhs);
+    Some(Value::new_integer(ord as i64))
+}
+
+/// ### Symbol#to_proc
+/// - to_proc -> Proc
+///
+/// The proc calls the method named *self* of the first argument with the rest of the arguments.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Symbol.html#I_TO_PROC]
+extern "C" fn to_proc(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let name = globals.get_ident_id(PROC_BODY_NAME);
+    let func_id = globals.get_method_inner(SYMBOL_CLASS, name).unwrap();
+    Some(Value::new_proc(func_id, arg.self_value(), Value::nil()))
+}
+
+extern "C" fn to_proc_body(
+    vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    len: usize,
+) -> Option<Value> {
+    if len == 0 {
+        globals.err_argument("no receiver given");
+        return None;
+    }
+    let name = match arg.self_value().unpack() {
+        RV::Symbol(id) => id,
+        _ => unreachable!(),
+    };
+    let args: Vec<Value> = (1..len).map(|i| arg[i]).collect();
+    send_values(vm, globals, arg[0], name, &args)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_symbol_length() {
+        run_test(":hello.length");
+        run_test(":hello.length == 5");
+        run_test(":hello.size");
+        run_test(":a.frozen?");
+    }
+
+    #[test]
+    fn test_all_symbols() {
+        run_test("Symbol.all_symbols.count(:puts)");
+        run_test(
+            r#"
+            a = Symbol.all_symbols
+            s = "qwzx_" + "sym"
+            [a.count(s.to_sym), Symbol.all_symbols.count(s.to_sym)].inspect
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_symbol_upcase() {
+        run_test(":hello.upcase");
+        run_test(":Hello_World.upcase == :HELLO_WORLD");
+    }
+
+    #[test]
+    fn test_symbol_cmp() {
+        run_test(":b <=> :a");
+        run_test(":a <=> :b");
+        run_test(":a <=> :a");
+        run_test(":abc <=> :ab");
+        run_test(":a <=> 1");
+    }
+
+    #[test]
+    fn test_symbol_to_proc() {
+        run_test("h = [1, 2, 2].group_by(&:itself); [h[1], h[2]].inspect");
+        run_test("[1, 2, 3].inject(&:+)");
+        run_test(r#"[" b", "a "].sort_by(&:strip).inspect"#);
+        run_test(r#"["a", "bb"].partition(&:frozen?).inspect"#);
+        run_test(":upcase.to_proc.class");
+        run_test_error(
+            "[nil].each(&:foo)",
+            MonorubyErrKind::MethodNotFound(IdentId::_ADD),
+        );
+    }
+
+    #[test]
+    fn test_symbol_index() {
+        run_test(":hello[1]");
+        run_test(":hello[-1]");
+        run_test(":hello[5]");
+        run_test(":hello[-6]");
+        run_test(":hello[1..3]");
+        run_test(":hello[1...-1]");
+        run_test(":hello[-3..nil]");
+        run_test(":hello[5..9]");
+        run_test(":hello[6..9]");
+        run_test(":hello[3..1]");
+    }
+}
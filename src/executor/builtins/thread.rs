@@ -0,0 +1,89 @@
+use crate::*;
+
+//
+// Thread class
+//
+// Threads are not concurrent. `Thread.new` runs its block to completion at once,
+// and keeps the result for `Thread#value`. So scripts using threads run serially.
+//
+
+/// The name of the hidden instance variable which holds the result of the block.
+/// It is not a valid instance variable name, so it can not be accessed from Ruby.
+const VALUE_VAR: &str = "/value";
+
+pub(super) fn init(globals: &mut Globals, class_id: ClassId) {
+    globals.define_builtin_singleton_func(class_id, "new", new, -1);
+    globals.define_builtin_func(class_id, "value", value, 0);
+    globals.define_builtin_func(class_id, "join", join, 0);
+}
+
+/// ### Thread.new
+/// - new(*arg) {|*arg| ... } -> Thread
+///
+/// The block is called with *arg* and runs to completion before this method returns.
+/// An exception raised in the block is propagated to the caller at once.
+///
+/// [https://docs.ruby-lang.org/ja/latest/method/Thread/s/new.html]
+extern "C" fn new(vm: &mut Interp, globals: &mut Globals, arg: Arg, len: usize) -> Option<Value> {
+    let block = arg.block(len);
+    if block.is_nil() {
+        globals.err_no_block_given();
+        return None;
+    }
+    let args: Vec<Value> = (0..len).map(|i| arg[i]).collect();
+    let res = vm.invoke_block(globals, block, &args)?;
+    let thread = Value::new_object(arg.self_value().as_class());
+    let name = globals.get_ident_id(VALUE_VAR);
+    thread.rvalue_mut().set_var(name, res);
+    Some(thread)
+}
+
+/// ### Thread#value
+/// - value -> object
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Thread.html#I_VALUE]
+extern "C" fn value(
+    _vm: &mut Interp,
+    globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    let name = globals.get_ident_id(VALUE_VAR);
+    let res = arg.self_value().rvalue().get_var(name);
+    Some(res.unwrap_or(Value::nil()))
+}
+
+/// ### Thread#join
+/// - join -> self
+///
+/// The thread has already finished, so this method returns at once.
+///
+/// [https://docs.ruby-lang.org/ja/latest/class/Thread.html#I_JOIN]
+extern "C" fn join(
+    _vm: &mut Interp,
+    _globals: &mut Globals,
+    arg: Arg,
+    _len: usize,
+) -> Option<Value> {
+    Some(arg.self_value())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_thread() {
+        run_test("Thread.new { 1 + 1 }.value == 2");
+        run_test("Thread.new { 1 + 1 }.value");
+        run_test("Thread.new(3, 4) { |a, b| a * b }.value");
+        run_test("Thread.new { nil }.value.inspect");
+        run_test("$x = 0; t = Thread.new { $x += 5 }; t.join.value + $x");
+        run_test(
+            r#"
+            def f(n); Thread.new(n) { |i| i * 10 }; end
+            f(1).value + f(2).value
+            "#,
+        );
+    }
+}
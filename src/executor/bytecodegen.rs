@@ -38,6 +38,8 @@ impl Funcs {
             None,
             FuncId(0),
             vec![],
+            vec![],
+            false,
             Node::new_nil(Loc(0, 0)),
             sourceinfo,
         )])
@@ -51,12 +53,15 @@ impl Funcs {
         &mut self,
         name: Option<String>,
         args: Vec<String>,
+        optional: Vec<Node>,
+        rest: bool,
         ast: Node,
         sourceinfo: SourceInfoRef,
     ) -> FuncId {
         let fid = self.next_func_id();
-        self.0
-            .push(FuncInfo::new_normal(name, fid, args, ast, sourceinfo));
+        self.0.push(FuncInfo::new_normal(
+            name, fid, args, optional, rest, ast, sourceinfo,
+        ));
         fid
     }
 
@@ -93,6 +98,8 @@ pub struct ConstSiteInfo {
     pub prefix: Vec<IdentId>,
     /// Is toplevel?. (e.g. ::Foo)
     pub toplevel: bool,
+    /// Returns nil instead of raising an error when not defined. (e.g. Foo ||= 1)
+    pub ignore_undefined: bool,
     /// Inline constant cache.
     pub cache: (usize, Option<Value>), //(version, value)
 }
@@ -129,6 +136,10 @@ pub struct FnStore {
     constsite_info: Vec<ConstSiteInfo>,
     /// literal values.
     literals: Vec<Value>,
+    /// if true, string literals are frozen and deduplicated.
+    pub frozen_string_literal: bool,
+    /// interned frozen string literals.
+    string_literals: HashMap<Vec<u8>, u32>,
 }
 
 impl std::ops::Index<FuncId> for FnStore {
@@ -186,6 +197,8 @@ impl FnStore {
             callsite_info: vec![],
             constsite_info: vec![],
             literals: vec![],
+            frozen_string_literal: false,
+            string_literals: HashMap::default(),
         }
     }
 
@@ -197,6 +210,13 @@ impl FnStore {
         &mut self.functions.0
     }
 
+    /// Forget the machine code of all functions, which is owned by the `Interp` that generated it.
+    pub(super) fn clear_jit_labels(&mut self) {
+        for func in self.funcs_mut().iter_mut() {
+            func.jit_label = None;
+        }
+    }
+
     fn add_method_def(&mut self, name: IdentId, func: FuncId) -> MethodDefId {
         let info = MethodDefInfo { name, func };
         let id = self.method_def_info.len();
@@ -215,6 +235,23 @@ impl FnStore {
         self.literals.push(val);
         constants as u32
     }
+
+    /// register a new string literal.
+    ///
+    /// If `frozen_string_literal` is true, the literal is frozen and shared with identical ones.
+    fn new_string_literal(&mut self, bytes: Vec<u8>) -> u32 {
+        if !self.frozen_string_literal {
+            return self.new_literal(Value::new_string(bytes));
+        }
+        if let Some(id) = self.string_literals.get(&bytes) {
+            return *id;
+        }
+        let val = Value::new_string(bytes.clone());
+        val.freeze();
+        let id = self.new_literal(val);
+        self.string_literals.insert(bytes, id);
+        id
+    }
 }
 
 impl FnStore {
@@ -224,9 +261,9 @@ impl FnStore {
         id_store: &mut IdentifierTable,
         sourceinfo: SourceInfoRef,
     ) -> Result<()> {
-        let mut fid = self
-            .functions
-            .add_normal_func(None, vec![], ast, sourceinfo.clone());
+        let mut fid =
+            self.functions
+                .add_normal_func(None, vec![], vec![], false, ast, sourceinfo.clone());
         self.main = Some(fid);
 
         while self.len() > fid.0 as usize {
@@ -282,6 +319,8 @@ pub struct FuncInfo {
     /// arity of this function.
     /// -1 for variable numbers.
     arity: i32,
+    /// the minimum number of arguments, which differs from *arity* if there are optional or rest parameters.
+    min_arity: i32,
     /// address of JIT function.
     jit_label: Option<CodePtr>,
     /// stack offset
@@ -296,14 +335,19 @@ impl FuncInfo {
         name: Option<String>,
         func_id: FuncId,
         args: Vec<String>,
+        optional: Vec<Node>,
+        rest: bool,
         ast: Node,
         sourceinfo: SourceInfoRef,
     ) -> Self {
-        let info = NormalFuncInfo::new(func_id, name.clone(), args, ast, sourceinfo);
+        let info =
+            NormalFuncInfo::new(func_id, name.clone(), args, optional, rest, ast, sourceinfo);
+        let params_len = info.params_len();
         Self {
             id: info.id,
             name,
-            arity: info.args.len() as i32,
+            arity: if rest { -1 } else { params_len as i32 },
+            min_arity: (params_len - info.optional.len()) as i32,
             jit_label: None,
             stack_offset: 0,
             inst_pc: BcPc::default(),
@@ -316,6 +360,7 @@ impl FuncInfo {
             id,
             name: Some(name),
             arity,
+            min_arity: arity.max(0),
             jit_label: None,
             stack_offset: if arity == -1 {
                 -1
@@ -333,10 +378,18 @@ impl FuncInfo {
         self.id
     }
 
+    pub(super) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
     pub(super) fn arity(&self) -> i32 {
         self.arity
     }
 
+    pub(super) fn min_arity(&self) -> i32 {
+        self.min_arity
+    }
+
     pub(super) fn stack_offset(&self) -> i64 {
         self.stack_offset
     }
@@ -368,10 +421,53 @@ impl FuncInfo {
     }
 }
 
+/// Jump targets of a loop for `break` and `next`.
 #[derive(Debug, Clone, PartialEq)]
-enum LoopKind {
-    For,
-    While,
+struct LoopContext {
+    /// label for `break`, just after the loop.
+    break_pos: usize,
+    /// label for `next`, where the next iteration starts.
+    next_pos: usize,
+    /// the register which holds the value of the loop, if used.
+    ret_reg: Option<BcReg>,
+}
+
+/// An exception handler, which is given by labels since instructions may be inserted later.
+#[derive(Debug, Clone, PartialEq)]
+struct HandlerInfo {
+    /// the start of the protected instructions.
+    start: usize,
+    /// the end of the protected instructions. (exclusive)
+    end: usize,
+    /// the start of the `rescue` clause.
+    dest: usize,
+    /// the register to which the exception object is stored.
+    err_reg: Option<BcReg>,
+    /// whether the handler is an `ensure` clause, which catches any exception.
+    ensure: bool,
+}
+
+/// An `ensure` clause, which is inlined where `break`, `next` or `return` escapes from it.
+#[derive(Debug, Clone, PartialEq)]
+struct EnsureContext {
+    body: Node,
+    /// the number of the loops enclosing the `begin`.
+    loop_depth: usize,
+}
+
+///
+/// An entry of the exception table of a function.
+///
+/// Errors raised by instructions in *range* are rescued by jumping to *dest*.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExceptionEntry {
+    pub range: std::ops::Range<usize>,
+    pub dest: usize,
+    /// the register to which the exception object is stored.
+    pub err_reg: Option<u16>,
+    /// whether the handler catches exceptions which are not rescued by a bare `rescue`.
+    pub ensure: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -380,8 +476,16 @@ pub(crate) struct IrContext {
     ir: Vec<(BcIr, Loc)>,
     /// destination labels.
     labels: Vec<Option<InstId>>,
-    /// loop information.
-    loops: Vec<(LoopKind, usize, Option<BcReg>)>, // (kind, label for exit, return register)
+    /// contexts of the enclosing loops. the innermost one is the last.
+    loops: Vec<LoopContext>,
+    /// exception handlers. inner ones precede outer ones.
+    handlers: Vec<HandlerInfo>,
+    /// the enclosing `ensure` clauses. the innermost one is the last.
+    ensures: Vec<EnsureContext>,
+    /// hidden local variables which hold the states of flip-flops.
+    flip_flops: Vec<BcLocal>,
+    /// registers which hold class objects of the enclosing class bodies.
+    class_regs: Vec<BcReg>,
 }
 
 impl IrContext {
@@ -390,6 +494,40 @@ impl IrContext {
             ir: vec![],
             labels: vec![],
             loops: vec![],
+            handlers: vec![],
+            ensures: vec![],
+            flip_flops: vec![],
+            class_regs: vec![],
+        }
+    }
+
+    /// The register which holds `self`.
+    ///
+    /// In a class body, `self` is the class object being defined.
+    fn self_reg(&self) -> BcReg {
+        self.class_regs.last().cloned().unwrap_or(BcReg::Self_)
+    }
+
+    /// Initialize the states of all flip-flops at the entry of the function.
+    fn init_flip_flops(&mut self) {
+        let init = self
+            .flip_flops
+            .iter()
+            .map(|local| BcIr::Nil((*local).into()))
+            .collect();
+        self.insert_at_entry(init);
+    }
+
+    /// Insert *ops* at the entry of the function.
+    fn insert_at_entry(&mut self, ops: Vec<BcIr>) {
+        let len = ops.len();
+        if len == 0 {
+            return;
+        }
+        let ops = ops.into_iter().map(|op| (op, Loc::default()));
+        self.ir.splice(0..0, ops);
+        for label in self.labels.iter_mut().flatten() {
+            label.0 += len as u32;
         }
     }
 
@@ -434,6 +572,12 @@ pub(super) struct NormalFuncInfo {
     pub sourcemap: Vec<Loc>,
     /// the name of arguments.
     args: Vec<String>,
+    /// default values of the optional parameters, which are at the end of the positional ones.
+    optional: Vec<Node>,
+    /// whether the last of *args* is a rest parameter.
+    rest: bool,
+    /// exception table.
+    exception_table: Vec<ExceptionEntry>,
     /// local variables.
     locals: HashMap<String, u16>,
     /// The current register id.
@@ -450,15 +594,21 @@ impl NormalFuncInfo {
         id: FuncId,
         name: Option<String>,
         args: Vec<String>,
+        optional: Vec<Node>,
+        rest: bool,
         ast: Node,
         sourceinfo: SourceInfoRef,
     ) -> Self {
+        let needs_init = !optional.is_empty() || rest;
         let mut info = NormalFuncInfo {
             id,
             name,
             bytecode: vec![],
             sourcemap: vec![],
             args: args.clone(),
+            optional,
+            rest,
+            exception_table: vec![],
             locals: HashMap::default(),
             temp: 0,
             reg_num: 0,
@@ -468,6 +618,14 @@ impl NormalFuncInfo {
         args.into_iter().for_each(|name| {
             info.add_local(name);
         });
+        // a hidden local variable which holds the block passed by the caller.
+        // callers set it just after the arguments.
+        info.add_local("/block".to_string());
+        // a hidden local variable which holds the number of the given arguments.
+        // InitOpt sets it just after the block.
+        if needs_init {
+            info.add_local("/argc".to_string());
+        }
         info
     }
 
@@ -481,6 +639,17 @@ impl NormalFuncInfo {
         &self.bytecode
     }
 
+    pub(super) fn exception_table(&self) -> &[ExceptionEntry] {
+        &self.exception_table
+    }
+
+    /// Find the innermost exception handler which covers the instruction at *idx*.
+    pub(super) fn find_handler(&self, idx: usize) -> Option<&ExceptionEntry> {
+        self.exception_table
+            .iter()
+            .find(|entry| entry.range.contains(&idx))
+    }
+
     /// get the next register id.
     fn next_reg(&self) -> BcTemp {
         BcTemp(self.temp)
@@ -515,6 +684,23 @@ impl NormalFuncInfo {
         }
     }
 
+    /// The hidden local variable which holds the block of this function.
+    fn block_local(&self) -> BcLocal {
+        BcLocal(self.args.len() as u16)
+    }
+
+    /// The hidden local variable which holds the number of the given arguments.
+    ///
+    /// This exists only in functions with optional or rest parameters.
+    fn argc_local(&self) -> BcLocal {
+        BcLocal(self.args.len() as u16 + 1)
+    }
+
+    /// The number of the positional parameters, which does not include the rest parameter.
+    fn params_len(&self) -> usize {
+        self.args.len() - self.rest as usize
+    }
+
     fn find_local(&mut self, ident: &str) -> BcLocal {
         match self.locals.get(ident) {
             Some(local) => BcLocal(*local),
@@ -533,6 +719,7 @@ impl NormalFuncInfo {
         &mut self,
         ir: &mut IrContext,
         dst: Option<BcLocal>,
+        prefix: Vec<IdentId>,
         name: IdentId,
         loc: Loc,
     ) {
@@ -540,7 +727,36 @@ impl NormalFuncInfo {
             Some(local) => local.into(),
             None => self.push().into(),
         };
-        ir.push(BcIr::LoadConst(reg, name), loc);
+        ir.push(BcIr::LoadConst(reg, prefix, name), loc);
+    }
+
+    /// Convert the qualifier of a constant reference (e.g. `Float` in `Float::INFINITY`).
+    fn gen_const_prefix(
+        &self,
+        id_store: &mut IdentifierTable,
+        toplevel: bool,
+        parent: Option<Box<Node>>,
+        prefix: Vec<String>,
+        loc: Loc,
+    ) -> Result<Vec<IdentId>> {
+        if toplevel {
+            return Err(MonorubyErr::unsupported_feature(
+                "toplevel constant",
+                loc,
+                self.sourceinfo.clone(),
+            ));
+        }
+        if parent.is_some() {
+            return Err(MonorubyErr::unsupported_feature(
+                "constant with an expression qualifier",
+                loc,
+                self.sourceinfo.clone(),
+            ));
+        }
+        Ok(prefix
+            .into_iter()
+            .map(|s| id_store.get_ident_id_from_string(s))
+            .collect())
     }
 
     fn gen_store_const(&mut self, ir: &mut IrContext, src: BcReg, name: IdentId, loc: Loc) {
@@ -562,6 +778,8 @@ impl NormalFuncInfo {
         ir.push(BcIr::Literal(reg, id), Loc::default());
     }
 
+    /// Integers which fit in i32 are embedded in the instruction,
+    /// and others are stored in the literal table.
     fn gen_integer(&mut self, ctx: &mut FnStore, ir: &mut IrContext, dst: Option<BcLocal>, i: i64) {
         if let Ok(i) = i32::try_from(i) {
             let reg = match dst {
@@ -593,9 +811,16 @@ impl NormalFuncInfo {
         dst: Option<BcLocal>,
         b: Vec<u8>,
     ) {
-        self.gen_literal(ctx, ir, dst, Value::new_string(b));
+        let reg = match dst {
+            Some(local) => local.into(),
+            None => self.push().into(),
+        };
+        let id = ctx.new_string_literal(b);
+        ir.push(BcIr::Literal(reg, id), Loc::default());
     }
 
+    /// Bignum literals which fit in i64 are treated as integer literals,
+    /// so that the same value always has the same representation.
     fn gen_bigint(
         &mut self,
         ctx: &mut FnStore,
@@ -603,7 +828,11 @@ impl NormalFuncInfo {
         dst: Option<BcLocal>,
         bigint: BigInt,
     ) {
-        self.gen_literal(ctx, ir, dst, Value::new_bigint(bigint));
+        if let Ok(i) = i64::try_from(&bigint) {
+            self.gen_integer(ctx, ir, dst, i);
+        } else {
+            self.gen_literal(ctx, ir, dst, Value::new_bigint(bigint));
+        }
     }
 
     fn gen_nil(&mut self, ir: &mut IrContext, dst: Option<BcLocal>) {
@@ -628,6 +857,15 @@ impl NormalFuncInfo {
         };
     }
 
+    /// Keep the temporary registers balanced where a jump is used as a value,
+    /// e.g. in a branch of `if` whose value is the receiver of a method call.
+    /// The register is never read, since the control never reaches there.
+    fn push_unreachable_value(&mut self, use_value: bool) {
+        if use_value {
+            self.push();
+        }
+    }
+
     fn gen_ret(&mut self, ir: &mut IrContext, local: Option<BcLocal>) {
         let ret = match local {
             Some(local) => local.into(),
@@ -684,6 +922,22 @@ impl NormalFuncInfo {
                 BcOp::StoreConst(reg, id) => {
                     eprintln!("const[{}] = %{}", id_store.get_name(id), reg)
                 }
+                BcOp::LoadIvar(reg, id) => {
+                    eprintln!("%{} = {}", reg, id_store.get_name(id))
+                }
+                BcOp::StoreIvar(reg, id) => {
+                    eprintln!("{} = %{}", id_store.get_name(id), reg)
+                }
+                BcOp::ClassDef(reg, id) => {
+                    eprintln!("%{} = class_def {}", reg, id_store.get_name(id))
+                }
+                BcOp::ClassDefEnd => eprintln!("class_def_end"),
+                BcOp::LoadGvar(reg, id) => {
+                    eprintln!("%{} = {}", reg, id_store.get_name(id))
+                }
+                BcOp::StoreGvar(reg, id) => {
+                    eprintln!("{} = %{}", id_store.get_name(id), reg)
+                }
                 BcOp::Nil(reg) => eprintln!("%{} = nil", reg),
                 BcOp::Neg(dst, src) => eprintln!("%{} = neg %{}", dst, src),
                 BcOp::Add(dst, lhs, rhs) => eprintln!("%{} = %{} + %{}", dst, lhs, rhs),
@@ -696,6 +950,7 @@ impl NormalFuncInfo {
                 }
                 BcOp::Mul(dst, lhs, rhs) => eprintln!("%{} = %{} * %{}", dst, lhs, rhs),
                 BcOp::Div(dst, lhs, rhs) => eprintln!("%{} = %{} / %{}", dst, lhs, rhs),
+                BcOp::Rem(dst, lhs, rhs) => eprintln!("%{} = %{} % %{}", dst, lhs, rhs),
                 BcOp::BitOr(dst, lhs, rhs) => eprintln!("%{} = %{} | %{}", dst, lhs, rhs),
                 BcOp::BitAnd(dst, lhs, rhs) => eprintln!("%{} = %{} & %{}", dst, lhs, rhs),
                 BcOp::BitXor(dst, lhs, rhs) => eprintln!("%{} = %{} ^ %{}", dst, lhs, rhs),
@@ -737,6 +992,37 @@ impl NormalFuncInfo {
                     0 => eprintln!("_ = concat(%{}; {})", args, len),
                     ret => eprintln!("%{:?} = concat(%{}; {})", ret, args, len),
                 },
+                BcOp::Block(ret, func) => eprintln!("%{} = block {:?}", ret, func),
+                BcOp::InitOpt(params_len, rest) => {
+                    eprintln!(
+                        "init_opt({}{})",
+                        params_len,
+                        if rest { ", rest" } else { "" }
+                    )
+                }
+                BcOp::Yield(ret, args, len) => match ret {
+                    0 => eprintln!("_ = yield(%{}; {})", args, len),
+                    ret => eprintln!("%{:?} = yield(%{}; {})", ret, args, len),
+                },
+                BcOp::Array(ret, args, len) => {
+                    eprintln!("%{} = [%{}; {}]", ret, args, len)
+                }
+                BcOp::ExpandArray(src, dst, len) => {
+                    eprintln!("%{}..%{} = *%{}", dst, dst + len, src)
+                }
+                BcOp::Range(ret, start, exclude_end) => {
+                    let dots = if exclude_end { "..." } else { ".." };
+                    eprintln!("%{} = %{}{}%{}", ret, start, dots, start + 1)
+                }
+                BcOp::Hash(ret, args, len) => {
+                    eprintln!("%{} = {{%{}; {}}}", ret, args, len)
+                }
+                BcOp::Index(ret, base, idx) => {
+                    eprintln!("%{} = %{}.[%{}]", ret, base, idx)
+                }
+                BcOp::IndexAssign(src, base, idx) => {
+                    eprintln!("%{}.[%{}] = %{}", base, idx, src)
+                }
             }
         }
         eprintln!("------------------------------------");
@@ -760,6 +1046,10 @@ pub fn is_local(node: &Node) -> Option<&String> {
     }
 }
 
+fn is_splat(node: &Node) -> bool {
+    matches!(node.kind, NodeKind::Splat(_))
+}
+
 impl NormalFuncInfo {
     fn compile_ast(
         &mut self,
@@ -768,11 +1058,43 @@ impl NormalFuncInfo {
     ) -> Result<IrContext> {
         let mut ir = IrContext::new();
         let ast = std::mem::take(&mut self.ast).unwrap();
+        let optional = std::mem::take(&mut self.optional);
+        let needs_init = !optional.is_empty() || self.rest;
+        self.gen_optional_params(ctx, &mut ir, id_store, optional)?;
         self.gen_expr(ctx, &mut ir, id_store, ast, true, true)?;
         assert_eq!(0, self.temp);
+        ir.init_flip_flops();
+        if needs_init {
+            ir.insert_at_entry(vec![BcIr::InitOpt(self.params_len(), self.rest)]);
+        }
         Ok(ir)
     }
 
+    /// Generate bytecode Ir which evaluates the default values of the optional parameters
+    /// not given by the caller.
+    fn gen_optional_params(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        optional: Vec<Node>,
+    ) -> Result<()> {
+        let required = self.params_len() - optional.len();
+        let argc = self.argc_local();
+        for (i, default) in optional.into_iter().enumerate() {
+            let pos = required + i;
+            let loc = default.loc;
+            let given = ir.new_label();
+            let cond = self.push().into();
+            ir.push(BcIr::Cmpri(CmpKind::Gt, cond, argc.into(), pos as i16), loc);
+            ir.gen_condbr(cond, given);
+            self.pop();
+            self.gen_store_expr(ctx, ir, id_store, BcLocal(pos as u16), default, false)?;
+            ir.apply_label(given);
+        }
+        Ok(())
+    }
+
     fn gen_comp_stmts(
         &mut self,
         ctx: &mut FnStore,
@@ -838,12 +1160,38 @@ impl NormalFuncInfo {
             BinOp::BitXor => self.gen_bitxor(ctx, ir, id_store, dst, lhs, rhs, loc)?,
             BinOp::Shr => self.gen_shr(ctx, ir, id_store, dst, lhs, rhs, loc)?,
             BinOp::Shl => self.gen_shl(ctx, ir, id_store, dst, lhs, rhs, loc)?,
+            BinOp::Rem => self.gen_rem(ctx, ir, id_store, dst, lhs, rhs, loc)?,
             BinOp::Eq => self.gen_cmp(ctx, ir, id_store, dst, CmpKind::Eq, lhs, rhs, loc)?,
             BinOp::Ne => self.gen_cmp(ctx, ir, id_store, dst, CmpKind::Ne, lhs, rhs, loc)?,
             BinOp::Ge => self.gen_cmp(ctx, ir, id_store, dst, CmpKind::Ge, lhs, rhs, loc)?,
             BinOp::Gt => self.gen_cmp(ctx, ir, id_store, dst, CmpKind::Gt, lhs, rhs, loc)?,
             BinOp::Le => self.gen_cmp(ctx, ir, id_store, dst, CmpKind::Le, lhs, rhs, loc)?,
             BinOp::Lt => self.gen_cmp(ctx, ir, id_store, dst, CmpKind::Lt, lhs, rhs, loc)?,
+            BinOp::LAnd => self.gen_logical(ctx, ir, id_store, false, dst, lhs, rhs)?,
+            BinOp::LOr => self.gen_logical(ctx, ir, id_store, true, dst, lhs, rhs)?,
+            BinOp::Cmp => {
+                // There is no instruction for `<=>`, so it is always a method call.
+                let ret = match dst {
+                    Some(local) => local.into(),
+                    None => self.push().into(),
+                };
+                let arglist = ArgList {
+                    args: vec![rhs],
+                    ..ArgList::default()
+                };
+                let method = "<=>".to_string();
+                self.gen_method_call(
+                    ctx,
+                    ir,
+                    id_store,
+                    method,
+                    lhs,
+                    arglist,
+                    Some(ret),
+                    false,
+                    loc,
+                )?
+            }
             _ => {
                 return Err(MonorubyErr::unsupported_operator(
                     op,
@@ -879,7 +1227,10 @@ impl NormalFuncInfo {
         match expr.kind {
             NodeKind::Nil => self.gen_nil(ir, None),
             NodeKind::Bool(b) => self.gen_literal(ctx, ir, None, Value::bool(b)),
-            NodeKind::SelfValue => self.gen_temp_mov(ir, BcReg::Self_),
+            NodeKind::SelfValue => {
+                let self_reg = ir.self_reg();
+                self.gen_temp_mov(ir, self_reg)
+            }
             NodeKind::Integer(i) => {
                 self.gen_integer(ctx, ir, None, i);
             }
@@ -891,7 +1242,13 @@ impl NormalFuncInfo {
             NodeKind::Float(f) => self.gen_float(ctx, ir, None, f),
             NodeKind::String(s) => self.gen_string(ctx, ir, None, s.into_bytes()),
             NodeKind::UnOp(op, box rhs) => {
-                assert!(op == UnOp::Neg);
+                if op != UnOp::Neg {
+                    return Err(MonorubyErr::unsupported_unop(
+                        op,
+                        loc,
+                        self.sourceinfo.clone(),
+                    ));
+                }
                 match rhs.kind {
                     //NodeKind::Integer(i) => self.gen_integer(ctx, ir, None, -i),
                     NodeKind::Float(f) => self.gen_float(ctx, ir, None, -f),
@@ -919,12 +1276,45 @@ impl NormalFuncInfo {
                         parent: _,
                         prefix: _,
                     } => {
-                        assert!(!toplevel);
+                        if *toplevel {
+                            return Err(MonorubyErr::unsupported_feature(
+                                "toplevel constant",
+                                lhs.loc,
+                                self.sourceinfo.clone(),
+                            ));
+                        }
                         let name = id_store.get_ident_id(name);
+                        let lhs_loc = lhs.loc;
+                        match op {
+                            BinOp::LOr | BinOp::LAnd => self.gen_const_logical_assign(
+                                ctx,
+                                ir,
+                                id_store,
+                                op == BinOp::LOr,
+                                name,
+                                rhs,
+                                lhs_loc,
+                            )?,
+                            _ => {
+                                let src = self.next_reg();
+                                self.gen_binop(ctx, ir, id_store, op, lhs, rhs, None, loc)?;
+                                self.gen_store_const(ir, src.into(), name, lhs_loc);
+                            }
+                        }
+                    }
+                    NodeKind::InstanceVar(name) => {
+                        let name = id_store.get_ident_id_from_string(name.clone());
+                        let lhs_loc = lhs.loc;
                         let src = self.next_reg();
+                        self.gen_binop(ctx, ir, id_store, op, lhs, rhs, None, loc)?;
+                        ir.push(BcIr::StoreIvar(src.into(), name), lhs_loc);
+                    }
+                    NodeKind::GlobalVar(name) => {
+                        let name = id_store.get_ident_id_from_string(name.clone());
                         let lhs_loc = lhs.loc;
+                        let src = self.next_reg();
                         self.gen_binop(ctx, ir, id_store, op, lhs, rhs, None, loc)?;
-                        self.gen_store_const(ir, src.into(), name, lhs_loc);
+                        ir.push(BcIr::StoreGvar(src.into(), name), lhs_loc);
                     }
                     _ => return Err(MonorubyErr::unsupported_lhs(lhs, self.sourceinfo.clone())),
                 };
@@ -933,7 +1323,7 @@ impl NormalFuncInfo {
                 self.gen_binop(ctx, ir, id_store, op, lhs, rhs, None, loc)?
             }
             NodeKind::MulAssign(mut mlhs, mut mrhs) => {
-                if mlhs.len() == 1 && mrhs.len() == 1 {
+                if mlhs.len() == 1 && mrhs.len() == 1 && !is_splat(&mlhs[0]) {
                     let (lhs, rhs) = (mlhs.remove(0), mrhs.remove(0));
                     match lhs.kind {
                         NodeKind::LocalVar(lhs) | NodeKind::Ident(lhs) => {
@@ -952,12 +1342,48 @@ impl NormalFuncInfo {
                             parent: _,
                             prefix: _,
                         } => {
-                            assert!(!toplevel);
+                            if toplevel {
+                                return Err(MonorubyErr::unsupported_feature(
+                                    "toplevel constant",
+                                    lhs.loc,
+                                    self.sourceinfo.clone(),
+                                ));
+                            }
                             let name = id_store.get_ident_id_from_string(name);
                             let src = self.next_reg();
                             self.gen_expr(ctx, ir, id_store, rhs, true, false)?;
                             self.gen_store_const(ir, src.into(), name, loc);
                         }
+                        NodeKind::InstanceVar(name) => {
+                            let name = id_store.get_ident_id_from_string(name);
+                            let src = self.next_reg();
+                            self.gen_expr(ctx, ir, id_store, rhs, true, false)?;
+                            ir.push(BcIr::StoreIvar(src.into(), name), loc);
+                        }
+                        NodeKind::GlobalVar(name) => {
+                            let name = id_store.get_ident_id_from_string(name);
+                            let src = self.next_reg();
+                            self.gen_expr(ctx, ir, id_store, rhs, true, false)?;
+                            ir.push(BcIr::StoreGvar(src.into(), name), loc);
+                        }
+                        NodeKind::Index {
+                            box base,
+                            mut index,
+                        } if index.len() == 1 => {
+                            // The receiver and the index are evaluated before the right-hand side.
+                            let base_reg = self.next_reg();
+                            let index_reg = BcTemp(base_reg.0 + 1);
+                            let src = BcTemp(base_reg.0 + 2);
+                            self.gen_expr(ctx, ir, id_store, base, true, false)?;
+                            self.gen_expr(ctx, ir, id_store, index.remove(0), true, false)?;
+                            self.gen_expr(ctx, ir, id_store, rhs, true, false)?;
+                            ir.push(
+                                BcIr::IndexAssign(src.into(), base_reg.into(), index_reg.into()),
+                                loc,
+                            );
+                            self.popn(3);
+                            self.gen_temp_mov(ir, src.into());
+                        }
                         _ => {
                             return Err(MonorubyErr::unsupported_lhs(lhs, self.sourceinfo.clone()))
                         }
@@ -978,12 +1404,12 @@ impl NormalFuncInfo {
             NodeKind::Const {
                 toplevel,
                 name,
-                parent: _,
-                prefix: _,
+                parent,
+                prefix,
             } => {
-                assert!(!toplevel);
+                let prefix = self.gen_const_prefix(id_store, toplevel, parent, prefix, loc)?;
                 let name = id_store.get_ident_id_from_string(name);
-                self.gen_load_const(ir, None, name, loc);
+                self.gen_load_const(ir, None, prefix, name, loc);
             }
             NodeKind::MethodCall {
                 box receiver,
@@ -1021,6 +1447,14 @@ impl NormalFuncInfo {
                 };
                 return self.gen_func_call(ctx, ir, id_store, method, arglist, ret, is_ret, loc);
             }
+            NodeKind::Yield(arglist) => {
+                let ret = if use_value {
+                    Some(self.push().into())
+                } else {
+                    None
+                };
+                return self.gen_yield(ctx, ir, id_store, arglist, ret, is_ret, loc);
+            }
             NodeKind::If {
                 box cond,
                 box then_,
@@ -1028,7 +1462,7 @@ impl NormalFuncInfo {
             } => {
                 let then_pos = ir.new_label();
                 let succ_pos = ir.new_label();
-                let cond = self.gen_temp_expr(ctx, ir, id_store, cond)?.into();
+                let cond = self.gen_cond(ctx, ir, id_store, cond)?.into();
                 ir.gen_condbr(cond, then_pos);
                 self.gen_expr(ctx, ir, id_store, else_, use_value, is_ret)?;
                 if !is_ret {
@@ -1042,13 +1476,21 @@ impl NormalFuncInfo {
                 ir.apply_label(succ_pos);
                 return Ok(());
             }
+            NodeKind::Case {
+                cond,
+                when_,
+                box else_,
+            } => {
+                let cond = cond.map(|box cond| cond);
+                self.gen_case(ctx, ir, id_store, cond, when_, else_, use_value, is_ret)?;
+                return Ok(());
+            }
             NodeKind::While {
                 box cond,
                 box body,
                 cond_op,
             } => {
-                assert!(cond_op);
-                self.gen_while(ctx, ir, id_store, cond, body, use_value)?;
+                self.gen_while(ctx, ir, id_store, cond_op, cond, body, use_value)?;
                 if is_ret {
                     self.gen_ret(ir, None);
                 }
@@ -1066,10 +1508,16 @@ impl NormalFuncInfo {
                 return Ok(());
             }
             NodeKind::Break(box val) => {
-                let (_kind, break_pos, ret_reg) = match ir.loops.last() {
+                let LoopContext {
+                    break_pos, ret_reg, ..
+                } = match ir.loops.last() {
                     Some(data) => data.clone(),
                     None => {
-                        return Err(MonorubyErr::escape_from_eval(loc, self.sourceinfo.clone()))
+                        return Err(MonorubyErr::invalid_jump(
+                            "break",
+                            loc,
+                            self.sourceinfo.clone(),
+                        ))
                     }
                 };
                 match ret_reg {
@@ -1079,19 +1527,45 @@ impl NormalFuncInfo {
                     }
                     None => {}
                 }
+                self.gen_escaped_ensures(ctx, ir, id_store, ir.loops.len())?;
                 ir.push(BcIr::Br(break_pos), loc);
+                self.push_unreachable_value(use_value);
+                return Ok(());
+            }
+            NodeKind::Next(box val) => {
+                let next_pos = match ir.loops.last() {
+                    Some(data) => data.next_pos,
+                    None => {
+                        return Err(MonorubyErr::invalid_jump(
+                            "next",
+                            loc,
+                            self.sourceinfo.clone(),
+                        ))
+                    }
+                };
+                // the value of `next` is discarded in loops.
+                self.gen_expr(ctx, ir, id_store, val, false, false)?;
+                self.gen_escaped_ensures(ctx, ir, id_store, ir.loops.len())?;
+                ir.push(BcIr::Br(next_pos), loc);
+                self.push_unreachable_value(use_value);
                 return Ok(());
             }
             NodeKind::Return(box expr) => {
-                if let Some(local) = is_local(&expr) {
+                // `return a, b` is parsed as `return [a, b]`, so the values are gathered
+                // into an Array by BcIr::Array.
+                if !ir.ensures.is_empty() {
+                    // the `ensure` clauses are run after the value is evaluated.
+                    self.gen_expr(ctx, ir, id_store, expr, true, false)?;
+                    self.gen_escaped_ensures(ctx, ir, id_store, 0)?;
+                    self.gen_ret(ir, None);
+                } else if let Some(local) = is_local(&expr) {
                     let local = self.load_local(local, expr.loc)?;
                     self.gen_ret(ir, Some(local));
                 } else {
                     self.gen_expr(ctx, ir, id_store, expr, true, true)?;
                 }
-                if use_value && !is_ret {
-                    unreachable!();
-                }
+                // e.g. `return` at the end of `begin` whose value is used.
+                self.push_unreachable_value(use_value && !is_ret);
                 return Ok(());
             }
             NodeKind::CompStmt(nodes) => {
@@ -1100,11 +1574,21 @@ impl NormalFuncInfo {
             NodeKind::Begin {
                 box body,
                 rescue,
-                else_: None,
-                ensure: None,
+                else_,
+                ensure,
             } => {
-                assert!(rescue.len() == 0);
-                self.gen_expr(ctx, ir, id_store, body, use_value, is_ret)?;
+                let else_ = else_.map(|box else_| else_);
+                match ensure {
+                    Some(box ensure) => self.gen_ensure(
+                        ctx, ir, id_store, body, rescue, else_, ensure, use_value, loc,
+                    )?,
+                    None => {
+                        self.gen_begin(ctx, ir, id_store, body, rescue, else_, use_value, loc)?
+                    }
+                }
+                if is_ret {
+                    self.gen_ret(ir, None);
+                }
                 return Ok(());
             }
             NodeKind::MethodDef(name, params, box node, _lv) => {
@@ -1117,6 +1601,26 @@ impl NormalFuncInfo {
                 }
                 return Ok(());
             }
+            NodeKind::ClassDef {
+                base,
+                name,
+                superclass,
+                info,
+                is_module,
+            } => {
+                if base.is_some() || superclass.is_some() || is_module {
+                    return Err(MonorubyErr::unsupported_feature(
+                        "inheritance, modules and nested class paths",
+                        loc,
+                        self.sourceinfo.clone(),
+                    ));
+                }
+                self.gen_class_def(ctx, ir, id_store, name, *info.body, use_value, loc)?;
+                if is_ret {
+                    self.gen_ret(ir, None);
+                }
+                return Ok(());
+            }
             NodeKind::InterporatedString(nodes) => {
                 let len = nodes.len();
                 let arg = self.next_reg();
@@ -1134,6 +1638,62 @@ impl NormalFuncInfo {
                 }
                 return Ok(());
             }
+            NodeKind::InstanceVar(name) => {
+                let name = id_store.get_ident_id_from_string(name);
+                let ret = self.push().into();
+                ir.push(BcIr::LoadIvar(ret, name), loc);
+            }
+            NodeKind::GlobalVar(name) => {
+                let name = id_store.get_ident_id_from_string(name);
+                let ret = self.push().into();
+                ir.push(BcIr::LoadGvar(ret, name), loc);
+            }
+            NodeKind::Index {
+                box base,
+                mut index,
+            } if index.len() == 1 => {
+                let base_reg = self.next_reg();
+                self.gen_expr(ctx, ir, id_store, base, true, false)?;
+                self.gen_expr(ctx, ir, id_store, index.remove(0), true, false)?;
+                self.popn(2);
+                let ret = self.push().into();
+                let index_reg = BcTemp(base_reg.0 + 1);
+                ir.push(BcIr::Index(ret, base_reg.into(), index_reg.into()), loc);
+            }
+            NodeKind::Hash(pairs, _) => {
+                let len = pairs.len();
+                let arg = self.next_reg();
+                for (k, v) in pairs {
+                    self.gen_expr(ctx, ir, id_store, k, true, false)?;
+                    self.gen_expr(ctx, ir, id_store, v, true, false)?;
+                }
+                self.temp -= len as u16 * 2;
+                let ret = self.push().into();
+                ir.push(BcIr::Hash(ret, arg, len), Loc::default());
+            }
+            NodeKind::Array(nodes, _) => {
+                let len = nodes.len();
+                let arg = self.next_reg();
+                for expr in nodes {
+                    self.gen_expr(ctx, ir, id_store, expr, true, false)?;
+                }
+                self.temp -= len as u16;
+                let ret = self.push().into();
+                ir.push(BcIr::Array(ret, arg, len), Loc::default());
+            }
+            NodeKind::Range {
+                box start,
+                box end,
+                exclude_end,
+                ..
+            } => {
+                let arg = self.next_reg();
+                self.gen_expr(ctx, ir, id_store, start, true, false)?;
+                self.gen_expr(ctx, ir, id_store, end, true, false)?;
+                self.temp -= 2;
+                let ret = self.push().into();
+                ir.push(BcIr::Range(ret, arg, exclude_end), loc);
+            }
             _ => return Err(MonorubyErr::unsupported_node(expr, self.sourceinfo.clone())),
         }
         if is_ret {
@@ -1157,7 +1717,10 @@ impl NormalFuncInfo {
         match rhs.kind {
             NodeKind::Nil => self.gen_nil(ir, Some(local)),
             NodeKind::Bool(b) => self.gen_literal(ctx, ir, Some(local), Value::bool(b)),
-            NodeKind::SelfValue => self.gen_mov(ir, local.into(), BcReg::Self_),
+            NodeKind::SelfValue => {
+                let self_reg = ir.self_reg();
+                self.gen_mov(ir, local.into(), self_reg)
+            }
             NodeKind::Integer(i) => self.gen_integer(ctx, ir, Some(local), i),
             NodeKind::Symbol(sym) => {
                 let sym = id_store.get_ident_id_from_string(sym);
@@ -1167,7 +1730,13 @@ impl NormalFuncInfo {
             NodeKind::Float(f) => self.gen_float(ctx, ir, Some(local), f),
             NodeKind::String(s) => self.gen_string(ctx, ir, Some(local), s.into_bytes()),
             NodeKind::UnOp(op, box rhs) => {
-                assert!(op == UnOp::Neg);
+                if op != UnOp::Neg {
+                    return Err(MonorubyErr::unsupported_unop(
+                        op,
+                        loc,
+                        self.sourceinfo.clone(),
+                    ));
+                }
                 match rhs.kind {
                     NodeKind::Integer(i) => self.gen_integer(ctx, ir, Some(local), -i),
                     NodeKind::Float(f) => self.gen_float(ctx, ir, Some(local), -f),
@@ -1181,7 +1750,7 @@ impl NormalFuncInfo {
                 self.gen_binop(ctx, ir, id_store, op, lhs, rhs, Some(local), loc)?
             }
             NodeKind::MulAssign(mut mlhs, mut mrhs) => {
-                if mlhs.len() == 1 && mrhs.len() == 1 {
+                if mlhs.len() == 1 && mrhs.len() == 1 && !is_splat(&mlhs[0]) {
                     let (lhs, rhs) = (mlhs.remove(0), mrhs.remove(0));
                     match lhs.kind {
                         NodeKind::LocalVar(lhs) | NodeKind::Ident(lhs) => {
@@ -1206,12 +1775,12 @@ impl NormalFuncInfo {
             NodeKind::Const {
                 toplevel,
                 name,
-                parent: _,
-                prefix: _,
+                parent,
+                prefix,
             } => {
-                assert!(!toplevel);
+                let prefix = self.gen_const_prefix(id_store, toplevel, parent, prefix, loc)?;
                 let name = id_store.get_ident_id_from_string(name);
-                self.gen_load_const(ir, local.into(), name, loc);
+                self.gen_load_const(ir, local.into(), prefix, name, loc);
                 return Ok(());
             }
             NodeKind::MethodCall {
@@ -1237,6 +1806,23 @@ impl NormalFuncInfo {
             NodeKind::CompStmt(nodes) => {
                 return self.gen_comp_stmts(ctx, ir, id_store, nodes, Some(local), use_value, false)
             }
+            // `cond ? then_ : else_` is also parsed as If.
+            // Both branches are stored directly into *local*.
+            NodeKind::If {
+                box cond,
+                box then_,
+                box else_,
+            } => {
+                let then_pos = ir.new_label();
+                let succ_pos = ir.new_label();
+                let cond = self.gen_cond(ctx, ir, id_store, cond)?.into();
+                ir.gen_condbr(cond, then_pos);
+                self.gen_store_expr(ctx, ir, id_store, local, else_, false)?;
+                ir.gen_br(succ_pos);
+                ir.apply_label(then_pos);
+                self.gen_store_expr(ctx, ir, id_store, local, then_, false)?;
+                ir.apply_label(succ_pos);
+            }
             _ => {
                 let ret = self.next_reg();
                 self.gen_expr(ctx, ir, id_store, rhs, true, false)?;
@@ -1262,24 +1848,128 @@ impl NormalFuncInfo {
         params: Vec<FormalParam>,
         node: Node,
     ) -> Result<()> {
+        let (args, optional, rest) = self.gen_params(params, true)?;
+        let func_id = ctx.functions.add_normal_func(
+            Some(name.clone()),
+            args,
+            optional,
+            rest,
+            node,
+            self.sourceinfo.clone(),
+        );
+        let name = id_store.get_ident_id_from_string(name);
+        ir.push(BcIr::MethodDef(name, func_id), Loc::default());
+        Ok(())
+    }
+
+    /// Get the names of *params*, the default values of the optional parameters and
+    /// whether the last one is a rest parameter.
+    ///
+    /// Optional and rest parameters are allowed only if *allow_optional* is true. Optional ones
+    /// must follow all of the required ones, and nothing can follow the rest parameter.
+    fn gen_params(
+        &self,
+        params: Vec<FormalParam>,
+        allow_optional: bool,
+    ) -> Result<(Vec<String>, Vec<Node>, bool)> {
         let mut args = vec![];
+        let mut optional = vec![];
+        let mut rest = false;
         for param in params {
             match param.kind {
-                ParamKind::Param(name) => args.push(name),
-                _ => {
+                ParamKind::Param(name) if optional.is_empty() && !rest => args.push(name),
+                ParamKind::Optional(name, box default) if allow_optional && !rest => {
+                    args.push(name);
+                    optional.push(default);
+                }
+                ParamKind::Rest(name) if allow_optional && !rest => {
+                    // `*` without a name still takes the extra arguments.
+                    args.push(name.unwrap_or_else(|| "/rest".to_string()));
+                    rest = true;
+                }
+                kind => {
                     return Err(MonorubyErr::unsupported_parameter_kind(
-                        param.kind,
+                        kind,
                         param.loc,
                         self.sourceinfo.clone(),
                     ))
                 }
             }
         }
-        let func_id =
-            ctx.functions
-                .add_normal_func(Some(name.clone()), args, node, self.sourceinfo.clone());
+        Ok((args, optional, rest))
+    }
+
+    /// Generate bytecode Ir for a block given to a method call.
+    ///
+    /// The block body is compiled as a separate function, and can not refer to
+    /// local variables of the outer scope. A block argument with `&` (e.g. `&:sym`)
+    /// is converted to a Proc by `to_proc`.
+    fn gen_block(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        block: Node,
+        ret: BcReg,
+    ) -> Result<()> {
+        let loc = block.loc;
+        match block.kind {
+            NodeKind::Lambda(BlockInfo { params, body, .. }) => {
+                let (args, _, _) = self.gen_params(params, false)?;
+                let func_id = ctx.functions.add_normal_func(
+                    None,
+                    args,
+                    vec![],
+                    false,
+                    *body,
+                    self.sourceinfo.clone(),
+                );
+                self.gen_mov(ir, ret, self.block_local().into());
+                ir.push(BcIr::Block(ret, func_id), loc);
+                Ok(())
+            }
+            _ => {
+                self.gen_expr(ctx, ir, id_store, block, true, false)?;
+                // no arguments and no block for `to_proc`.
+                let arg = self.push();
+                ir.push(BcIr::Nil(arg.into()), loc);
+                self.pop();
+                let recv = self.pop().into();
+                let to_proc = id_store.get_ident_id_from_string("to_proc".to_string());
+                ir.push(BcIr::MethodCall(recv, to_proc, Some(ret), arg, 0), loc);
+                Ok(())
+            }
+        }
+    }
+
+    /// Generate bytecode Ir for a class definition.
+    ///
+    /// The class body is evaluated in the current function, with the class object
+    /// held in a temporary register as `self`.
+    fn gen_class_def(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        name: String,
+        body: Node,
+        use_value: bool,
+        loc: Loc,
+    ) -> Result<()> {
         let name = id_store.get_ident_id_from_string(name);
-        ir.push(BcIr::MethodDef(name, func_id), Loc::default());
+        let class_reg = self.push().into();
+        ir.push(BcIr::ClassDef(class_reg, name), loc);
+        ir.class_regs.push(class_reg);
+        self.gen_expr(ctx, ir, id_store, body, use_value, false)?;
+        ir.class_regs.pop();
+        ir.push(BcIr::ClassDefEnd, loc);
+        if use_value {
+            let res = self.pop().into();
+            self.pop();
+            self.gen_temp_mov(ir, res);
+        } else {
+            self.pop();
+        }
         Ok(())
     }
 
@@ -1303,25 +1993,72 @@ impl NormalFuncInfo {
         ir: &mut IrContext,
         id_store: &mut IdentifierTable,
         arglist: ArgList,
+        loc: Loc,
     ) -> Result<(BcTemp, usize)> {
-        assert!(arglist.kw_args.len() == 0);
-        assert!(arglist.hash_splat.len() == 0);
-        assert!(arglist.block.is_none());
-        assert!(!arglist.delegate);
-        self.check_fast_call_inner(ctx, ir, id_store, arglist.args)
+        self.check_arglist(&arglist, loc)?;
+        let len = arglist.args.len();
+        let arg = self.gen_args(ctx, ir, id_store, arglist.args)?;
+        // the block (or nil) is passed in the register next to the arguments.
+        let block = self.push().into();
+        match arglist.block {
+            Some(box node) => self.gen_block(ctx, ir, id_store, node, block)?,
+            None => ir.push(BcIr::Nil(block), Loc::default()),
+        }
+        self.temp -= len as u16 + 1;
+        Ok((arg, len))
     }
 
-    fn check_fast_call_inner(
+    fn check_arglist(&self, arglist: &ArgList, loc: Loc) -> Result<()> {
+        let unsupported = if arglist.kw_args.len() != 0 {
+            Some("keyword arguments")
+        } else if arglist.hash_splat.len() != 0 {
+            Some("hash splat arguments")
+        } else if arglist.delegate {
+            Some("argument delegation")
+        } else {
+            None
+        };
+        match unsupported {
+            Some(feature) => Err(MonorubyErr::unsupported_feature(
+                feature,
+                loc,
+                self.sourceinfo.clone(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Generate bytecode Ir for `yield`.
+    ///
+    /// The block of the current function is passed in the register next to the arguments.
+    fn gen_yield(
         &mut self,
         ctx: &mut FnStore,
         ir: &mut IrContext,
         id_store: &mut IdentifierTable,
-        args: Vec<Node>,
-    ) -> Result<(BcTemp, usize)> {
-        let len = args.len();
-        let arg = self.gen_args(ctx, ir, id_store, args)?;
-        self.temp -= len as u16;
-        Ok((arg, len))
+        arglist: ArgList,
+        ret: Option<BcReg>,
+        is_ret: bool,
+        loc: Loc,
+    ) -> Result<()> {
+        self.check_arglist(&arglist, loc)?;
+        if arglist.block.is_some() {
+            return Err(MonorubyErr::unsupported_feature(
+                "block arguments for yield",
+                loc,
+                self.sourceinfo.clone(),
+            ));
+        }
+        let len = arglist.args.len();
+        let arg = self.gen_args(ctx, ir, id_store, arglist.args)?;
+        let block = self.push().into();
+        self.gen_mov(ir, block, self.block_local().into());
+        self.temp -= len as u16 + 1;
+        ir.push(BcIr::Yield(ret, arg, len), loc);
+        if is_ret {
+            self.gen_ret(ir, None);
+        }
+        Ok(())
     }
 
     fn gen_method_call(
@@ -1338,11 +2075,12 @@ impl NormalFuncInfo {
     ) -> Result<()> {
         let method = id_store.get_ident_id_from_string(method);
         if receiver.kind == NodeKind::SelfValue {
-            let (arg, len) = self.check_fast_call(ctx, ir, id_store, arglist)?;
-            ir.push(BcIr::MethodCall(BcReg::Self_, method, ret, arg, len), loc);
+            let (arg, len) = self.check_fast_call(ctx, ir, id_store, arglist, loc)?;
+            let recv = ir.self_reg();
+            ir.push(BcIr::MethodCall(recv, method, ret, arg, len), loc);
         } else {
             self.gen_expr(ctx, ir, id_store, receiver, true, false)?;
-            let (arg, len) = self.check_fast_call(ctx, ir, id_store, arglist)?;
+            let (arg, len) = self.check_fast_call(ctx, ir, id_store, arglist, loc)?;
             let recv = self.pop().into();
             ir.push(BcIr::MethodCall(recv, method, ret, arg, len), loc);
         }
@@ -1363,15 +2101,76 @@ impl NormalFuncInfo {
         is_ret: bool,
         loc: Loc,
     ) -> Result<()> {
-        let (arg, len) = self.check_fast_call(ctx, ir, id_store, arglist)?;
+        let (arg, len) = self.check_fast_call(ctx, ir, id_store, arglist, loc)?;
         let method = id_store.get_ident_id_from_string(method);
-        ir.push(BcIr::MethodCall(BcReg::Self_, method, ret, arg, len), loc);
+        let recv = ir.self_reg();
+        ir.push(BcIr::MethodCall(recv, method, ret, arg, len), loc);
         if is_ret {
             self.gen_ret(ir, None);
         }
         return Ok(());
     }
 
+    /// Generate bytecode Ir for `lhs || rhs` (*is_or* = true) and `lhs && rhs`.
+    ///
+    /// *rhs* is evaluated only when *lhs* does not determine the result,
+    /// and the value of the last evaluated operand is returned.
+    fn gen_logical(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        is_or: bool,
+        dst: Option<BcLocal>,
+        lhs: Node,
+        rhs: Node,
+    ) -> Result<()> {
+        let exit_pos = ir.new_label();
+        self.gen_expr(ctx, ir, id_store, lhs, true, false)?;
+        let ret = self.pop().into();
+        if is_or {
+            ir.gen_condbr(ret, exit_pos);
+        } else {
+            ir.gen_condnotbr(ret, exit_pos);
+        }
+        self.gen_expr(ctx, ir, id_store, rhs, true, false)?;
+        ir.apply_label(exit_pos);
+        if let Some(local) = dst {
+            self.pop();
+            self.gen_mov(ir, local.into(), ret);
+        }
+        Ok(())
+    }
+
+    /// Generate bytecode Ir for `Const ||= rhs` (*is_or* = true) and `Const &&= rhs`.
+    ///
+    /// `Const ||= rhs` does not raise an error even if `Const` is not defined.
+    fn gen_const_logical_assign(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        is_or: bool,
+        name: IdentId,
+        rhs: Node,
+        loc: Loc,
+    ) -> Result<()> {
+        let exit_pos = ir.new_label();
+        let ret = self.push().into();
+        if is_or {
+            ir.push(BcIr::LoadConstOrNil(ret, name), loc);
+            ir.gen_condbr(ret, exit_pos);
+        } else {
+            ir.push(BcIr::LoadConst(ret, vec![], name), loc);
+            ir.gen_condnotbr(ret, exit_pos);
+        }
+        self.pop();
+        self.gen_expr(ctx, ir, id_store, rhs, true, false)?;
+        self.gen_store_const(ir, ret, name, loc);
+        ir.apply_label(exit_pos);
+        Ok(())
+    }
+
     fn gen_binary(
         &mut self,
         ctx: &mut FnStore,
@@ -1492,6 +2291,7 @@ impl NormalFuncInfo {
     gen_ops!(
         (mul, Mul),
         (div, Div),
+        (rem, Rem),
         (bitor, BitOr),
         (bitand, BitAnd),
         (bitxor, BitXor),
@@ -1522,50 +2322,145 @@ impl NormalFuncInfo {
         Ok(())
     }
 
+    /// Generate bytecode Ir for multiple assignment.
+    ///
+    /// A single right-hand side value is expanded if there are several targets, e.g.
+    /// `a, b = [1, 2]`, and one splat target `*b` takes the rest of the values as an Array.
+    /// Missing values are nil, and the value of the assignment is an Array of the right-hand side.
     fn gen_mul_assign(
         &mut self,
         ctx: &mut FnStore,
         ir: &mut IrContext,
         id_store: &mut IdentifierTable,
         mlhs: Vec<Node>,
-        mrhs: Vec<Node>,
+        mut mrhs: Vec<Node>,
         use_value: bool,
         is_ret: bool,
     ) -> Result<()> {
         let mlhs_len = mlhs.len();
-        assert!(mlhs_len == mrhs.len());
-        let mut temp_reg = self.next_reg();
-        // At first we evaluate right-hand side values and save them in temporory registers.
-        for rhs in mrhs {
+        let splat = mlhs.iter().position(is_splat);
+        let rhs_reg = self.next_reg();
+        if mrhs.len() == 1 && (mlhs_len > 1 || splat.is_some()) {
+            let rhs = mrhs.remove(0);
+            let loc = rhs.loc;
+            if let Some(pos) = splat {
+                return Err(MonorubyErr::unsupported_lhs(
+                    mlhs.into_iter().nth(pos).unwrap(),
+                    self.sourceinfo.clone(),
+                ));
+            }
             self.gen_expr(ctx, ir, id_store, rhs, true, false)?;
-        }
-        // Assign values to left-hand side expressions.
-        for lhs in mlhs {
-            match lhs.kind {
-                NodeKind::LocalVar(lhs) | NodeKind::Ident(lhs) => {
-                    let local = self.find_local(&lhs);
-                    self.gen_mov(ir, local.into(), temp_reg.into());
-                }
-                NodeKind::Const {
-                    toplevel,
-                    parent,
-                    prefix,
-                    name,
-                } if !toplevel && parent.is_none() && prefix.len() == 0 => {
-                    let name = id_store.get_ident_id_from_string(name);
-                    self.gen_store_const(ir, temp_reg.into(), name, lhs.loc);
+            let dst = self.next_reg();
+            for _ in 0..mlhs_len {
+                self.push();
+            }
+            ir.push(BcIr::ExpandArray(rhs_reg.into(), dst, mlhs_len), loc);
+            for (i, lhs) in mlhs.into_iter().enumerate() {
+                self.gen_assign_lhs(ctx, ir, id_store, lhs, BcTemp(dst.0 + i as u16).into())?;
+            }
+            self.popn(mlhs_len);
+            // the value of the assignment is the right-hand side itself.
+            if !use_value && !is_ret {
+                self.pop();
+            }
+        } else {
+            let mrhs_len = mrhs.len();
+            // At first we evaluate right-hand side values and save them in temporory registers.
+            for rhs in mrhs {
+                self.gen_expr(ctx, ir, id_store, rhs, true, false)?;
+            }
+            // `a = 1, 2` is the same as `*a = 1, 2`.
+            let splat = if mlhs_len == 1 && mrhs_len > 1 {
+                Some(0)
+            } else {
+                splat
+            };
+            let pre = splat.unwrap_or(mlhs_len);
+            let post = splat.map_or(0, |splat| mlhs_len - splat - 1);
+            // the position of the value for the first target after the splat one.
+            let post_start = mrhs_len.saturating_sub(post).max(pre);
+            for (i, lhs) in mlhs.into_iter().enumerate() {
+                let pos = match splat {
+                    Some(splat) if i == splat => {
+                        let src = self.push();
+                        let arg = BcTemp(rhs_reg.0 + pre as u16);
+                        ir.push(BcIr::Array(src.into(), arg, post_start - pre), lhs.loc);
+                        self.gen_assign_lhs(ctx, ir, id_store, lhs, src.into())?;
+                        self.pop();
+                        continue;
+                    }
+                    Some(splat) if i > splat => post_start + i - splat - 1,
+                    _ => i,
+                };
+                if pos < mrhs_len {
+                    let src = BcTemp(rhs_reg.0 + pos as u16).into();
+                    self.gen_assign_lhs(ctx, ir, id_store, lhs, src)?;
+                } else {
+                    let src = self.next_reg().into();
+                    self.gen_nil(ir, None);
+                    self.gen_assign_lhs(ctx, ir, id_store, lhs, src)?;
+                    self.pop();
                 }
-                _ => return Err(MonorubyErr::unsupported_lhs(lhs, self.sourceinfo.clone())),
             }
-            temp_reg += 1;
+            self.popn(mrhs_len);
+            if use_value || is_ret {
+                let ret = self.push().into();
+                ir.push(BcIr::Array(ret, rhs_reg, mrhs_len), Loc::default());
+            }
         }
-        self.popn(mlhs_len);
-        // TODO: This is not correct. We must make an Array.
         if is_ret {
-            self.gen_nil(ir, None);
             self.gen_ret(ir, None);
-        } else if use_value {
-            self.gen_nil(ir, None);
+        }
+        Ok(())
+    }
+
+    /// Generate bytecode Ir which assigns *src* to *lhs*, one of the targets of multiple assignment.
+    fn gen_assign_lhs(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        lhs: Node,
+        src: BcReg,
+    ) -> Result<()> {
+        match lhs.kind {
+            NodeKind::Splat(box lhs) => self.gen_assign_lhs(ctx, ir, id_store, lhs, src)?,
+            NodeKind::LocalVar(lhs) | NodeKind::Ident(lhs) => {
+                let local = self.find_local(&lhs);
+                self.gen_mov(ir, local.into(), src);
+            }
+            NodeKind::Const {
+                toplevel,
+                parent,
+                prefix,
+                name,
+            } if !toplevel && parent.is_none() && prefix.len() == 0 => {
+                let name = id_store.get_ident_id_from_string(name);
+                self.gen_store_const(ir, src, name, lhs.loc);
+            }
+            NodeKind::InstanceVar(name) => {
+                let name = id_store.get_ident_id_from_string(name);
+                ir.push(BcIr::StoreIvar(src, name), lhs.loc);
+            }
+            NodeKind::GlobalVar(name) => {
+                let name = id_store.get_ident_id_from_string(name);
+                ir.push(BcIr::StoreGvar(src, name), lhs.loc);
+            }
+            NodeKind::Index {
+                box base,
+                mut index,
+            } if index.len() == 1 => {
+                let base_reg = self.next_reg();
+                let index_reg = BcTemp(base_reg.0 + 1);
+                self.gen_expr(ctx, ir, id_store, base, true, false)?;
+                self.gen_expr(ctx, ir, id_store, index.remove(0), true, false)?;
+                self.popn(2);
+                ir.push(
+                    BcIr::IndexAssign(src, base_reg.into(), index_reg.into()),
+                    lhs.loc,
+                );
+            }
+            _ => return Err(MonorubyErr::unsupported_lhs(lhs, self.sourceinfo.clone())),
         }
         Ok(())
     }
@@ -1583,14 +2478,15 @@ impl NormalFuncInfo {
         assert_eq!(1, param.len());
         let counter = self.find_local(&param[0]);
         let break_pos = ir.new_label();
-        ir.loops.push((
-            LoopKind::For,
+        let next_pos = ir.new_label();
+        ir.loops.push(LoopContext {
             break_pos,
-            match use_value {
+            next_pos,
+            ret_reg: match use_value {
                 true => Some(self.next_reg().into()),
                 false => None,
             },
-        ));
+        });
         let loc = iter.loc;
         if let NodeKind::Range {
             box start,
@@ -1613,13 +2509,18 @@ impl NormalFuncInfo {
 
             self.gen_expr(ctx, ir, id_store, *body.body, false, false)?;
 
+            ir.apply_label(next_pos);
             ir.push(BcIr::Addri(counter.into(), counter.into(), 1), loc);
             ir.gen_br(loop_entry);
 
             ir.apply_label(loop_exit);
             self.pop();
         } else {
-            unimplemented!()
+            return Err(MonorubyErr::unsupported_feature(
+                "for-loop over non-range object",
+                loc,
+                self.sourceinfo.clone(),
+            ));
         }
         if use_value {
             // TODO: we must return iter object.
@@ -1630,11 +2531,298 @@ impl NormalFuncInfo {
         Ok(())
     }
 
+    /// Generate bytecode Ir for a condition of `if` or `while`.
+    fn gen_cond(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        cond: Node,
+    ) -> Result<BcTemp> {
+        match cond.kind {
+            NodeKind::Range {
+                box start,
+                box end,
+                exclude_end,
+                ..
+            } => self.gen_flip_flop(ctx, ir, id_store, start, end, exclude_end),
+            _ => self.gen_temp_expr(ctx, ir, id_store, cond),
+        }
+    }
+
+    /// Generate bytecode Ir for a flip-flop (e.g. `if (i == 2)..(i == 4)`).
+    ///
+    /// The state of the flip-flop is held in a hidden local variable.
+    fn gen_flip_flop(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        start: Node,
+        end: Node,
+        exclude_end: bool,
+    ) -> Result<BcTemp> {
+        let state = self.add_local(format!("/flipflop{}", ir.flip_flops.len()));
+        ir.flip_flops.push(state);
+        let on_pos = ir.new_label();
+        let true_pos = ir.new_label();
+        let false_pos = ir.new_label();
+        let exit_pos = ir.new_label();
+        ir.gen_condbr(state.into(), on_pos);
+        // the flip-flop is off: check the start condition.
+        let start = self.gen_temp_expr(ctx, ir, id_store, start)?.into();
+        ir.gen_condnotbr(start, false_pos);
+        self.gen_literal(ctx, ir, Some(state), Value::bool(true));
+        if exclude_end {
+            ir.gen_br(true_pos);
+        }
+        // the flip-flop is on: check the end condition.
+        ir.apply_label(on_pos);
+        let end = self.gen_temp_expr(ctx, ir, id_store, end)?.into();
+        ir.gen_condnotbr(end, true_pos);
+        self.gen_nil(ir, Some(state));
+        ir.apply_label(true_pos);
+        self.gen_literal(ctx, ir, None, Value::bool(true));
+        ir.gen_br(exit_pos);
+        self.pop();
+        ir.apply_label(false_pos);
+        self.gen_literal(ctx, ir, None, Value::bool(false));
+        ir.apply_label(exit_pos);
+        Ok(self.pop())
+    }
+
+    /// Generate bytecode Ir for `case` expressions.
+    ///
+    /// Each `when` value is compared with the subject by `==`, instead of `===`.
+    /// Without the subject, `when` values are evaluated as conditions.
+    fn gen_case(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        cond: Option<Node>,
+        when_: Vec<CaseBranch>,
+        else_: Node,
+        use_value: bool,
+        is_ret: bool,
+    ) -> Result<()> {
+        let succ_pos = ir.new_label();
+        // the subject is kept in a temporary register while evaluating `when` values.
+        let subject: Option<BcReg> = match cond {
+            Some(cond) => {
+                let reg = self.next_reg();
+                self.gen_expr(ctx, ir, id_store, cond, true, false)?;
+                Some(reg.into())
+            }
+            None => None,
+        };
+        let mut bodies = vec![];
+        for branch in when_ {
+            let body_pos = ir.new_label();
+            for val in branch.when {
+                let loc = val.loc;
+                let cond = match (subject, is_smi(&val)) {
+                    (Some(subject), Some(i)) => {
+                        let dst = self.push().into();
+                        ir.push(BcIr::Cmpri(CmpKind::Eq, dst, subject, i), loc);
+                        dst
+                    }
+                    (Some(subject), None) => {
+                        let dst = self.next_reg().into();
+                        self.gen_expr(ctx, ir, id_store, val, true, false)?;
+                        ir.push(BcIr::Cmp(CmpKind::Eq, dst, dst, subject), loc);
+                        dst
+                    }
+                    (None, _) => {
+                        let dst = self.next_reg().into();
+                        self.gen_expr(ctx, ir, id_store, val, true, false)?;
+                        dst
+                    }
+                };
+                ir.gen_condbr(cond, body_pos);
+                self.pop();
+            }
+            bodies.push((body_pos, *branch.body));
+        }
+        if subject.is_some() {
+            self.pop();
+        }
+        let mut branches = vec![(None, else_)];
+        branches.extend(bodies.into_iter().map(|(pos, body)| (Some(pos), body)));
+        let last = branches.len() - 1;
+        for (i, (pos, body)) in branches.into_iter().enumerate() {
+            if let Some(pos) = pos {
+                ir.apply_label(pos);
+            }
+            self.gen_expr(ctx, ir, id_store, body, use_value, is_ret)?;
+            if i != last && !is_ret {
+                ir.gen_br(succ_pos);
+                if use_value {
+                    self.pop();
+                }
+            }
+        }
+        ir.apply_label(succ_pos);
+        Ok(())
+    }
+
+    /// Generate bytecode Ir for `begin` with `rescue` and `else` clauses.
+    ///
+    /// Errors raised in *body* are rescued by the `rescue` clause through the exception table.
+    /// Only a bare `rescue`, which rescues StandardError, is supported for now.
+    fn gen_begin(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        body: Node,
+        mut rescue: Vec<RescueEntry>,
+        else_: Option<Node>,
+        use_value: bool,
+        loc: Loc,
+    ) -> Result<()> {
+        if rescue.is_empty() {
+            return match else_ {
+                Some(else_) => {
+                    self.gen_expr(ctx, ir, id_store, body, false, false)?;
+                    self.gen_expr(ctx, ir, id_store, else_, use_value, false)
+                }
+                None => self.gen_expr(ctx, ir, id_store, body, use_value, false),
+            };
+        }
+        if rescue.len() != 1 || !rescue[0].exception_list.is_empty() {
+            return Err(MonorubyErr::unsupported_feature(
+                "rescue with exception classes",
+                loc,
+                self.sourceinfo.clone(),
+            ));
+        }
+        let RescueEntry {
+            assign,
+            body: box rescue_body,
+            ..
+        } = rescue.remove(0);
+        let start = ir.new_label();
+        let end = ir.new_label();
+        let rescue_pos = ir.new_label();
+        let succ_pos = ir.new_label();
+        ir.apply_label(start);
+        self.gen_expr(ctx, ir, id_store, body, use_value && else_.is_none(), false)?;
+        ir.apply_label(end);
+        // errors in the `else` clause are not rescued.
+        if let Some(else_) = else_ {
+            self.gen_expr(ctx, ir, id_store, else_, use_value, false)?;
+        }
+        ir.gen_br(succ_pos);
+        if use_value {
+            self.pop();
+        }
+        ir.apply_label(rescue_pos);
+        let err_reg = match assign {
+            Some(box assign) => {
+                let reg = self.push().into();
+                self.gen_assign_lhs(ctx, ir, id_store, assign, reg)?;
+                self.pop();
+                Some(reg)
+            }
+            None => None,
+        };
+        ir.handlers.push(HandlerInfo {
+            start,
+            end,
+            dest: rescue_pos,
+            err_reg,
+            ensure: false,
+        });
+        self.gen_expr(ctx, ir, id_store, rescue_body, use_value, false)?;
+        ir.apply_label(succ_pos);
+        Ok(())
+    }
+
+    /// Generate bytecode Ir for `begin` with an `ensure` clause.
+    ///
+    /// The `ensure` clause is run after the `begin` completes, and before the error
+    /// is raised again when the `begin` is exited by an exception.
+    fn gen_ensure(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        body: Node,
+        rescue: Vec<RescueEntry>,
+        else_: Option<Node>,
+        ensure: Node,
+        use_value: bool,
+        loc: Loc,
+    ) -> Result<()> {
+        let start = ir.new_label();
+        let end = ir.new_label();
+        let ensure_pos = ir.new_label();
+        let succ_pos = ir.new_label();
+        ir.ensures.push(EnsureContext {
+            body: ensure.clone(),
+            loop_depth: ir.loops.len(),
+        });
+        ir.apply_label(start);
+        self.gen_begin(ctx, ir, id_store, body, rescue, else_, use_value, loc)?;
+        ir.apply_label(end);
+        ir.ensures.pop().unwrap();
+        self.gen_expr(ctx, ir, id_store, ensure.clone(), false, false)?;
+        ir.gen_br(succ_pos);
+        if use_value {
+            self.pop();
+        }
+        ir.apply_label(ensure_pos);
+        let err = self.push();
+        self.gen_expr(ctx, ir, id_store, ensure, false, false)?;
+        // raise the exception again by `raise(err)`.
+        let block = self.push().into();
+        ir.push(BcIr::Nil(block), Loc::default());
+        let raise = id_store.get_ident_id_from_string("raise".to_string());
+        let recv = ir.self_reg();
+        ir.push(BcIr::MethodCall(recv, raise, None, err, 1), loc);
+        self.popn(2);
+        ir.handlers.push(HandlerInfo {
+            start,
+            end,
+            dest: ensure_pos,
+            err_reg: Some(err.into()),
+            ensure: true,
+        });
+        self.push_unreachable_value(use_value);
+        ir.apply_label(succ_pos);
+        Ok(())
+    }
+
+    /// Generate bytecode Ir for the `ensure` clauses escaped by a jump, which are
+    /// the ones inside *loop_depth* loops, from the innermost one.
+    fn gen_escaped_ensures(
+        &mut self,
+        ctx: &mut FnStore,
+        ir: &mut IrContext,
+        id_store: &mut IdentifierTable,
+        loop_depth: usize,
+    ) -> Result<()> {
+        let ensures = ir.ensures.clone();
+        while let Some(ensure) = ir.ensures.last() {
+            if ensure.loop_depth < loop_depth {
+                break;
+            }
+            // a jump in the clause itself only runs the outer ones.
+            let ensure = ir.ensures.pop().unwrap();
+            self.gen_expr(ctx, ir, id_store, ensure.body, false, false)?;
+        }
+        ir.ensures = ensures;
+        Ok(())
+    }
+
+    /// Generate bytecode Ir for `while` loops (*cond_op* = true) and `until` loops.
     fn gen_while(
         &mut self,
         ctx: &mut FnStore,
         ir: &mut IrContext,
         id_store: &mut IdentifierTable,
+        cond_op: bool,
         cond: Node,
         body: Node,
         use_value: bool,
@@ -1642,17 +2830,21 @@ impl NormalFuncInfo {
         let cond_pos = ir.new_label();
         let succ_pos = ir.new_label();
         let break_pos = ir.new_label();
-        ir.loops.push((
-            LoopKind::While,
+        ir.loops.push(LoopContext {
             break_pos,
-            match use_value {
+            next_pos: cond_pos,
+            ret_reg: match use_value {
                 true => Some(self.next_reg().into()),
                 false => None,
             },
-        ));
+        });
         ir.apply_label(cond_pos);
-        let cond = self.gen_temp_expr(ctx, ir, id_store, cond)?.into();
-        ir.gen_condnotbr(cond, succ_pos);
+        let cond = self.gen_cond(ctx, ir, id_store, cond)?.into();
+        if cond_op {
+            ir.gen_condnotbr(cond, succ_pos);
+        } else {
+            ir.gen_condbr(cond, succ_pos);
+        }
         self.gen_expr(ctx, ir, id_store, body, false, false)?;
         ir.gen_br(cond_pos);
         ir.apply_label(succ_pos);
@@ -1682,11 +2874,13 @@ impl NormalFuncInfo {
         name: IdentId,
         prefix: Vec<IdentId>,
         toplevel: bool,
+        ignore_undefined: bool,
     ) -> ConstSiteId {
         let info = ConstSiteInfo {
             name,
             prefix,
             toplevel,
+            ignore_undefined,
             cache: (usize::MAX, None),
         };
         let id = store.constsite_info.len();
@@ -1737,11 +2931,22 @@ impl NormalFuncInfo {
                 BcIr::Integer(reg, num) => BcOp::Integer(self.get_index(reg), *num),
                 BcIr::Symbol(reg, name) => BcOp::Symbol(self.get_index(reg), *name),
                 BcIr::Literal(reg, num) => BcOp::Literal(self.get_index(reg), *num),
-                BcIr::LoadConst(reg, name) => BcOp::LoadConst(
+                BcIr::LoadConst(reg, prefix, name) => BcOp::LoadConst(
                     self.get_index(reg),
-                    self.add_constsite(store, *name, vec![], false),
+                    self.add_constsite(store, *name, prefix.clone(), false, false),
+                ),
+                BcIr::LoadConstOrNil(reg, name) => BcOp::LoadConst(
+                    self.get_index(reg),
+                    self.add_constsite(store, *name, vec![], false, true),
                 ),
                 BcIr::StoreConst(reg, name) => BcOp::StoreConst(self.get_index(reg), *name),
+                BcIr::LoadIvar(reg, name) => BcOp::LoadIvar(self.get_index(reg), *name),
+                BcIr::StoreIvar(reg, name) => BcOp::StoreIvar(self.get_index(reg), *name),
+                BcIr::ClassDef(reg, name) => BcOp::ClassDef(self.get_index(reg), *name),
+                BcIr::ClassDefEnd => BcOp::ClassDefEnd,
+                BcIr::Block(ret, func_id) => BcOp::Block(self.get_index(ret), *func_id),
+                BcIr::LoadGvar(reg, name) => BcOp::LoadGvar(self.get_index(reg), *name),
+                BcIr::StoreGvar(reg, name) => BcOp::StoreGvar(self.get_index(reg), *name),
                 BcIr::Nil(reg) => BcOp::Nil(self.get_index(reg)),
                 BcIr::Neg(dst, src) => BcOp::Neg(self.get_index(dst), self.get_index(src)),
                 BcIr::Add(dst, lhs, rhs) => BcOp::Add(
@@ -1770,6 +2975,11 @@ impl NormalFuncInfo {
                     self.get_index(lhs),
                     self.get_index(rhs),
                 ),
+                BcIr::Rem(dst, lhs, rhs) => BcOp::Rem(
+                    self.get_index(dst),
+                    self.get_index(lhs),
+                    self.get_index(rhs),
+                ),
                 BcIr::BitOr(dst, lhs, rhs) => BcOp::BitOr(
                     self.get_index(dst),
                     self.get_index(lhs),
@@ -1821,11 +3031,53 @@ impl NormalFuncInfo {
                     let ret = ret.map_or(0, |ret| self.get_index(&ret));
                     BcOp::ConcatStr(ret, self.get_index(&BcReg::from(*arg)), *len as u16)
                 }
+                BcIr::Yield(ret, arg, len) => {
+                    let ret = ret.map_or(0, |ret| self.get_index(&ret));
+                    BcOp::Yield(ret, self.get_index(&BcReg::from(*arg)), *len as u16)
+                }
+                BcIr::Array(ret, arg, len) => {
+                    let ret = self.get_index(ret);
+                    BcOp::Array(ret, self.get_index(&BcReg::from(*arg)), *len as u16)
+                }
+                BcIr::ExpandArray(src, dst, len) => {
+                    let src = self.get_index(src);
+                    BcOp::ExpandArray(src, self.get_index(&BcReg::from(*dst)), *len as u16)
+                }
+                BcIr::Range(ret, start, exclude_end) => {
+                    let ret = self.get_index(ret);
+                    BcOp::Range(ret, self.get_index(&BcReg::from(*start)), *exclude_end)
+                }
+                BcIr::InitOpt(params_len, rest) => BcOp::InitOpt(*params_len as u16, *rest),
+                BcIr::Hash(ret, arg, len) => {
+                    let ret = self.get_index(ret);
+                    BcOp::Hash(ret, self.get_index(&BcReg::from(*arg)), *len as u16)
+                }
+                BcIr::Index(ret, base, idx) => BcOp::Index(
+                    self.get_index(ret),
+                    self.get_index(base),
+                    self.get_index(idx),
+                ),
+                BcIr::IndexAssign(src, base, idx) => BcOp::IndexAssign(
+                    self.get_index(src),
+                    self.get_index(base),
+                    self.get_index(idx),
+                ),
             };
             ops.push(op.to_u64());
             locs.push(*loc);
         }
         self.bytecode = ops;
         self.sourcemap = locs;
+        let pos = |label: usize| ir.labels[label].unwrap().0 as usize;
+        self.exception_table = ir
+            .handlers
+            .iter()
+            .map(|handler| ExceptionEntry {
+                range: pos(handler.start)..pos(handler.end),
+                dest: pos(handler.dest),
+                err_reg: handler.err_reg.map(|reg| self.get_index(&reg)),
+                ensure: handler.ensure,
+            })
+            .collect();
     }
 }
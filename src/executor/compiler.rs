@@ -9,6 +9,11 @@ use super::*;
 mod vmgen;
 
 pub type JitFunc<'r, 's> = extern "C" fn(&'r mut Interp, &'s mut Globals) -> Option<Value>;
+pub type VmEntry = fn(&mut Interp, &mut Globals, FuncId) -> Option<Value>;
+/// fn(&mut Interp, &mut Globals, block, *const args, len)
+pub type BlockInvoker =
+    extern "C" fn(&mut Interp, &mut Globals, Value, *const Value, usize) -> Option<Value>;
+pub type JitCheckHook = fn(Option<Value>) -> Option<Value>;
 
 ///
 /// Bytecode compiler
@@ -24,6 +29,12 @@ pub struct Codegen {
     entry_find_method: DestLabel,
     pub vm_return: DestLabel,
     pub dispatch: Vec<CodePtr>,
+    /// Entry point to call a block from Rust code.
+    pub block_invoker: Option<BlockInvoker>,
+    /// Check each method against the VM when it is JIT-compiled. (`--jit-check`)
+    ///
+    /// The function is applied to the results of JIT-ed code, so that tests can inject a wrong one.
+    pub jit_check: Option<JitCheckHook>,
 }
 
 fn conv(reg: u16) -> i64 {
@@ -37,7 +48,7 @@ fn conv(reg: u16) -> i64 {
 ///
 /// Get an absolute address of the given method.
 ///
-/// If no method was found, return None (==0u64).
+/// If no method was found, or the method failed `--jit-check`, return None (==0u64).
 ///
 extern "C" fn get_func_address(
     interp: &mut Interp,
@@ -55,6 +66,12 @@ extern "C" fn get_func_address(
             let mut info = std::mem::take(&mut globals.func[func_id]);
             let label = interp.codegen.jit_compile(&mut info, &globals.func);
             globals.func[func_id] = info;
+            if let Some(hook) = interp.codegen.jit_check {
+                if let Err(err) = interp.check_method(globals, func_id, hook) {
+                    globals.set_error(err);
+                    return None;
+                }
+            }
             Some(label)
         }
     }
@@ -66,7 +83,8 @@ extern "C" fn define_method(
     name: IdentId,
     func: FuncId,
 ) {
-    globals.class.add_method(OBJECT_CLASS, name, func);
+    let class_id = globals.current_class();
+    globals.class.add_method(class_id, name, func);
 }
 
 pub extern "C" fn unimplemented_inst(_: &mut Interp, _: &mut Globals) {
@@ -90,6 +108,49 @@ extern "C" fn get_error_location(
     globals.push_error_location(loc, sourceinfo);
 }
 
+///
+/// Find the exception handler for the error raised at *pc* in the VM, and return its pc.
+///
+/// The exception object is stored to the register of the handler in the frame of *rbp*.
+/// If the error is not rescued, return null and leave the error pending.
+///
+extern "C" fn handle_error(
+    _interp: &mut Interp,
+    globals: &mut Globals,
+    func_id: FuncId,
+    pc: BcPc,
+    rbp: *mut u8,
+) -> BcPc {
+    let bc_base = globals.func[func_id].inst_pc();
+    let entry = match globals.func[func_id].as_normal().find_handler(pc - bc_base) {
+        Some(entry) => entry.clone(),
+        None => return BcPc::default(),
+    };
+    let err = match globals.take_exception(entry.ensure) {
+        Some(err) => err,
+        None => return BcPc::default(),
+    };
+    if let Some(reg) = entry.err_reg {
+        unsafe { *(rbp.sub(conv(reg) as usize) as *mut Value) = err };
+    }
+    let mut dest = bc_base;
+    dest += entry.dest as i32;
+    dest
+}
+
+///
+/// Take the pending error as an exception object for the exception handlers of JIT-ed code.
+///
+/// If the error is not rescued, return None and leave the error pending.
+///
+extern "C" fn take_exception(
+    _interp: &mut Interp,
+    globals: &mut Globals,
+    ensure: bool,
+) -> Option<Value> {
+    globals.take_exception(ensure)
+}
+
 impl Codegen {
     pub fn new() -> Self {
         let mut jit = JitMemory::new();
@@ -100,6 +161,7 @@ impl Codegen {
         let entry_find_method = jit.label();
         let jit_return = jit.label();
         let vm_return = jit.label();
+        let vm_raise = jit.label();
         let vm_entry = jit.label();
         monoasm!(&mut jit,
         entry_panic:
@@ -116,18 +178,7 @@ impl Codegen {
             // check call_kind.
             movl r15, [rbp - 8];
             testq r15, r15;
-            jne  jit_return;
-            // save return value
-            movq r15, rax;
-            movq rdi, rbx;
-            movq rsi, r12;
-            movl rdx, [rbp - 0x4];
-            movq rcx, r13;
-            subq rcx, 8;
-            movq rax, (get_error_location);
-            call rax;
-            // restore return value
-            movq rax, r15;
+            jeq  vm_raise;
         jit_return:
             leave;
             ret;
@@ -143,7 +194,7 @@ impl Codegen {
                 ret;
         };
         let dispatch = vec![entry_unimpl; 256];
-        Self {
+        let mut codegen = Self {
             jit,
             class_version,
             const_version,
@@ -152,7 +203,46 @@ impl Codegen {
             vm_entry,
             vm_return,
             dispatch,
-        }
+            block_invoker: None,
+            jit_check: None,
+        };
+        codegen.gen_vm_raise(vm_raise, jit_return);
+        codegen
+    }
+
+    ///
+    /// Generate the error path of the VM, which resumes the execution at the exception handler
+    /// if any, or returns to the caller after recording the location of the error.
+    ///
+    fn gen_vm_raise(&mut self, vm_raise: DestLabel, jit_return: DestLabel) {
+        let not_rescued = self.jit.label();
+        monoasm!(self.jit,
+        vm_raise:
+            movq rdi, rbx;
+            movq rsi, r12;
+            movl rdx, [rbp - 0x4];
+            movq rcx, r13;
+            subq rcx, 8;
+            movq r8, rbp;
+            movq rax, (handle_error);
+            call rax;
+            testq rax, rax;
+            jeq  not_rescued;
+            movq r13, rax;
+        );
+        self.fetch_and_dispatch();
+        monoasm!(self.jit,
+        not_rescued:
+            movq rdi, rbx;
+            movq rsi, r12;
+            movl rdx, [rbp - 0x4];
+            movq rcx, r13;
+            subq rcx, 8;
+            movq rax, (get_error_location);
+            call rax;
+            xorq rax, rax;
+            jmp  jit_return;
+        );
     }
 
     fn prologue(&mut self, regs: usize) {
@@ -223,6 +313,35 @@ impl Codegen {
         );
     }
 
+    ///
+    /// Call init_opt_args() to set up the optional and rest parameters.
+    ///
+    /// More arguments than the parameters may be given to a function with a rest parameter, and
+    /// they can lie beyond sp. So sp is pushed down during the call not to destroy them.
+    ///
+    /// #### @args
+    /// - *rdi*: the address of %0
+    /// - *rsi*: args len
+    /// - *rdx*: params len
+    /// - *rcx*: 1 if there is a rest parameter, otherwise 0
+    ///
+    fn call_init_opt_args(&mut self) {
+        monoasm!(self.jit,
+            // rax <- (args len + 1) * 8 + 16 bytes, aligned to 16.
+            movq rax, rsi;
+            shlq rax, 3;
+            addq rax, 39;
+            andq rax, (-16);
+            subq rsp, rax;
+            // the bottom slot is not used by the arguments.
+            movq [rsp], rax;
+            movq rax, (init_opt_args);
+            call rax;
+            movq rax, [rsp];
+            addq rsp, rax;
+        );
+    }
+
     fn side_generic_op(&mut self, generic: DestLabel, exit: DestLabel, ret: u16, func: u64) {
         self.jit.select(1);
         self.jit.bind_label(generic);
@@ -461,7 +580,10 @@ impl Codegen {
             movq r12, rsi;
             movl [rsp - 0x14], (main_id.0);
             movl [rsp - 0x18], 1;
-            movq [rsp - 0x20], (NIL_VALUE);
+            movq rax, (globals.main_object.get());
+            movq [rsp - 0x20], rax;
+            // no block is given to the toplevel.
+            movq [rsp - 0x28], (NIL_VALUE);
             xorq rdi, rdi;
             movq rax, (main.as_ptr());
             call rax;
@@ -470,10 +592,90 @@ impl Codegen {
             popq rbp;
             ret;
         );
+        if self.block_invoker.is_none() {
+            let func_offset = self.jit.const_i64(0);
+            let func_address = self.jit.const_i64(0);
+            let func_pc = self.jit.const_i64(0);
+            let _func_ret = self.jit.const_i64(0);
+            self.block_invoker =
+                Some(self.gen_block_invoker(func_offset, func_address, func_pc, 1));
+        }
         self.jit.finalize();
         self.jit.get_label_addr2(entry)
     }
 
+    ///
+    /// Generate an entry point to call a block from Rust code. (e.g. builtin methods which take a block)
+    ///
+    /// *call_kind* of the block frame is 0 for the VM and 1 for the JIT.
+    ///
+    fn gen_block_invoker(
+        &mut self,
+        func_offset: DestLabel,
+        func_address: DestLabel,
+        func_pc: DestLabel,
+        call_kind: u32,
+    ) -> BlockInvoker {
+        let entry = self.jit.get_current_address();
+        let loop_ = self.jit.label();
+        let loop_exit = self.jit.label();
+        let exit = self.jit.label();
+        monoasm!(self.jit,
+            pushq rbx;
+            pushq r12;
+            pushq r13;
+            pushq r14;
+            pushq r15;
+            movq rbx, rdi;  // rdi: &mut Interp
+            movq r12, rsi;  // rsi: &mut Globals
+            movq r14, rcx;  // rcx: *const Value
+            movq r15, r8;   // r8: len
+            // rdx: block
+            movq rcx, r15;
+            lea  r8, [rip + func_offset];
+            movq rax, (vmgen::get_block_data);
+            call rax;       // rax <- &BlockData
+            testq rax, rax;
+            jeq exit;
+            // set meta
+            movl rdi, [rax];
+            movl [rsp - 0x14], rdi;
+            movl [rsp - 0x18], (call_kind);
+            // set self
+            movq rdi, [rax + 8];
+            movq [rsp - 0x20], rdi;
+            // copy the arguments.
+            lea  rdi, [rsp - 0x28];
+            movq rcx, r15;
+            movq rdx, r14;
+        loop_:
+            testq rcx, rcx;
+            jeq  loop_exit;
+            movq rsi, [rdx];
+            movq [rdi], rsi;
+            addq rdx, 8;
+            subq rdi, 8;
+            subq rcx, 1;
+            jmp  loop_;
+        loop_exit:
+            // set the outer block next to the arguments.
+            movq rsi, [rax + 16];
+            movq [rdi], rsi;
+            movq r13, [rip + func_pc];
+            movq rdi, r15;
+            movq rax, [rip + func_address];
+            call rax;
+        exit:
+            popq r15;
+            popq r14;
+            popq r13;
+            popq r12;
+            popq rbx;
+            ret;
+        );
+        unsafe { std::mem::transmute(entry.as_ptr()) }
+    }
+
     fn jit_compile(&mut self, func: &mut FuncInfo, store: &FnStore) -> CodePtr {
         let now = Instant::now();
         let label = match &func.kind {
@@ -501,6 +703,31 @@ impl Codegen {
         label
     }
 
+    ///
+    /// Whether *func* can be run on sampled arguments by `--jit-check`.
+    ///
+    /// Only methods without calls and loops are run, so that the runs terminate and have
+    /// no effects other than on the copies of `Globals`.
+    ///
+    pub(super) fn is_checkable(func: &FuncInfo) -> bool {
+        let info = match &func.kind {
+            FuncKind::Normal(info) => info,
+            FuncKind::Builtin { .. } => return false,
+        };
+        func.arity() >= 0
+            && func.arity() == func.min_arity()
+            && info.bytecode().iter().all(|op| match BcOp::from_u64(*op) {
+                BcOp::Br(disp) | BcOp::CondBr(_, disp) | BcOp::CondNotBr(_, disp) => disp >= 0,
+                BcOp::MethodCall(..)
+                | BcOp::MethodDef(_)
+                | BcOp::ClassDef(..)
+                | BcOp::ClassDefEnd
+                | BcOp::Block(..)
+                | BcOp::Yield(..) => false,
+                _ => true,
+            })
+    }
+
     pub fn wrap_builtin(&mut self, abs_address: u64) -> CodePtr {
         //
         // generate a wrapper for a builtin function which has C ABI.
@@ -517,6 +744,10 @@ impl Codegen {
         //       +-------------+
         // -0x20 | %1(1st arg) |
         //       +-------------+
+        //       |             |
+        //       +-------------+
+        //       |    block    |
+        //       +-------------+
         //
         // argument registers:
         //   rdi: number of args
@@ -536,7 +767,8 @@ impl Codegen {
             andq rax, (0b01);
             addq rax, rdi;
             shlq rax, 3;
-            addq rax, 16;
+            // meta, self and the block.
+            addq rax, 32;
         );
         monoasm!(self.jit,
             lea  rdx, [rsp - 0x20];
@@ -573,8 +805,9 @@ impl Codegen {
                     jmp exit;
                 generic:
                     // generic path
-                    movq rax, ($generic);
-                    call rax;
+                );
+                self.call_binop($generic as _, self.vm_return);
+                monoasm!(self.jit,
                 exit:
                     // store the result to return reg.
                     movq [rbp - (conv($ret))], rax;
@@ -599,8 +832,9 @@ impl Codegen {
                     orq rax, (FALSE_VALUE);
                     jmp exit;
                 generic:
-                    movq rax, ($generic_func);
-                    call rax;
+                );
+                self.call_binop($generic_func as _, self.vm_return);
+                monoasm!(self.jit,
                 exit:
                     movq [rbp - (conv($ret))], rax;
                 );
@@ -625,8 +859,23 @@ impl Codegen {
             labels.push(self.jit.label());
         }
         self.prologue(func.total_reg_num());
+        // errors in protected instructions go to their handlers instead of returning.
+        let vm_return = self.vm_return;
+        let handlers: Vec<_> = func
+            .exception_table()
+            .iter()
+            .map(|entry| self.gen_handler(entry, labels[entry.dest]))
+            .collect();
         for (idx, op) in func.bytecode().iter().enumerate() {
             self.jit.bind_label(labels[idx]);
+            self.vm_return = match func
+                .exception_table()
+                .iter()
+                .position(|entry| entry.range.contains(&idx))
+            {
+                Some(i) => handlers[i],
+                None => vm_return,
+            };
             match BcOp::from_u64(*op) {
                 BcOp::Integer(ret, i) => {
                     let i = Value::int32(i).get();
@@ -643,7 +892,7 @@ impl Codegen {
                 }
                 BcOp::Literal(ret, id) => {
                     let v = store.get_literal(id);
-                    if v.is_packed_value() {
+                    if v.is_frozen() {
                         monoasm!(self.jit,
                           movq rax, (v.get());
                           movq [rbp - (conv(ret))], rax;
@@ -689,6 +938,65 @@ impl Codegen {
                     );
                     self.jit.select(0);
                 }
+                BcOp::LoadIvar(ret, id) => {
+                    monoasm!(self.jit,
+                        movq rsi, (id.get());  // name: IdentId
+                        movq rdi, [rbp - (conv(0))];  // self: Value
+                        movq rax, (get_instance_var);
+                        call rax;
+                        movq [rbp - (conv(ret))], rax;
+                    );
+                }
+                BcOp::StoreIvar(src, id) => {
+                    let jit_return = self.vm_return;
+                    monoasm!(self.jit,
+                        movq rdx, (id.get());  // name: IdentId
+                        movq rcx, [rbp - (conv(src))];  // val: Value
+                        movq rsi, [rbp - (conv(0))];  // self: Value
+                        movq rdi, r12;  // &mut Globals
+                        movq rax, (set_instance_var);
+                        call rax;
+                        testq rax, rax;
+                        jeq  jit_return;
+                    );
+                }
+                BcOp::ClassDef(ret, id) => {
+                    let jit_return = self.vm_return;
+                    monoasm!(self.jit,
+                        movq rsi, (id.get());  // name: IdentId
+                        movq rdi, r12;  // &mut Globals
+                        movq rax, (define_class);
+                        call rax;
+                        testq rax, rax;
+                        jeq  jit_return;
+                        movq [rbp - (conv(ret))], rax;
+                    );
+                }
+                BcOp::ClassDefEnd => {
+                    monoasm!(self.jit,
+                        movq rdi, r12;  // &mut Globals
+                        movq rax, (end_class_def);
+                        call rax;
+                    );
+                }
+                BcOp::LoadGvar(ret, id) => {
+                    monoasm!(self.jit,
+                        movq rsi, (id.get());  // name: IdentId
+                        movq rdi, r12;  // &Globals
+                        movq rax, (get_global_var);
+                        call rax;
+                        movq [rbp - (conv(ret))], rax;
+                    );
+                }
+                BcOp::StoreGvar(src, id) => {
+                    monoasm!(self.jit,
+                        movq rsi, (id.get());  // name: IdentId
+                        movq rdx, [rbp - (conv(src))];  // val: Value
+                        movq rdi, r12;  // &mut Globals
+                        movq rax, (set_global_var);
+                        call rax;
+                    );
+                }
                 BcOp::StoreConst(ret, id) => {
                     let const_version = self.const_version;
                     monoasm!(self.jit,
@@ -764,6 +1072,10 @@ impl Codegen {
                     self.load_binary_args(lhs, rhs);
                     self.generic_op(ret, div_values as _);
                 }
+                BcOp::Rem(ret, lhs, rhs) => {
+                    self.load_binary_args(lhs, rhs);
+                    self.generic_op(ret, rem_values as _);
+                }
                 BcOp::BitOr(ret, lhs, rhs) => bin_ops!(bit_or, ret, lhs, rhs),
                 BcOp::BitAnd(ret, lhs, rhs) => bin_ops!(bit_and, ret, lhs, rhs),
                 BcOp::BitXor(ret, lhs, rhs) => bin_ops!(bit_xor, ret, lhs, rhs),
@@ -811,7 +1123,86 @@ impl Codegen {
                         );
                     }
                 }
+                BcOp::Array(ret, arg, len) => {
+                    monoasm!(self.jit,
+                        lea rdi, [rbp - (conv(arg))];
+                        movq rsi, (len);
+                        movq rax, (make_array);
+                        call rax;
+                        movq [rbp - (conv(ret))], rax;
+                    );
+                }
+                BcOp::InitOpt(params_len, rest) => {
+                    // rdi (args len) is preserved since the prologue.
+                    monoasm!(self.jit,
+                        movq rsi, rdi;
+                        lea  rdi, [rbp - (conv(0))];
+                        movq rdx, (params_len);
+                        movq rcx, (rest as u64);
+                    );
+                    self.call_init_opt_args();
+                }
+                BcOp::ExpandArray(src, dst, len) => {
+                    monoasm!(self.jit,
+                        movq rdi, [rbp - (conv(src))];
+                        lea  rsi, [rbp - (conv(dst))];
+                        movq rdx, (len);
+                        movq rax, (expand_array);
+                        call rax;
+                    );
+                }
+                BcOp::Range(ret, start, exclude_end) => {
+                    let entry_return = self.vm_return;
+                    monoasm!(self.jit,
+                        movq rdi, rbx;
+                        movq rsi, r12;
+                        movq rdx, [rbp - (conv(start))];
+                        movq rcx, [rbp - (conv(start + 1))];
+                        movq r8, (exclude_end as u64);
+                        movq rax, (make_range);
+                        call rax;
+                        testq rax, rax;
+                        jeq entry_return;
+                        movq [rbp - (conv(ret))], rax;
+                    );
+                }
+                BcOp::Hash(ret, arg, len) => {
+                    let entry_return = self.vm_return;
+                    monoasm!(self.jit,
+                        movq rdi, rbx;
+                        movq rsi, r12;
+                        lea rdx, [rbp - (conv(arg))];
+                        movq rcx, (len);
+                        movq rax, (make_hash);
+                        call rax;
+                        testq rax, rax;
+                        jeq entry_return;
+                        movq [rbp - (conv(ret))], rax;
+                    );
+                }
+                BcOp::Index(ret, base, idx) => {
+                    self.load_binary_args(base, idx);
+                    self.generic_op(ret, get_index as _);
+                }
+                BcOp::IndexAssign(src, base, idx) => {
+                    self.load_binary_args(base, idx);
+                    monoasm!(self.jit,
+                        movq r8, [rbp - (conv(src))];
+                    );
+                    self.call_binop(set_index as _, self.vm_return);
+                }
                 BcOp::MethodCall(recv, id) => self.jit_method_call(store, recv, id),
+                BcOp::Yield(ret, args, len) => self.jit_yield(ret, args, len),
+                BcOp::Block(ret, func_id) => {
+                    monoasm!(self.jit,
+                        movq rdi, (func_id.0);
+                        movq rsi, [rbp - 16];  // self: Value
+                        movq rdx, [rbp - (conv(ret))];  // outer block: Value
+                        movq rax, (make_block);
+                        call rax;
+                        movq [rbp - (conv(ret))], rax;
+                    );
+                }
                 BcOp::MethodDef(id) => {
                     let MethodDefInfo { name, func } = store[id];
                     let class_version = self.class_version;
@@ -845,7 +1236,7 @@ impl Codegen {
                     let cond_ = conv(cond_);
                     let dest = labels[(idx as i32 + 1 + disp) as usize];
                     monoasm!(self.jit,
-                        cmpq rax, [rbp - (cond_)];
+                        movq rax, [rbp - (cond_)];
                         orq rax, 0x10;
                         cmpq rax, (FALSE_VALUE);
                         jeq dest;
@@ -853,6 +1244,7 @@ impl Codegen {
                 }
             }
         }
+        self.vm_return = vm_return;
         label
     }
 
@@ -893,7 +1285,8 @@ impl Codegen {
             );
         }
 
-        let sp_max = 0x40 + (len as u64 + (len % 2) as u64) * 8;
+        // the block is passed next to the arguments.
+        let sp_max = 0x40 + (len as u64 + 1 + ((len + 1) % 2) as u64) * 8;
         monoasm!(self.jit,
             // set meta
             movl [rsp - 0x18], 1;
@@ -901,8 +1294,8 @@ impl Codegen {
             movq rax, [rbp - (conv(recv))];
             movq [rsp - 0x20], rax;
         );
-        // set arguments
-        for i in 0..len {
+        // set arguments and the block
+        for i in 0..len + 1 {
             let reg = args + i;
             monoasm!(self.jit,
                 movq rax, [rbp - (conv(reg))];
@@ -978,9 +1371,95 @@ impl Codegen {
     }
 }
 
+impl Codegen {
+    ///
+    /// Generate an exception handler of JIT-ed code, which stores the exception object and
+    /// jumps to *dest*, or returns to the caller if the error is not rescued.
+    ///
+    fn gen_handler(&mut self, entry: &ExceptionEntry, dest: DestLabel) -> DestLabel {
+        let handler = self.jit.label();
+        let vm_return = self.vm_return;
+        self.jit.select(1);
+        monoasm!(self.jit,
+        handler:
+            movq rdi, rbx;
+            movq rsi, r12;
+            movq rdx, (entry.ensure as u64);
+            movq rax, (take_exception);
+            call rax;
+            testq rax, rax;
+            jeq  vm_return;
+        );
+        if let Some(reg) = entry.err_reg {
+            monoasm!(self.jit,
+                movq [rbp - (conv(reg))], rax;
+            );
+        }
+        monoasm!(self.jit,
+            jmp  dest;
+        );
+        self.jit.select(0);
+        handler
+    }
+
+    fn jit_yield(&mut self, ret: u16, args: u16, len: u16) {
+        // FuncData of the block.
+        let func_data = self.jit.const_i64(0);
+        let func_address = self.jit.const_i64(0);
+        let _func_pc = self.jit.const_i64(0);
+        let _func_ret = self.jit.const_i64(0);
+        let entry_return = self.vm_return;
+        monoasm!(self.jit,
+            movq rdi, rbx;  // &mut Interp
+            movq rsi, r12;  // &mut Globals
+            // the block is in the register next to the arguments.
+            movq rdx, [rbp - (conv(args + len))];
+            movq rcx, (len);
+            lea  r8, [rip + func_data];
+            movq rax, (vmgen::get_block_data);
+            call rax;       // rax <- &BlockData
+            testq rax, rax;
+            jeq entry_return;
+            // set meta
+            movl rdi, [rax];
+            movl [rsp - 0x14], rdi;
+            movl [rsp - 0x18], 1;
+            // set self
+            movq rdi, [rax + 8];
+            movq [rsp - 0x20], rdi;
+            // set the outer block next to the arguments.
+            movq rdi, [rax + 16];
+            movq [rsp - ((0x28 + len * 8) as i64)], rdi;
+        );
+        // set arguments
+        for i in 0..len {
+            let reg = args + i;
+            monoasm!(self.jit,
+                movq rax, [rbp - (conv(reg))];
+                movq [rsp - ((0x28 + i * 8) as i64)], rax;
+            );
+        }
+        monoasm!(self.jit,
+            movq rdi, (len);
+            movq rax, [rip + func_address];
+            call rax;
+            testq rax, rax;
+            jeq entry_return;
+        );
+        if ret != 0 {
+            monoasm!(self.jit,
+                movq [rbp - (conv(ret))], rax;
+            );
+        }
+    }
+}
+
 impl Codegen {
     pub fn precompile(&mut self, store: &mut FnStore, vm_entry: CodePtr) {
         for func in store.funcs_mut().iter_mut() {
+            if func.jit_label().is_some() {
+                continue;
+            }
             match &func.kind {
                 FuncKind::Normal(_) => {
                     func.set_jit_label(vm_entry);
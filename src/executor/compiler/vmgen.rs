@@ -5,7 +5,7 @@ use std::num::NonZeroU64;
 
 #[derive(Debug, Clone)]
 #[repr(C)]
-struct FuncData {
+pub(super) struct FuncData {
     /// stack offset. (only used in calling vm_entry)
     offset: i64,
     address: *mut u8,
@@ -34,9 +34,8 @@ macro_rules! cmp_ops {
               self.vm_get_rdi(); // rdi <- lhs addr
               self.vm_get_rsi(); // rsi <- rhs addr
               self.vm_get_addr_r15(); // r15 <- ret addr
+              self.call_binop([<cmp_ $op _values>] as _, self.vm_return);
               monoasm! { self.jit,
-                  movq rax, ([<cmp_ $op _values>]);
-                  call rax;
                   // store the result to return reg.
                   movq [r15], rax;
               };
@@ -58,9 +57,8 @@ macro_rules! cmp_ri_ops {
               let label = self.jit.get_current_address();
               self.vm_get_rdi(); // rdi <- lhs addr
               self.vm_get_addr_r15(); // r15 <- ret addr
+              self.call_binop([<cmp_ $op _ri_values>] as _, self.vm_return);
               monoasm! { self.jit,
-                  movq rax, ([<cmp_ $op _ri_values>]);
-                  call rax;
                   // store the result to return reg.
                   movq [r15], rax;
               };
@@ -107,8 +105,43 @@ extern "C" fn get_func_data(
     data.pc = globals.func[func_id].inst_pc();
 }
 
+///
+/// Get the block data of *block* and set the information of its function to *data*.
+///
+/// If *block* is not a block or *len* does not match the arity of the block, return None.
+///
+pub(super) extern "C" fn get_block_data(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    block: Value,
+    len: usize,
+    data: &mut FuncData,
+) -> Option<std::ptr::NonNull<BlockData>> {
+    let block_data = match block.as_proc() {
+        Some(block_data) => block_data,
+        None => {
+            globals.err_no_block_given();
+            return None;
+        }
+    };
+    let func_id = block_data.func_id;
+    globals.check_arity(func_id, len)?;
+    if globals.func[func_id].jit_label().is_none() {
+        let mut info = std::mem::take(&mut globals.func[func_id]);
+        interp.codegen.jit_compile(&mut info, &globals.func);
+        globals.func[func_id] = info;
+    }
+    get_func_data(interp, globals, func_id, data);
+    Some(std::ptr::NonNull::from(block_data))
+}
+
 extern "C" fn get_literal(_interp: &mut Interp, globals: &mut Globals, literal_id: u32) -> Value {
-    Value::dup(globals.func.get_literal(literal_id))
+    let val = globals.func.get_literal(literal_id);
+    if val.is_frozen() {
+        val
+    } else {
+        Value::dup(val)
+    }
 }
 
 extern "C" fn vm_define_method(
@@ -118,7 +151,8 @@ extern "C" fn vm_define_method(
     class_version: &mut usize,
 ) {
     let MethodDefInfo { name, func } = globals.func[def_id];
-    globals.class.add_method(OBJECT_CLASS, name, func);
+    let class_id = globals.current_class();
+    globals.class.add_method(class_id, name, func);
     *class_version += 1;
 }
 
@@ -130,7 +164,7 @@ impl Codegen {
     ///
     /// Generator of virtual machine.
     ///
-    pub fn construct_vm(&mut self) -> fn(&mut Interp, &mut Globals, FuncId) -> Option<Value> {
+    pub fn construct_vm(&mut self, main_object: Value) -> VmEntry {
         let vm_entry = self.vm_entry;
         let entry = self.jit.get_current_address();
         let func_offset = self.jit.const_i64(0);
@@ -172,7 +206,10 @@ impl Codegen {
             //       |             |
             //
             // set self
-            movq [rsp - 0x20], (NIL_VALUE);
+            movq rax, (main_object.get());
+            movq [rsp - 0x20], rax;
+            // no block is given to the toplevel.
+            movq [rsp - 0x28], (NIL_VALUE);
             movq rax, [rip + func_address];
             call rax;
             popq r15;
@@ -198,6 +235,8 @@ impl Codegen {
             pushq rbp;
             movq rbp, rsp;
             subq rsp, [rip + func_offset];
+            // keep args len for InitOpt, which is not destroyed by fetch_and_dispatch.
+            movq rcx, rdi;
         };
         self.fetch_and_dispatch();
 
@@ -240,6 +279,14 @@ impl Codegen {
         self.dispatch[9] = self.vm_symbol();
         self.dispatch[10] = self.vm_load_const();
         self.dispatch[11] = self.vm_store_const();
+        self.dispatch[12] = self.vm_load_ivar();
+        self.dispatch[13] = self.vm_store_ivar();
+        self.dispatch[14] = self.vm_load_gvar();
+        self.dispatch[15] = self.vm_store_gvar();
+        self.dispatch[16] = self.vm_class_def();
+        self.dispatch[17] = self.vm_class_def_end();
+        self.dispatch[18] = self.vm_block();
+        self.dispatch[19] = self.vm_init_opt();
 
         self.dispatch[129] = self.vm_neg();
         self.dispatch[130] = self.vm_addrr();
@@ -271,6 +318,16 @@ impl Codegen {
         self.dispatch[153] = shr;
         self.dispatch[154] = shl;
         self.dispatch[155] = self.vm_concat();
+        self.dispatch[156] = self.vm_remrr();
+        self.dispatch[157] = self.vm_array();
+        self.dispatch[158] = self.vm_index();
+        self.dispatch[159] = self.vm_index_assign();
+        self.dispatch[160] = self.vm_hash();
+        self.dispatch[161] = self.vm_yield(func_offset, func_address, func_pc);
+        self.dispatch[162] = self.vm_range();
+        self.dispatch[163] = self.vm_expand_array();
+
+        self.block_invoker = Some(self.gen_block_invoker(func_offset, func_address, func_pc, 0));
 
         self.jit.finalize();
         unsafe { std::mem::transmute(entry.as_ptr()) }
@@ -412,6 +469,103 @@ impl Codegen {
         label
     }
 
+    fn vm_array(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        self.vm_get_addr_rdi();
+        self.vm_get_addr_r15();
+        monoasm! { self.jit,
+            movq rax, (make_array);
+            call rax;
+            movq [r15], rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_expand_array(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        self.vm_get_addr_rdi(); // rdi <- dst addr
+        self.vm_get_addr_r15(); // r15 <- src addr
+        monoasm! { self.jit,
+            movq rdx, rsi;  // len
+            movq rsi, rdi;
+            movq rdi, [r15];
+            movq rax, (expand_array);
+            call rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_range(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        let vm_return = self.vm_return;
+        self.vm_get_addr_r15();
+        monoasm! { self.jit,
+            movq r8, rsi;  // exclude_end
+        };
+        self.vm_get_addr_rdi();
+        monoasm! { self.jit,
+            movq rdx, [rdi];      // start
+            movq rcx, [rdi - 8];  // end
+            movq rdi, rbx;
+            movq rsi, r12;
+            movq rax, (make_range);
+            call rax;
+            testq rax, rax;
+            jeq vm_return;
+            movq [r15], rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_hash(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        let vm_return = self.vm_return;
+        self.vm_get_addr_rdi();
+        self.vm_get_addr_r15();
+        monoasm! { self.jit,
+            movq rdx, rdi;  // args
+            movq rcx, rsi;  // len
+            movq rdi, rbx;
+            movq rsi, r12;
+            movq rax, (make_hash);
+            call rax;
+            testq rax, rax;
+            jeq vm_return;
+            movq [r15], rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_index(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        self.vm_get_rdi(); // rdi <- base
+        self.vm_get_rsi(); // rsi <- index
+        self.vm_get_addr_r15(); // r15 <- ret addr
+        self.call_binop(get_index as _, self.vm_return);
+        monoasm! { self.jit,
+            movq [r15], rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_index_assign(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        self.vm_get_rdi(); // rdi <- base
+        self.vm_get_rsi(); // rsi <- index
+        self.vm_get_addr_r15(); // r15 <- src addr
+        monoasm! { self.jit,
+            movq r8, [r15];
+        };
+        self.call_binop(set_index as _, self.vm_return);
+        self.fetch_and_dispatch();
+        label
+    }
+
     fn vm_method_call(
         &mut self,
         func_offset: DestLabel,
@@ -423,7 +577,6 @@ impl Codegen {
         let class_version = self.class_version;
         let exit = self.jit.label();
         let loop_ = self.jit.label();
-        let loop_exit = self.jit.label();
         let vm_return = self.vm_return;
         self.vm_get_addr_r15();
         monoasm! { self.jit,
@@ -477,17 +630,90 @@ impl Codegen {
             //
             movq rcx, rdi;
             movq rdi, r8;
-            testq r8, r8;
-            jeq  loop_exit;
-            //shlq r8, 3;
+            // copy the arguments and the block next to them.
+            addq r8, 1;
             negq r8;
         loop_:
             movq rax, [rcx + r8 * 8 + 8];
             movq [rsp + r8 * 8- 0x20], rax;
             addq r8, 1;
             jne  loop_;
+
+            movq rax, [rip + func_address];
+            call rax;
+            popq r15;
+            popq r13;
+            testq rax, rax;
+            jeq vm_return;
+        };
+        self.vm_store_r15_if_nonzero(exit);
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_yield(
+        &mut self,
+        func_offset: DestLabel,
+        func_address: DestLabel,
+        func_pc: DestLabel,
+    ) -> CodePtr {
+        let label = self.jit.get_current_address();
+        let exit = self.jit.label();
+        let error = self.jit.label();
+        let loop_ = self.jit.label();
+        let loop_exit = self.jit.label();
+        let vm_return = self.vm_return;
+        monoasm! { self.jit,
+            pushq r13;
+            pushq r15;      // ret reg
+            movq r13, rdi;  // r13 <- args reg
+            movq r15, rsi;  // r15 <- len
+            // the block is in the register next to the arguments.
+            movq rax, rdi;
+            addq rax, rsi;
+            negq rax;
+            movq rdx, [rbp + rax * 8 - 16]; // rdx: block
+            movq rdi, rbx;  // rdi: &mut Interp
+            movq rsi, r12;  // rsi: &mut Globals
+            movq rcx, r15;  // rcx: len
+            lea r8, [rip + func_offset]; // r8: &mut FuncData
+            movq rax, (get_block_data);
+            call rax;       // rax <- &BlockData
+            testq rax, rax;
+            jeq error;
+            movq rdx, rax;  // rdx <- &BlockData
+            // set meta/func_id
+            movl rdi, [rdx];
+            movl [rsp - 0x14], rdi;
+            // set meta/call_kind = 0(VM)
+            movl [rsp - 0x18], 0;
+            // set self
+            movq rdi, [rdx + 8];
+            movq [rsp - 0x20], rdi;
+            // set the outer block next to the arguments.
+            movq rdi, r15;
+            negq rdi;
+            movq rax, [rdx + 16];
+            movq [rsp + rdi * 8 - 0x28], rax;
+            movq rdi, r13;
+        };
+        self.vm_get_addr_rdi(); // rdi <- *args
+        monoasm! { self.jit,
+            // copy the arguments.
+            movq rcx, rdi;
+            movq rdi, r15;  // rdi <- len
+            testq r15, r15;
+            jeq  loop_exit;
+            movq r8, r15;
+            negq r8;
+        loop_:
+            movq rax, [rcx + r8 * 8 + 8];
+            movq [rsp + r8 * 8 - 0x20], rax;
+            addq r8, 1;
+            jne  loop_;
         loop_exit:
 
+            movq r13, [rip + func_pc];    // r13: BcPc
             movq rax, [rip + func_address];
             call rax;
             popq r15;
@@ -497,6 +723,39 @@ impl Codegen {
         };
         self.vm_store_r15_if_nonzero(exit);
         self.fetch_and_dispatch();
+        monoasm! { self.jit,
+        error:
+            popq r15;
+            popq r13;
+            jmp vm_return;
+        };
+        label
+    }
+
+    fn vm_block(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        self.vm_get_addr_r15();
+        monoasm! { self.jit,
+            movq rsi, [rbp - 16];  // self: Value
+            movq rdx, [r15];       // outer block: Value
+            movq rax, (make_block);
+            call rax;
+            movq [r15], rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_init_opt(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        monoasm! { self.jit,
+            movq rdx, r15;         // r15: params len
+            movq rsi, rcx;         // rcx: args len (set in vm_entry)
+            movq rcx, rdi;         // rdi: 1 if there is a rest parameter
+            lea  rdi, [rbp - 16];  // &%0
+        };
+        self.call_init_opt_args();
+        self.fetch_and_dispatch();
         label
     }
 
@@ -586,6 +845,94 @@ impl Codegen {
         label
     }
 
+    fn vm_load_ivar(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        self.vm_get_addr_r15();
+        monoasm! { self.jit,
+            movq rsi, rdi;  // name: IdentId
+            movq rdi, [rbp - 16];  // self: Value
+            movq rax, (get_instance_var);
+            call rax;
+            movq [r15], rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_store_ivar(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        let entry_return = self.vm_return;
+        self.vm_get_addr_r15();
+        monoasm! { self.jit,
+            movq rdx, rdi;  // name: IdentId
+            movq rcx, [r15];  // val: Value
+            movq rsi, [rbp - 16];  // self: Value
+            movq rdi, r12;  // &mut Globals
+            movq rax, (set_instance_var);
+            call rax;
+            testq rax, rax;
+            jeq  entry_return;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_load_gvar(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        self.vm_get_addr_r15();
+        monoasm! { self.jit,
+            movq rsi, rdi;  // name: IdentId
+            movq rdi, r12;  // &Globals
+            movq rax, (get_global_var);
+            call rax;
+            movq [r15], rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_store_gvar(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        self.vm_get_addr_r15();
+        monoasm! { self.jit,
+            movq rsi, rdi;  // name: IdentId
+            movq rdx, [r15];  // val: Value
+            movq rdi, r12;  // &mut Globals
+            movq rax, (set_global_var);
+            call rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_class_def(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        let entry_return = self.vm_return;
+        self.vm_get_addr_r15();
+        monoasm! { self.jit,
+            movq rsi, rdi;  // name: IdentId
+            movq rdi, r12;  // &mut Globals
+            movq rax, (define_class);
+            call rax;
+            testq rax, rax;
+            jeq  entry_return;
+            movq [r15], rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
+    fn vm_class_def_end(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        monoasm! { self.jit,
+            movq rdi, r12;  // &mut Globals
+            movq rax, (end_class_def);
+            call rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
     fn vm_neg(&mut self) -> CodePtr {
         let label = self.jit.get_current_address();
         let generic = self.jit.label();
@@ -719,6 +1066,20 @@ impl Codegen {
         label
     }
 
+    fn vm_remrr(&mut self) -> CodePtr {
+        let label = self.jit.get_current_address();
+        self.vm_get_rdi(); // rdi <- lhs
+        self.vm_get_rsi(); // rsi <- rhs
+        self.vm_get_addr_r15(); // r15 <- ret addr
+        self.call_binop(rem_values as _, self.vm_return);
+        monoasm! { self.jit,
+            // store the result to return reg.
+            movq [r15], rax;
+        };
+        self.fetch_and_dispatch();
+        label
+    }
+
     fn vm_bitorrr(&mut self) -> CodePtr {
         let label = self.jit.get_current_address();
         let generic = self.jit.label();
@@ -768,34 +1129,24 @@ impl Codegen {
         label
     }
 
+    ///
+    /// Shift operations are always done in `shl_values` / `shr_values`,
+    /// which flip the direction for negative shift counts.
+    ///
     fn vm_shift(&mut self) -> (CodePtr, CodePtr) {
         let shl_label = self.jit.get_current_address();
         let generic_shl = self.jit.label();
-        let generic_shr = self.jit.label();
-        let to_shl = self.jit.label();
-        let to_shr = self.jit.label();
-        let shl = self.jit.label();
-        let shr = self.jit.label();
         self.vm_get_rdi(); // rdi <- lhs
         self.vm_get_rsi(); // rsi <- rhs
         self.vm_get_addr_r15(); // r15 <- ret addr
         self.vm_generic_binop(generic_shl, shl_values as _);
-        monoasm! { self.jit,
-        to_shr:
-            negq rcx;
-            jmp shr;
-        };
 
         let shr_label = self.jit.get_current_address();
+        let generic_shr = self.jit.label();
         self.vm_get_rdi(); // rdi <- lhs
         self.vm_get_rsi(); // rsi <- rhs
         self.vm_get_addr_r15(); // r15 <- ret addr
         self.vm_generic_binop(generic_shr, shr_values as _);
-        monoasm! { self.jit,
-        to_shl:
-            negq rcx;
-            jmp shl;
-        };
         (shl_label, shr_label)
     }
 
@@ -1,4 +1,4 @@
-use std::io::{stdout, BufWriter, Stdout};
+use std::io::{stdout, BufWriter, Write};
 use std::path::PathBuf;
 
 use super::*;
@@ -21,8 +21,18 @@ pub struct Globals {
     error: Option<MonorubyErr>,
     /// warning level.
     pub warning: u8,
-    /// stdout.
-    pub stdout: BufWriter<Stdout>,
+    /// deeply freeze all values assigned to constants.
+    pub frozen_constants: bool,
+    /// stdout. It can be replaced to capture the output. (e.g. in tests)
+    pub stdout: BufWriter<Box<dyn Write>>,
+    /// the toplevel `self` object.
+    pub main_object: Value,
+    /// global variables.
+    global_vars: HashMap<IdentId, Value>,
+    /// classes whose bodies are being evaluated.
+    class_context: Vec<ClassId>,
+    /// blocks registered by `at_exit`.
+    at_exit: Vec<Value>,
 }
 
 impl Globals {
@@ -33,12 +43,23 @@ impl Globals {
             class: ClassStore::new(),
             error: None,
             warning,
-            stdout: BufWriter::new(stdout()),
+            frozen_constants: false,
+            stdout: BufWriter::new(Box::new(stdout())),
+            main_object: Value::new_object(OBJECT_CLASS),
+            global_vars: HashMap::default(),
+            class_context: vec![],
+            at_exit: vec![],
         };
         builtins::init_builtins(&mut globals);
         globals
     }
 
+    /// Freeze values assigned to constants, and freeze and deduplicate string literals.
+    pub fn enable_frozen(&mut self) {
+        self.frozen_constants = true;
+        self.func.frozen_string_literal = true;
+    }
+
     pub fn clone(&self) -> Self {
         Self {
             func: self.func.clone(),
@@ -46,7 +67,12 @@ impl Globals {
             class: self.class.clone(),
             error: None,
             warning: self.warning,
-            stdout: BufWriter::new(stdout()),
+            frozen_constants: self.frozen_constants,
+            stdout: BufWriter::new(Box::new(stdout())),
+            main_object: Value::dup(self.main_object),
+            global_vars: self.global_vars.clone(),
+            class_context: vec![],
+            at_exit: self.at_exit.clone(),
         }
     }
 }
@@ -55,7 +81,7 @@ impl Globals {
 // error handlers
 //
 impl Globals {
-    fn set_error(&mut self, err: MonorubyErr) {
+    pub(crate) fn set_error(&mut self, err: MonorubyErr) {
         self.error = Some(err);
     }
 
@@ -78,6 +104,26 @@ impl Globals {
         )));
     }
 
+    pub fn err_frozen(&mut self, val: Value) {
+        self.set_error(MonorubyErr::frozenerr(format!(
+            "can't modify frozen {}: {}",
+            val.class_id().get_name(self),
+            self.val_inspect(val),
+        )));
+    }
+
+    pub fn err_cant_coerce(&mut self, val: Value, expect: ClassId) {
+        let name = match val.unpack() {
+            RV::Nil | RV::Bool(_) => self.val_inspect(val),
+            _ => val.class_id().get_name(self),
+        };
+        self.set_error(MonorubyErr::typeerr(format!(
+            "{} can't be coerced into {}",
+            name,
+            expect.get_name(self),
+        )));
+    }
+
     pub fn err_no_implict_conv(&mut self, actual: ClassId, expect: ClassId) {
         self.set_error(MonorubyErr::typeerr(format!(
             "no implicit conversion of {} into {}",
@@ -86,10 +132,54 @@ impl Globals {
         )));
     }
 
+    pub fn err_index_too_small(&mut self, index: i64, min: i64) {
+        self.set_error(MonorubyErr::indexerr(format!(
+            "index {} too small for array; minimum: {}",
+            index, min
+        )));
+    }
+
+    pub fn err_no_block_given(&mut self) {
+        self.set_error(MonorubyErr::localjumperr(
+            "no block given (yield)".to_string(),
+        ));
+    }
+
+    pub fn err_not_class(&mut self, name: IdentId) {
+        self.set_error(MonorubyErr::typeerr(format!(
+            "{} is not a class",
+            self.get_ident_name(name)
+        )));
+    }
+
+    pub fn err_invalid_ivar_name(&mut self, name: IdentId) {
+        self.set_error(MonorubyErr::nameerr(format!(
+            "'{}' is not allowed as an instance variable name",
+            self.get_ident_name(name)
+        )));
+    }
+
+    pub fn err_argument(&mut self, msg: &str) {
+        self.set_error(MonorubyErr::argumenterr(msg.to_string()));
+    }
+
     pub fn take_error(&mut self) -> Option<MonorubyErr> {
         std::mem::take(&mut self.error)
     }
 
+    /// Take the pending error as an exception object to be rescued.
+    ///
+    /// Return None and leave the error pending if it is not a StandardError,
+    /// unless it is caught by an `ensure` clause. Internal errors are never taken.
+    pub(crate) fn take_exception(&mut self, ensure: bool) -> Option<Value> {
+        let class_id = self.error.as_ref()?.class_id()?;
+        if !ensure && !self.is_subclass_of(class_id, STANDARD_ERROR_CLASS) {
+            return None;
+        }
+        let err = self.take_error().unwrap();
+        Some(Value::new_exception(class_id, err))
+    }
+
     pub fn push_error_location(&mut self, loc: Loc, sourceinfo: SourceInfoRef) {
         match &mut self.error {
             Some(err) => {
@@ -107,15 +197,20 @@ impl Globals {
             RV::Bool(b) => format!("{:?}", b),
             RV::Integer(n) => format!("{}", n),
             RV::BigInt(n) => format!("{}", n),
-            RV::Float(f) => dtoa::Buffer::new().format(f).to_string(),
+            RV::Float(f) => float_tos(f),
             RV::Symbol(id) => self.get_ident_name(id).to_string(),
             RV::String(s) => match String::from_utf8(s.to_vec()) {
                 Ok(s) => s,
                 Err(_) => format!("{:?}", s),
             },
             RV::Object(rvalue) => match &rvalue.kind {
+                ObjKind::Object | ObjKind::Proc(_) => self.object_tos(val),
                 ObjKind::Class(class_id) => class_id.get_name(self),
                 ObjKind::Time(time) => time.to_string(),
+                ObjKind::Array(v) => self.array_tos(v),
+                ObjKind::Hash(map) => self.hash_tos(map),
+                ObjKind::Range(range) => self.range_tos(range, false),
+                ObjKind::Exception(err) => self.get_error_message(err),
                 _ => unreachable!(),
             },
         }
@@ -127,12 +222,17 @@ impl Globals {
             RV::Bool(b) => format!("{:?}", b).into_bytes(),
             RV::Integer(n) => format!("{}", n).into_bytes(),
             RV::BigInt(n) => format!("{}", n).into_bytes(),
-            RV::Float(f) => dtoa::Buffer::new().format(f).to_string().into_bytes(),
+            RV::Float(f) => float_tos(f).into_bytes(),
             RV::Symbol(id) => self.get_ident_name(id).to_string().into_bytes(),
             RV::String(s) => s.clone(),
             RV::Object(rvalue) => match &rvalue.kind {
+                ObjKind::Object | ObjKind::Proc(_) => self.object_tos(val).into_bytes(),
                 ObjKind::Class(class_id) => class_id.get_name(self).into_bytes(),
                 ObjKind::Time(time) => time.to_string().into_bytes(),
+                ObjKind::Array(v) => self.array_tos(v).into_bytes(),
+                ObjKind::Hash(map) => self.hash_tos(map).into_bytes(),
+                ObjKind::Range(range) => self.range_tos(range, false).into_bytes(),
+                ObjKind::Exception(err) => self.get_error_message(err).into_bytes(),
                 _ => unreachable!(),
             },
         }
@@ -144,20 +244,71 @@ impl Globals {
             RV::Bool(b) => format!("{:?}", b),
             RV::Integer(n) => format!("{}", n),
             RV::BigInt(n) => format!("{}", n),
-            RV::Float(f) => dtoa::Buffer::new().format(f).to_string(),
+            RV::Float(f) => float_tos(f),
             RV::Symbol(id) => format!(":{}", self.get_ident_name(id)),
             RV::String(s) => match String::from_utf8(s.to_vec()) {
-                Ok(s) => format!("\"{}\"", s),
+                Ok(s) => format!("\"{}\"", escape_string(&s)),
                 Err(_) => format!("{:?}", s),
             },
             RV::Object(rvalue) => match &rvalue.kind {
+                ObjKind::Object | ObjKind::Proc(_) => self.object_tos(val),
                 ObjKind::Class(class_id) => class_id.get_name(self),
                 ObjKind::Time(time) => time.to_string(),
+                ObjKind::Array(v) => self.array_tos(v),
+                ObjKind::Hash(map) => self.hash_tos(map),
+                ObjKind::Range(range) => self.range_tos(range, true),
+                ObjKind::Exception(err) => format!(
+                    "#<{}: {}>",
+                    val.class_id().get_name(self),
+                    self.get_error_message(err)
+                ),
                 _ => unreachable!(),
             },
         }
     }
 
+    fn object_tos(&self, val: Value) -> String {
+        if val == self.main_object {
+            return "main".to_string();
+        }
+        format!("#<{}:0x{:016x}>", val.class_id().get_name(self), val.get())
+    }
+
+    /// `Range#to_s` (*inspect* = false) and `Range#inspect` (*inspect* = true).
+    ///
+    /// A nil start or end is omitted unless both are nil. (e.g. `1..`, `..1`)
+    fn range_tos(&self, range: &RangeInfo, inspect: bool) -> String {
+        let tos = |val: Value| match inspect {
+            true => self.val_inspect(val),
+            false => self.val_tos(val),
+        };
+        let start = if range.start.is_nil() && !range.end.is_nil() {
+            String::new()
+        } else {
+            tos(range.start)
+        };
+        let end = if range.end.is_nil() && !range.start.is_nil() {
+            String::new()
+        } else {
+            tos(range.end)
+        };
+        let dots = if range.exclude_end { "..." } else { ".." };
+        format!("{}{}{}", start, dots, end)
+    }
+
+    fn array_tos(&self, v: &[Value]) -> String {
+        let elems: Vec<_> = v.iter().map(|v| self.val_inspect(*v)).collect();
+        format!("[{}]", elems.join(", "))
+    }
+
+    fn hash_tos(&self, map: &HashTable) -> String {
+        let elems: Vec<_> = map
+            .iter()
+            .map(|(k, v)| format!("{}=>{}", self.val_inspect(k.0), self.val_inspect(*v)))
+            .collect();
+        format!("{{{}}}", elems.join(", "))
+    }
+
     /// Get *FuncId* of the toplevel function.
     pub fn get_main_func(&self) -> FuncId {
         self.func.main.unwrap()
@@ -179,6 +330,18 @@ impl Globals {
         self.class[class_id].super_class()
     }
 
+    /// Whether *class_id* is *base* or one of its descendants.
+    pub(crate) fn is_subclass_of(&self, class_id: ClassId, base: ClassId) -> bool {
+        let mut class_id = Some(class_id);
+        while let Some(id) = class_id {
+            if id == base {
+                return true;
+            }
+            class_id = self.get_super_class(id);
+        }
+        false
+    }
+
     pub fn define_class_under_obj(&mut self, name: &str) -> Value {
         self.define_class(name, Some(OBJECT_CLASS))
     }
@@ -285,19 +448,88 @@ impl Globals {
                 return None;
             }
         };
+        self.check_arity(func_id, args_len)?;
+        Some(func_id)
+    }
+
+    ///
+    /// Check whether *args_len* arguments can be passed to the function *func_id*.
+    ///
+    pub(super) fn check_arity(&mut self, func_id: FuncId, args_len: usize) -> Option<()> {
         let arity = self.func[func_id].arity();
-        if arity != -1 && (arity as usize) != args_len {
-            self.error = Some(MonorubyErr::wrong_arguments(arity as usize, args_len));
+        let min_arity = self.func[func_id].min_arity();
+        if arity == -1 {
+            if args_len < min_arity as usize {
+                self.error = Some(MonorubyErr::wrong_arguments_min(
+                    min_arity as usize,
+                    args_len,
+                ));
+                return None;
+            }
+        } else if !((min_arity as usize)..=(arity as usize)).contains(&args_len) {
+            self.error = Some(if min_arity == arity {
+                MonorubyErr::wrong_arguments(arity as usize, args_len)
+            } else {
+                MonorubyErr::wrong_arguments_range(min_arity as usize, arity as usize, args_len)
+            });
             return None;
         }
-        Some(func_id)
+        Some(())
     }
 
     pub fn get_constant(&self, name: IdentId) -> Option<Value> {
         self.class.get_constants(name)
     }
 
+    /// Get the constant *name* defined in the class *parent*, or at the toplevel if *parent* is None.
+    pub fn get_scoped_constant(&self, parent: Option<ClassId>, name: IdentId) -> Option<Value> {
+        match parent {
+            Some(class_id) => self.class.get_class_constant(class_id, name),
+            None => self.get_constant(name),
+        }
+    }
+
+    /// Define the constant *name* in the class *class_id*.
+    pub fn set_class_constant(&mut self, class_id: ClassId, name: &str, val: Value) {
+        let name = self.get_ident_id(name);
+        self.class.set_class_constant(class_id, name, val);
+    }
+
+    /// Get the class in which methods are defined now.
+    pub fn current_class(&self) -> ClassId {
+        self.class_context.last().cloned().unwrap_or(OBJECT_CLASS)
+    }
+
+    pub fn push_class_context(&mut self, class_id: ClassId) {
+        self.class_context.push(class_id);
+    }
+
+    pub fn pop_class_context(&mut self) {
+        self.class_context.pop().unwrap();
+    }
+
+    /// Get the value of a global variable *name*. An unset global variable is nil.
+    pub fn get_gvar(&self, name: IdentId) -> Value {
+        self.global_vars.get(&name).cloned().unwrap_or(Value::nil())
+    }
+
+    pub fn set_gvar(&mut self, name: IdentId, val: Value) {
+        self.global_vars.insert(name, val);
+    }
+
+    pub(crate) fn push_at_exit(&mut self, block: Value) {
+        self.at_exit.push(block);
+    }
+
+    /// Take the block registered by `at_exit` most recently.
+    pub(crate) fn pop_at_exit(&mut self) -> Option<Value> {
+        self.at_exit.pop()
+    }
+
     pub fn set_constant(&mut self, name: IdentId, val: Value) -> Option<Value> {
+        if self.frozen_constants {
+            val.deep_freeze();
+        }
         self.class.set_constants(name, val)
     }
 
@@ -329,6 +561,8 @@ impl Globals {
     }
 
     pub fn compile_script(&mut self, code: String, path: impl Into<PathBuf>) -> Result<()> {
+        let code = self.split_data_section(code);
+        Self::reject_begin_end_blocks(&code)?;
         let res = match Parser::parse_program(code, path.into()) {
             Ok(res) => self
                 .func
@@ -337,6 +571,148 @@ impl Globals {
         };
         res
     }
+
+    ///
+    /// Compile *code* and run it in the VM, and return the value of the last expression.
+    ///
+    /// ```ignore
+    /// let mut globals = Globals::new(1);
+    /// let val = globals.eval_script("1 + 2")?;
+    /// assert_eq!(Value::new_integer(3), val);
+    /// ```
+    ///
+    /// Objects are allocated in a heap local to the current thread, so `Globals` and
+    /// the values it returns must be used only in the thread which created them.
+    ///
+    pub fn eval_script(&mut self, code: &str) -> Result<Value> {
+        self.compile_for_eval(code)?;
+        Interp::eval_toplevel(self)
+    }
+
+    /// Compile *code* and run it with the JIT compiler. See `eval_script`.
+    pub fn jit_eval_script(&mut self, code: &str) -> Result<Value> {
+        self.compile_for_eval(code)?;
+        Interp::jit_exec_toplevel(self)
+    }
+
+    /// Compile *code* to be run by a new `Interp`.
+    ///
+    /// Methods and global variables defined by the previous scripts are kept, but
+    /// their machine code is generated again since it is dropped with the previous `Interp`.
+    fn compile_for_eval(&mut self, code: &str) -> Result<()> {
+        self.compile_script(code.to_string(), "")?;
+        self.func.clear_jit_labels();
+        Ok(())
+    }
+
+    /// Cut off the data section following `__END__` from *code*, and make it readable via `DATA`.
+    fn split_data_section(&mut self, mut code: String) -> String {
+        let mut offset = 0;
+        let mut end = None;
+        for line in code.split_inclusive('\n') {
+            if line.trim_end_matches(&['\n', '\r'][..]) == "__END__" {
+                end = Some((offset, offset + line.len()));
+                break;
+            }
+            offset += line.len();
+        }
+        if let Some((start, data_start)) = end {
+            let data = code.split_off(data_start);
+            code.truncate(start);
+            let file_id = self.get_ident_id("File");
+            let file_class = self.get_constant(file_id).unwrap().as_class();
+            let mut data = Value::new_string(data.into_bytes());
+            data.change_class(file_class);
+            let data_id = self.get_ident_id("DATA");
+            self.set_constant(data_id, data);
+        }
+        code
+    }
+
+    /// `BEGIN { ... }` and `END { ... }` are not supported. Report them here with a clear
+    /// message instead of letting them fall into a parse error.
+    fn reject_begin_end_blocks(code: &str) -> Result<()> {
+        for (i, line) in code.lines().enumerate() {
+            for keyword in ["BEGIN", "END"] {
+                if let Some(rest) = line.trim_start().strip_prefix(keyword) {
+                    if rest.trim_start().starts_with('{') {
+                        return Err(MonorubyErr::unimplemented(format!(
+                            "unsupported feature: {} blocks (line {})",
+                            keyword,
+                            i + 1
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// Convert *f* to the shortest decimal representation which round-trips,
+/// in the same manner as Float#to_s.
+///
+/// Exponential notation is used when the decimal exponent is less than -4 or
+/// not less than 16.
+///
+pub(crate) fn float_tos(f: f64) -> String {
+    if f.is_nan() {
+        return "NaN".to_string();
+    } else if f.is_infinite() {
+        return if f > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    // `{:e}` gives the shortest round-tripping digits, e.g. "-1.2345e-7".
+    let sci = format!("{:e}", f);
+    let (mantissa, exp) = sci.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => ("-", m),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    if exp < -4 || exp >= 16 {
+        let frac = if digits.len() == 1 { "0" } else { &digits[1..] };
+        let exp_sign = if exp < 0 { '-' } else { '+' };
+        format!(
+            "{}{}.{}e{}{:02}",
+            sign,
+            &digits[..1],
+            frac,
+            exp_sign,
+            exp.abs()
+        )
+    } else if exp < 0 {
+        format!("{}0.{}{}", sign, "0".repeat((-exp - 1) as usize), digits)
+    } else {
+        let int_len = exp as usize + 1;
+        if digits.len() <= int_len {
+            format!("{}{}{}.0", sign, digits, "0".repeat(int_len - digits.len()))
+        } else {
+            format!("{}{}.{}", sign, &digits[..int_len], &digits[int_len..])
+        }
+    }
+}
+
+///
+/// Escape *s* in the same manner as String#inspect.
+///
+fn escape_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            '\r' => res.push_str("\\r"),
+            '\x1b' => res.push_str("\\e"),
+            '#' if matches!(chars.peek(), Some('{' | '$' | '@')) => res.push_str("\\#"),
+            c => res.push(c),
+        }
+    }
+    res
 }
 
 impl Globals {
@@ -358,6 +734,13 @@ impl Globals {
             MonorubyErrKind::DivideByZero => format!("divided by 0"),
             MonorubyErrKind::Range(msg) => msg.to_string(),
             MonorubyErrKind::Type(msg) => msg.to_string(),
+            MonorubyErrKind::Argument(msg) => msg.to_string(),
+            MonorubyErrKind::JitMismatch(msg) => msg.to_string(),
+            MonorubyErrKind::Frozen(msg) => msg.to_string(),
+            MonorubyErrKind::Index(msg) => msg.to_string(),
+            MonorubyErrKind::LocalJump(msg) => msg.to_string(),
+            MonorubyErrKind::Name(msg) => msg.to_string(),
+            MonorubyErrKind::Exception(_, msg) => msg.to_string(),
         }
     }
 }
@@ -10,6 +10,25 @@ pub const FLOAT_CLASS: ClassId = ClassId::new(7);
 pub const STRING_CLASS: ClassId = ClassId::new(8);
 pub const SYMBOL_CLASS: ClassId = ClassId::new(9);
 pub const TIME_CLASS: ClassId = ClassId::new(10);
+pub const ARRAY_CLASS: ClassId = ClassId::new(11);
+pub const HASH_CLASS: ClassId = ClassId::new(12);
+pub const PROC_CLASS: ClassId = ClassId::new(13);
+pub const RANGE_CLASS: ClassId = ClassId::new(14);
+pub const EXCEPTION_CLASS: ClassId = ClassId::new(15);
+pub const STANDARD_ERROR_CLASS: ClassId = ClassId::new(16);
+pub const NAME_ERROR_CLASS: ClassId = ClassId::new(17);
+pub const NO_METHOD_ERROR_CLASS: ClassId = ClassId::new(18);
+pub const ARGUMENT_ERROR_CLASS: ClassId = ClassId::new(19);
+pub const ZERO_DIVISION_ERROR_CLASS: ClassId = ClassId::new(20);
+pub const RANGE_ERROR_CLASS: ClassId = ClassId::new(21);
+pub const TYPE_ERROR_CLASS: ClassId = ClassId::new(22);
+pub const RUNTIME_ERROR_CLASS: ClassId = ClassId::new(23);
+pub const FROZEN_ERROR_CLASS: ClassId = ClassId::new(24);
+pub const INDEX_ERROR_CLASS: ClassId = ClassId::new(25);
+pub const LOCAL_JUMP_ERROR_CLASS: ClassId = ClassId::new(26);
+pub const STOP_ITERATION_CLASS: ClassId = ClassId::new(27);
+pub const METHOD_CLASS: ClassId = ClassId::new(28);
+pub const ENUMERATOR_CLASS: ClassId = ClassId::new(29);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(transparent)]
@@ -180,6 +199,19 @@ impl ClassStore {
         self.classes[0].constants.get(&name).cloned()
     }
 
+    pub fn set_class_constant(
+        &mut self,
+        class_id: ClassId,
+        name: IdentId,
+        val: Value,
+    ) -> Option<Value> {
+        self[class_id].constants.insert(name, val)
+    }
+
+    pub fn get_class_constant(&self, class_id: ClassId, name: IdentId) -> Option<Value> {
+        self[class_id].constants.get(&name).cloned()
+    }
+
     pub(super) fn get_real_class_obj(&self, val: Value) -> Value {
         let mut id = val.class_id();
         while self[id].is_singleton() {
@@ -18,6 +18,14 @@ pub enum MonorubyErrKind {
     DivideByZero,
     Range(String),
     Type(String),
+    Argument(String),
+    JitMismatch(String),
+    Frozen(String),
+    Index(String),
+    LocalJump(String),
+    Name(String),
+    /// An exception raised by `raise`, with its class and message.
+    Exception(ClassId, String),
 }
 
 impl MonorubyErr {
@@ -43,15 +51,63 @@ impl MonorubyErr {
 
     pub fn show_loc(&self) {
         if let Some((loc, sourceinfo)) = self.loc.first() {
+            if let Some((line, col)) = self.get_line_col() {
+                eprintln!("{}:{}:{}", sourceinfo.path.to_string_lossy(), line, col);
+            }
             sourceinfo.show_loc(loc);
         } else {
             eprintln!("location not defined.");
         }
     }
 
+    /// Get the line and the column (both 1-origin) where the error occured.
+    pub fn get_line_col(&self) -> Option<(usize, usize)> {
+        let (loc, sourceinfo) = self.loc.first()?;
+        let code = sourceinfo.code.get(..loc.0)?;
+        let line_start = code.rfind('\n').map_or(0, |i| i + 1);
+        let line = code.matches('\n').count() + 1;
+        let col = code[line_start..].chars().count() + 1;
+        Some((line, col))
+    }
+
     pub fn get_error_message(&self, globals: &Globals) -> String {
         globals.get_error_message(self)
     }
+
+    /// Get the message shown when the error reaches the top level, like `msg (RuntimeError)`.
+    pub fn get_uncaught_message(&self, globals: &Globals) -> String {
+        let msg = self.get_error_message(globals);
+        match self.class_id() {
+            Some(class_id) => format!("{} ({})", msg, class_id.get_name(globals)),
+            None => msg,
+        }
+    }
+
+    /// The exception class of the error, or None if it is an internal error
+    /// which can not be rescued.
+    pub(crate) fn class_id(&self) -> Option<ClassId> {
+        let class_id = match &self.kind {
+            MonorubyErrKind::UndefinedLocal(_)
+            | MonorubyErrKind::UninitConst(_)
+            | MonorubyErrKind::Name(_) => NAME_ERROR_CLASS,
+            MonorubyErrKind::MethodNotFound(_) => NO_METHOD_ERROR_CLASS,
+            MonorubyErrKind::WrongArguments(_) | MonorubyErrKind::Argument(_) => {
+                ARGUMENT_ERROR_CLASS
+            }
+            MonorubyErrKind::DivideByZero => ZERO_DIVISION_ERROR_CLASS,
+            MonorubyErrKind::Range(_) => RANGE_ERROR_CLASS,
+            MonorubyErrKind::Type(_) => TYPE_ERROR_CLASS,
+            MonorubyErrKind::Frozen(_) => FROZEN_ERROR_CLASS,
+            MonorubyErrKind::Index(_) => INDEX_ERROR_CLASS,
+            MonorubyErrKind::LocalJump(_) => LOCAL_JUMP_ERROR_CLASS,
+            MonorubyErrKind::Exception(class_id, _) => *class_id,
+            MonorubyErrKind::Syntax(_)
+            | MonorubyErrKind::Syntax2(_)
+            | MonorubyErrKind::Unimplemented(_)
+            | MonorubyErrKind::JitMismatch(_) => return None,
+        };
+        Some(class_id)
+    }
 }
 
 // Parser level errors.
@@ -87,6 +143,22 @@ impl MonorubyErr {
         )
     }
 
+    pub fn unsupported_unop(op: UnOp, loc: Loc, sourceinfo: SourceInfoRef) -> MonorubyErr {
+        MonorubyErr::new_with_loc(
+            MonorubyErrKind::Unimplemented(format!("unsupported operator {:?}", op)),
+            loc,
+            sourceinfo,
+        )
+    }
+
+    pub fn unsupported_feature(feature: &str, loc: Loc, sourceinfo: SourceInfoRef) -> MonorubyErr {
+        MonorubyErr::new_with_loc(
+            MonorubyErrKind::Unimplemented(format!("unsupported feature: {}", feature)),
+            loc,
+            sourceinfo,
+        )
+    }
+
     pub fn unsupported_lhs(lhs: Node, sourceinfo: SourceInfoRef) -> MonorubyErr {
         MonorubyErr::new_with_loc(
             MonorubyErrKind::Unimplemented(format!("unsupported lhs {:?}", lhs.kind)),
@@ -111,6 +183,14 @@ impl MonorubyErr {
         )
     }
 
+    pub fn invalid_jump(name: &str, loc: Loc, sourceinfo: SourceInfoRef) -> MonorubyErr {
+        MonorubyErr::new_with_loc(
+            MonorubyErrKind::Syntax2(format!("Invalid {}", name)),
+            loc,
+            sourceinfo,
+        )
+    }
+
     pub fn undefined_local(ident: String, loc: Loc, sourceinfo: SourceInfoRef) -> MonorubyErr {
         MonorubyErr::new_with_loc(MonorubyErrKind::UndefinedLocal(ident), loc, sourceinfo)
     }
@@ -129,6 +209,24 @@ impl MonorubyErr {
         )))
     }
 
+    pub fn wrong_arguments_range(min: usize, max: usize, actual: usize) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::WrongArguments(format!(
+            "number of arguments mismatch. expected:{}..{} actual:{}",
+            min, max, actual
+        )))
+    }
+
+    pub fn wrong_arguments_min(min: usize, actual: usize) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::WrongArguments(format!(
+            "number of arguments mismatch. expected:{}+ actual:{}",
+            min, actual
+        )))
+    }
+
+    pub fn unimplemented(msg: String) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::Unimplemented(msg))
+    }
+
     pub fn divide_by_zero() -> MonorubyErr {
         MonorubyErr::new(MonorubyErrKind::DivideByZero)
     }
@@ -144,4 +242,35 @@ impl MonorubyErr {
     pub fn typeerr(msg: String) -> MonorubyErr {
         MonorubyErr::new(MonorubyErrKind::Type(msg))
     }
+
+    pub fn argumenterr(msg: String) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::Argument(msg))
+    }
+
+    pub fn frozenerr(msg: String) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::Frozen(msg))
+    }
+
+    pub fn indexerr(msg: String) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::Index(msg))
+    }
+
+    pub fn localjumperr(msg: String) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::LocalJump(msg))
+    }
+
+    pub fn nameerr(msg: String) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::Name(msg))
+    }
+
+    pub fn exception(class_id: ClassId, msg: String) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::Exception(class_id, msg))
+    }
+
+    pub fn jit_mismatch(call: String, vm: String, jit: String) -> MonorubyErr {
+        MonorubyErr::new(MonorubyErrKind::JitMismatch(format!(
+            "results of VM and JIT differ in {}. vm:{} jit:{}",
+            call, vm, jit
+        )))
+    }
 }
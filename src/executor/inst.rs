@@ -11,8 +11,16 @@ pub(super) enum BcIr {
     Integer(BcReg, i32),
     Symbol(BcReg, IdentId),
     Literal(BcReg, u32),
-    LoadConst(BcReg, IdentId),
+    LoadConst(BcReg, Vec<IdentId>, IdentId), // ret, prefix, name
+    LoadConstOrNil(BcReg, IdentId),
     StoreConst(BcReg, IdentId),
+    LoadIvar(BcReg, IdentId),  // ret, name
+    StoreIvar(BcReg, IdentId), // src, name
+    LoadGvar(BcReg, IdentId),  // ret, name
+    StoreGvar(BcReg, IdentId), // src, name
+    ClassDef(BcReg, IdentId),  // ret, name
+    ClassDefEnd,
+    Block(BcReg, FuncId), // ret(which holds the outer block), func
     Nil(BcReg),
     Neg(BcReg, BcReg),                 // ret, src
     Add(BcReg, BcReg, BcReg),          // ret, lhs, rhs
@@ -21,6 +29,7 @@ pub(super) enum BcIr {
     Subri(BcReg, BcReg, i16),          // ret, lhs, int
     Mul(BcReg, BcReg, BcReg),          // ret, lhs, rhs
     Div(BcReg, BcReg, BcReg),          // ret, lhs, rhs
+    Rem(BcReg, BcReg, BcReg),          // ret, lhs, rhs
     BitOr(BcReg, BcReg, BcReg),        // ret, lhs, rhs
     BitAnd(BcReg, BcReg, BcReg),       // ret, lhs, rhs
     BitXor(BcReg, BcReg, BcReg),       // ret, lhs, rhs
@@ -33,6 +42,14 @@ pub(super) enum BcIr {
     MethodCall(BcReg, IdentId, Option<BcReg>, BcTemp, usize), // (recv, id, ret, args, args_len)
     MethodDef(IdentId, FuncId),
     ConcatStr(Option<BcReg>, BcTemp, usize), // (ret, args, args_len)
+    Array(BcReg, BcTemp, usize),             // (ret, args, args_len)
+    Index(BcReg, BcReg, BcReg),              // (ret, base, index)
+    IndexAssign(BcReg, BcReg, BcReg),        // (src, base, index)
+    Hash(BcReg, BcTemp, usize),              // (ret, args, pairs_len)
+    Yield(Option<BcReg>, BcTemp, usize),     // (ret, args, args_len)
+    Range(BcReg, BcTemp, bool),              // (ret, start, exclude_end)
+    ExpandArray(BcReg, BcTemp, usize),       // (src, dst, len)
+    InitOpt(usize, bool),                    // (params_len, rest)
 }
 
 ///
@@ -54,6 +71,20 @@ pub(super) enum BcOp {
     Literal(u16, u32),
     LoadConst(u16, ConstSiteId),
     StoreConst(u16, IdentId),
+    /// load instance variable(%ret, name)
+    LoadIvar(u16, IdentId),
+    /// store instance variable(%src, name)
+    StoreIvar(u16, IdentId),
+    /// load global variable(%ret, name)
+    LoadGvar(u16, IdentId),
+    /// store global variable(%src, name)
+    StoreGvar(u16, IdentId),
+    /// define or reopen a class(%ret, name)
+    ClassDef(u16, IdentId),
+    /// end of a class body
+    ClassDefEnd,
+    /// create a block(%ret, func_id)  %ret must hold the block of the current scope.
+    Block(u16, FuncId),
     /// nil(%reg)
     Nil(u16),
     /// negate(%ret, %src)
@@ -70,6 +101,8 @@ pub(super) enum BcOp {
     Mul(u16, u16, u16),
     /// div(%ret, %lhs, %rhs)
     Div(u16, u16, u16),
+    /// rem(%ret, %lhs, %rhs)
+    Rem(u16, u16, u16),
     /// bor(%ret, %lhs, %rhs)
     BitOr(u16, u16, u16),
     /// band(%ret, %lhs, %rhs)
@@ -94,6 +127,22 @@ pub(super) enum BcOp {
     MethodDef(MethodDefId),
     /// concatenate strings(ret, args, args_len)
     ConcatStr(u16, u16, u16),
+    /// create an array(ret, args, args_len)
+    Array(u16, u16, u16),
+    /// %ret = %base[%index]
+    Index(u16, u16, u16),
+    /// %base[%index] = %src
+    IndexAssign(u16, u16, u16),
+    /// create a hash(ret, args, pairs_len)
+    Hash(u16, u16, u16),
+    /// call the block of the current method(ret, args, args_len)
+    Yield(u16, u16, u16),
+    /// create a range(ret, start, exclude_end)  the end is in %start + 1.
+    Range(u16, u16, bool),
+    /// expand an array to registers for multiple assignment(src, dst, len)
+    ExpandArray(u16, u16, u16),
+    /// set up the optional and rest parameters(params_len, rest)  this must be the first instruction.
+    InitOpt(u16, bool),
 }
 
 fn enc_wl(opcode: u16, op1: u16, op2: u32) -> u64 {
@@ -143,6 +192,14 @@ impl BcOp {
             Symbol(op1, op2) => enc_wl(9, *op1, op2.get()),
             LoadConst(op1, op2) => enc_wl(10, *op1, op2.get()),
             StoreConst(op1, op2) => enc_wl(11, *op1, op2.get()),
+            LoadIvar(op1, op2) => enc_wl(12, *op1, op2.get()),
+            StoreIvar(op1, op2) => enc_wl(13, *op1, op2.get()),
+            LoadGvar(op1, op2) => enc_wl(14, *op1, op2.get()),
+            StoreGvar(op1, op2) => enc_wl(15, *op1, op2.get()),
+            ClassDef(op1, op2) => enc_wl(16, *op1, op2.get()),
+            ClassDefEnd => enc_wl(17, 0, 0),
+            Block(op1, op2) => enc_wl(18, *op1, op2.0),
+            InitOpt(op1, op2) => enc_wl(19, *op1, *op2 as u32),
 
             Neg(op1, op2) => enc_ww(129, *op1, *op2),
             Add(op1, op2, op3) => enc_www(130, *op1, *op2, *op3),
@@ -161,6 +218,14 @@ impl BcOp {
             Shr(op1, op2, op3) => enc_www(153, *op1, *op2, *op3),
             Shl(op1, op2, op3) => enc_www(154, *op1, *op2, *op3),
             ConcatStr(op1, op2, op3) => enc_www(155, *op1, *op2, *op3),
+            Rem(op1, op2, op3) => enc_www(156, *op1, *op2, *op3),
+            Array(op1, op2, op3) => enc_www(157, *op1, *op2, *op3),
+            Index(op1, op2, op3) => enc_www(158, *op1, *op2, *op3),
+            IndexAssign(op1, op2, op3) => enc_www(159, *op1, *op2, *op3),
+            Hash(op1, op2, op3) => enc_www(160, *op1, *op2, *op3),
+            Yield(op1, op2, op3) => enc_www(161, *op1, *op2, *op3),
+            Range(op1, op2, op3) => enc_www(162, *op1, *op2, *op3 as u16),
+            ExpandArray(op1, op2, op3) => enc_www(163, *op1, *op2, *op3),
         }
     }
 
@@ -180,6 +245,14 @@ impl BcOp {
                 9 => Self::Symbol(op1, IdentId::from(op2)),
                 10 => Self::LoadConst(op1, ConstSiteId(op2)),
                 11 => Self::StoreConst(op1, IdentId::from(op2)),
+                12 => Self::LoadIvar(op1, IdentId::from(op2)),
+                13 => Self::StoreIvar(op1, IdentId::from(op2)),
+                14 => Self::LoadGvar(op1, IdentId::from(op2)),
+                15 => Self::StoreGvar(op1, IdentId::from(op2)),
+                16 => Self::ClassDef(op1, IdentId::from(op2)),
+                17 => Self::ClassDefEnd,
+                18 => Self::Block(op1, FuncId(op2)),
+                19 => Self::InitOpt(op1, op2 != 0),
                 _ => unreachable!(),
             }
         } else {
@@ -212,6 +285,14 @@ impl BcOp {
                 153 => Self::Shr(op1, op2, op3),
                 154 => Self::Shl(op1, op2, op3),
                 155 => Self::ConcatStr(op1, op2, op3),
+                156 => Self::Rem(op1, op2, op3),
+                157 => Self::Array(op1, op2, op3),
+                158 => Self::Index(op1, op2, op3),
+                159 => Self::IndexAssign(op1, op2, op3),
+                160 => Self::Hash(op1, op2, op3),
+                161 => Self::Yield(op1, op2, op3),
+                162 => Self::Range(op1, op2, op3 != 0),
+                163 => Self::ExpandArray(op1, op2, op3),
                 _ => unreachable!(),
             }
         }
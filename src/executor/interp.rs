@@ -76,12 +76,15 @@ impl std::default::Default for BcPc {
 ///
 pub struct Interp {
     pub codegen: Codegen,
+    /// Entry point of the VM. (None for JIT execution)
+    vm_entry_point: Option<VmEntry>,
 }
 
 impl Interp {
     fn new() -> Self {
         Self {
             codegen: Codegen::new(),
+            vm_entry_point: None,
         }
     }
 
@@ -89,20 +92,232 @@ impl Interp {
         let mut eval = Self::new();
         let f = eval.codegen.exec_toplevel(globals);
         let res = f(&mut eval, globals);
-        globals.stdout.flush().unwrap();
-        res.ok_or_else(|| globals.take_error().unwrap())
+        eval.finish_toplevel(globals, res)
+    }
+
+    ///
+    /// Execute the toplevel in the JIT, and check each method against the VM when it is compiled.
+    ///
+    pub fn jit_check_toplevel(globals: &mut Globals) -> Result<Value> {
+        Self::jit_check_toplevel_with(globals, |res| res)
+    }
+
+    ///
+    /// Execute the toplevel with `--jit-check`, applying *hook* to the results of JIT-ed methods.
+    ///
+    /// *hook* is separated so that tests can inject a wrong result of the JIT.
+    ///
+    pub(crate) fn jit_check_toplevel_with(
+        globals: &mut Globals,
+        hook: JitCheckHook,
+    ) -> Result<Value> {
+        let mut eval = Self::new();
+        eval.codegen.jit_check = Some(hook);
+        let f = eval.codegen.exec_toplevel(globals);
+        let res = f(&mut eval, globals);
+        eval.finish_toplevel(globals, res)
     }
 
     pub fn eval_toplevel(globals: &mut Globals) -> Result<Value> {
         let main_id = globals.get_main_func();
-        let mut eval = Self::new();
+        let mut eval = Self::new_vm(globals);
+        let f = eval.vm_entry_point.unwrap();
+        let res = f(&mut eval, globals, main_id);
+        eval.finish_toplevel(globals, res)
+    }
 
-        let f = eval.codegen.construct_vm();
+    ///
+    /// Create a VM, and make all functions in *globals* which are not compiled yet run in it.
+    ///
+    fn new_vm(globals: &mut Globals) -> Self {
+        let mut eval = Self::new();
+        let f = eval.codegen.construct_vm(globals.main_object);
+        eval.vm_entry_point = Some(f);
         let vm_entry = eval.codegen.jit.get_label_address(eval.codegen.vm_entry);
         eval.codegen.precompile(&mut globals.func, vm_entry);
+        eval
+    }
 
-        let res = f(&mut eval, globals, main_id);
+    ///
+    /// Run the blocks registered by `at_exit` in reverse order, and return the result of the toplevel.
+    ///
+    /// An error in the toplevel takes precedence over errors in the blocks.
+    ///
+    fn finish_toplevel(&mut self, globals: &mut Globals, res: Option<Value>) -> Result<Value> {
+        let mut res = res.ok_or_else(|| globals.take_error().unwrap());
+        while let Some(block) = globals.pop_at_exit() {
+            if self.invoke_block(globals, block, &[]).is_none() {
+                let err = globals.take_error().unwrap();
+                if res.is_ok() {
+                    res = Err(err);
+                }
+            }
+        }
         globals.stdout.flush().unwrap();
-        res.ok_or_else(|| globals.take_error().unwrap())
+        res
+    }
+
+    ///
+    /// Run the JIT-compiled method *func_id* on sampled arguments, and check that the VM agrees.
+    ///
+    /// Both run on copies of *globals*, and *hook* is applied to the results of the JIT.
+    /// Values are compared by their `inspect` strings, so that e.g. NaNs agree with each other.
+    ///
+    pub(super) fn check_method(
+        &mut self,
+        globals: &Globals,
+        func_id: FuncId,
+        hook: JitCheckHook,
+    ) -> Result<()> {
+        /// the maximum number of the runs for a method.
+        const MAX_RUNS: usize = 64;
+        let func = &globals.func[func_id];
+        if !Codegen::is_checkable(func) {
+            return Ok(());
+        }
+        let samples = [
+            Value::new_integer(0),
+            Value::new_integer(1),
+            Value::new_integer(-7),
+            Value::new_float(2.5),
+            Value::new_float(-0.0),
+            Value::new_float(f64::NAN),
+        ];
+        let arity = func.arity() as u32;
+        let runs = samples
+            .len()
+            .checked_pow(arity)
+            .map_or(MAX_RUNS, |runs| runs.min(MAX_RUNS));
+
+        let mut vm_globals = globals.clone();
+        vm_globals.func.clear_jit_labels();
+        let mut vm = Self::new_vm(&mut vm_globals);
+        let mut jit_globals = globals.clone();
+        let show = |globals: &mut Globals, res: Option<Value>| match res {
+            Some(val) => Ok(globals.val_inspect(val)),
+            None => Err(globals.take_error().unwrap()),
+        };
+        for run in 0..runs {
+            // the *run*-th combination of the samples.
+            let mut rest = run;
+            let args: Vec<_> = (0..arity)
+                .map(|_| {
+                    let arg = samples[rest % samples.len()];
+                    rest /= samples.len();
+                    arg
+                })
+                .collect();
+            let main_object = vm_globals.main_object;
+            let res = vm.invoke_method(&mut vm_globals, func_id, main_object, &args);
+            let vm_res = show(&mut vm_globals, res);
+            let main_object = jit_globals.main_object;
+            let res = hook(self.invoke_method(&mut jit_globals, func_id, main_object, &args));
+            let jit_res = show(&mut jit_globals, res);
+            let agree = match (&vm_res, &jit_res) {
+                (Ok(vm_val), Ok(jit_val)) => vm_val == jit_val,
+                (Err(vm_err), Err(jit_err)) => vm_err.kind == jit_err.kind,
+                _ => false,
+            };
+            if !agree {
+                let show = |res: Result<String>| match res {
+                    Ok(s) => s,
+                    Err(err) => err.get_error_message(globals),
+                };
+                let args: Vec<_> = args.iter().map(|arg| globals.val_inspect(*arg)).collect();
+                let call = format!("{}({})", func.name().unwrap_or_default(), args.join(", "));
+                return Err(MonorubyErr::jit_mismatch(call, show(vm_res), show(jit_res)));
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Call *block* with *args* from Rust code. (e.g. builtin methods which take a block)
+    ///
+    /// As in Ruby, extra arguments are dropped and missing ones are filled with nil,
+    /// and a single Array argument is expanded for a block with several parameters.
+    ///
+    pub fn invoke_block(
+        &mut self,
+        globals: &mut Globals,
+        block: Value,
+        args: &[Value],
+    ) -> Option<Value> {
+        let mut args = args.to_vec();
+        if let Some(data) = block.as_proc() {
+            // variadic builtins (e.g. the procs made by `Symbol#to_proc`) take the arguments as they are.
+            let arity = globals.func[data.func_id].arity();
+            if arity >= 0 {
+                let arity = arity as usize;
+                if arity > 1 && args.len() == 1 {
+                    if let Some(ary) = args[0].as_array() {
+                        args = ary.clone();
+                    }
+                }
+                args.resize(arity, Value::nil());
+            }
+        }
+        let f = self.codegen.block_invoker.unwrap();
+        f(self, globals, block, args.as_ptr(), args.len())
+    }
+
+    ///
+    /// Call *block* with *args* from Rust code, with `self` in the block body rebound to *self_val*.
+    /// (e.g. `instance_eval` and `class_eval`)
+    ///
+    pub fn invoke_block_with_self(
+        &mut self,
+        globals: &mut Globals,
+        block: Value,
+        self_val: Value,
+        args: &[Value],
+    ) -> Option<Value> {
+        let block = match block.as_proc() {
+            Some(data) => Value::new_proc(data.func_id, self_val, data.outer_block),
+            None => block,
+        };
+        self.invoke_block(globals, block, args)
+    }
+
+    ///
+    /// Call the method *func_id* of *self_val* with *args* from Rust code. (e.g. operator methods defined in Ruby)
+    ///
+    /// No block is passed to the method.
+    ///
+    pub fn invoke_method(
+        &mut self,
+        globals: &mut Globals,
+        func_id: FuncId,
+        self_val: Value,
+        args: &[Value],
+    ) -> Option<Value> {
+        // A method is invoked as a block whose self is the receiver.
+        let block = Value::new_proc(func_id, self_val, Value::nil());
+        let f = self.codegen.block_invoker.unwrap();
+        f(self, globals, block, args.as_ptr(), args.len())
+    }
+
+    ///
+    /// Compile *code* as a new toplevel function and execute it in the current VM or JIT context.
+    ///
+    /// Local variables of the caller are not visible from *code*.
+    ///
+    pub fn eval_script(&mut self, globals: &mut Globals, code: String) -> Option<Value> {
+        if let Err(err) = globals.compile_script(code, "(eval)") {
+            globals.set_error(err);
+            return None;
+        }
+        let main_id = globals.get_main_func();
+        match self.vm_entry_point {
+            Some(f) => {
+                let vm_entry = self.codegen.jit.get_label_address(self.codegen.vm_entry);
+                self.codegen.precompile(&mut globals.func, vm_entry);
+                f(self, globals, main_id)
+            }
+            None => {
+                let f = self.codegen.exec_toplevel(globals);
+                f(self, globals)
+            }
+        }
     }
 }
@@ -8,11 +8,45 @@ use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Shl, Shr, Sub};
 // Generic operations.
 //
 
+///
+/// Find the operator method *name* of *lhs* which is defined in Ruby.
+///
+/// Builtin operators are not returned, as their behavior is already inlined in the generic operations.
+///
+pub(super) fn find_user_operator(globals: &Globals, lhs: Value, name: IdentId) -> Option<FuncId> {
+    let func_id = globals.get_method_inner(lhs.class_id(), name)?;
+    match globals.func[func_id].kind {
+        FuncKind::Normal(_) => Some(func_id),
+        FuncKind::Builtin { .. } => None,
+    }
+}
+
+///
+/// Call the operator method *name* of *lhs* with *args*.
+///
+/// Raise NoMethodError if *lhs* has no such method defined in Ruby.
+///
+fn call_user_operator(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    name: IdentId,
+    lhs: Value,
+    args: &[Value],
+) -> Option<Value> {
+    match find_user_operator(globals, lhs, name) {
+        Some(func_id) => interp.invoke_method(globals, func_id, lhs, args),
+        None => {
+            globals.err_method_not_found(name);
+            None
+        }
+    }
+}
+
 macro_rules! binop_values {
     (($op:ident, $op_str:expr)) => {
         paste! {
             pub(super) extern "C" fn [<$op _values>](
-                _interp: &mut Interp,
+                interp: &mut Interp,
                 globals: &mut Globals,
                 lhs: Value,
                 rhs: Value
@@ -26,12 +60,11 @@ macro_rules! binop_values {
                     (RV::Integer(lhs), RV::BigInt(rhs)) => Value::new_bigint(BigInt::from(lhs).$op(rhs)),
                     (RV::BigInt(lhs), RV::BigInt(rhs)) => Value::new_bigint(lhs.$op(rhs)),
                     (RV::Integer(lhs), RV::Float(rhs)) => Value::new_float((lhs as f64).$op(&rhs)),
+                    (RV::BigInt(lhs), RV::Float(rhs)) => Value::new_float(lhs.to_f64().unwrap().$op(&rhs)),
                     (RV::Float(lhs), RV::Integer(rhs)) => Value::new_float(lhs.$op(&(rhs as f64))),
+                    (RV::Float(lhs), RV::BigInt(rhs)) => Value::new_float(lhs.$op(&rhs.to_f64().unwrap())),
                     (RV::Float(lhs), RV::Float(rhs)) => Value::new_float(lhs.$op(&rhs)),
-                    _ => {
-                        globals.err_method_not_found($op_str);
-                        return None;
-                    }
+                    _ => return call_user_operator(interp, globals, $op_str, lhs, &[rhs]),
                 };
                 Some(v)
             }
@@ -44,13 +77,45 @@ macro_rules! binop_values {
 }
 
 binop_values!(
-    (add, IdentId::_ADD),
     //(sub, IdentId::_SUB),
     (mul, IdentId::_MUL)
 );
 
+pub(super) extern "C" fn add_values(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    lhs: Value,
+    rhs: Value,
+) -> Option<Value> {
+    let v = match (lhs.unpack(), rhs.unpack()) {
+        (RV::Integer(lhs), RV::Integer(rhs)) => match lhs.checked_add(rhs) {
+            Some(res) => Value::new_integer(res),
+            None => Value::new_bigint(BigInt::from(lhs).add(BigInt::from(rhs))),
+        },
+        (RV::BigInt(lhs), RV::Integer(rhs)) => Value::new_bigint(lhs.add(BigInt::from(rhs))),
+        (RV::Integer(lhs), RV::BigInt(rhs)) => Value::new_bigint(BigInt::from(lhs).add(rhs)),
+        (RV::BigInt(lhs), RV::BigInt(rhs)) => Value::new_bigint(lhs.add(rhs)),
+        (RV::Integer(lhs), RV::Float(rhs)) => Value::new_float((lhs as f64).add(&rhs)),
+        (RV::BigInt(lhs), RV::Float(rhs)) => Value::new_float(lhs.to_f64().unwrap().add(&rhs)),
+        (RV::Float(lhs), RV::Integer(rhs)) => Value::new_float(lhs.add(&(rhs as f64))),
+        (RV::Float(lhs), RV::BigInt(rhs)) => Value::new_float(lhs.add(&rhs.to_f64().unwrap())),
+        (RV::Float(lhs), RV::Float(rhs)) => Value::new_float(lhs.add(&rhs)),
+        (RV::String(lhs), RV::String(rhs)) => {
+            let mut res = lhs.clone();
+            res.extend_from_slice(rhs);
+            Value::new_string(res)
+        }
+        (RV::String(_), _) => {
+            globals.err_no_implict_conv(rhs.class_id(), STRING_CLASS);
+            return None;
+        }
+        _ => return call_user_operator(interp, globals, IdentId::_ADD, lhs, &[rhs]),
+    };
+    Some(v)
+}
+
 pub(super) extern "C" fn sub_values(
-    _interp: &mut Interp,
+    interp: &mut Interp,
     globals: &mut Globals,
     lhs: Value,
     rhs: Value,
@@ -64,30 +129,30 @@ pub(super) extern "C" fn sub_values(
         (RV::Integer(lhs), RV::BigInt(rhs)) => Value::new_bigint(BigInt::from(lhs).sub(rhs)),
         (RV::BigInt(lhs), RV::BigInt(rhs)) => Value::new_bigint(lhs.sub(rhs)),
         (RV::Integer(lhs), RV::Float(rhs)) => Value::new_float((lhs as f64).sub(&rhs)),
+        (RV::BigInt(lhs), RV::Float(rhs)) => Value::new_float(lhs.to_f64().unwrap().sub(&rhs)),
         (RV::Float(lhs), RV::Integer(rhs)) => Value::new_float(lhs.sub(&(rhs as f64))),
+        (RV::Float(lhs), RV::BigInt(rhs)) => Value::new_float(lhs.sub(&rhs.to_f64().unwrap())),
         (RV::Float(lhs), RV::Float(rhs)) => Value::new_float(lhs.sub(&rhs)),
-        (RV::Object(lhs), RV::Object(rhs)) => match (&lhs.kind, &rhs.kind) {
+        (RV::Object(lhs_obj), RV::Object(rhs_obj)) => match (&lhs_obj.kind, &rhs_obj.kind) {
             (ObjKind::Time(lhs), ObjKind::Time(rhs)) => Value::new_float(
                 ((lhs.clone() - rhs.clone()).num_nanoseconds().unwrap() as f64)
                     / 1000.0
                     / 1000.0
                     / 1000.0,
             ),
-            _ => {
-                globals.err_method_not_found(IdentId::_SUB);
-                return None;
-            }
+            _ => return call_user_operator(interp, globals, IdentId::_SUB, lhs, &[rhs]),
         },
-        _ => {
-            globals.err_method_not_found(IdentId::_SUB);
-            return None;
-        }
+        _ => return call_user_operator(interp, globals, IdentId::_SUB, lhs, &[rhs]),
     };
     Some(v)
 }
 
+///
+/// Division. Integer division by zero raises ZeroDivisionError, while float division by zero
+/// does not raise an error but returns Infinity or NaN.
+///
 pub(super) extern "C" fn div_values(
-    _interp: &mut Interp,
+    interp: &mut Interp,
     globals: &mut Globals,
     lhs: Value,
     rhs: Value,
@@ -98,7 +163,11 @@ pub(super) extern "C" fn div_values(
                 globals.err_divide_by_zero();
                 return None;
             }
-            Value::new_integer(lhs.div_floor(rhs))
+            match lhs.checked_div(rhs) {
+                Some(_) => Value::new_integer(lhs.div_floor(rhs)),
+                // i64::MIN / -1 overflows, and falls back to BigInt.
+                None => Value::new_bigint(BigInt::from(lhs).div_floor(&BigInt::from(rhs))),
+            }
         }
         (RV::Integer(lhs), RV::BigInt(rhs)) => {
             if rhs.is_zero() {
@@ -107,13 +176,7 @@ pub(super) extern "C" fn div_values(
             }
             Value::new_bigint(BigInt::from(lhs).div_floor(rhs))
         }
-        (RV::Integer(lhs), RV::Float(rhs)) => {
-            if rhs.is_zero() {
-                globals.err_divide_by_zero();
-                return None;
-            }
-            Value::new_float((lhs as f64).div(&rhs))
-        }
+        (RV::Integer(lhs), RV::Float(rhs)) => Value::new_float((lhs as f64).div(&rhs)),
         (RV::BigInt(lhs), RV::Integer(rhs)) => {
             if rhs.is_zero() {
                 globals.err_divide_by_zero();
@@ -128,47 +191,82 @@ pub(super) extern "C" fn div_values(
             }
             Value::new_bigint(lhs.div_floor(rhs))
         }
-        (RV::BigInt(lhs), RV::Float(rhs)) => {
+        (RV::BigInt(lhs), RV::Float(rhs)) => Value::new_float((lhs.to_f64().unwrap()).div(&rhs)),
+        (RV::Float(lhs), RV::Integer(rhs)) => Value::new_float(lhs.div(&(rhs as f64))),
+        (RV::Float(lhs), RV::BigInt(rhs)) => Value::new_float(lhs.div(&rhs.to_f64().unwrap())),
+        (RV::Float(lhs), RV::Float(rhs)) => Value::new_float(lhs.div(&rhs)),
+        _ => return call_user_operator(interp, globals, IdentId::_DIV, lhs, &[rhs]),
+    };
+    Some(v)
+}
+
+///
+/// Floored modulo, as in `Integer#%` and `Float#%`. The sign of the result follows *rhs*.
+///
+/// String receivers are formatted as in `String#%`.
+///
+pub(super) extern "C" fn rem_values(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    lhs: Value,
+    rhs: Value,
+) -> Option<Value> {
+    let v = match (lhs.unpack(), rhs.unpack()) {
+        (RV::Integer(lhs), RV::Integer(rhs)) => {
             if rhs.is_zero() {
                 globals.err_divide_by_zero();
                 return None;
             }
-            Value::new_float((lhs.to_f64().unwrap()).div(&rhs))
-        }
-        (RV::Float(lhs), RV::Integer(rhs)) => {
-            if rhs.is_zero() {
-                globals.err_divide_by_zero();
-                return None;
+            match lhs.checked_rem(rhs) {
+                Some(_) => Value::new_integer(lhs.mod_floor(&rhs)),
+                // i64::MIN % -1
+                None => Value::new_integer(0),
             }
-            Value::new_float(lhs.div(&(rhs as f64)))
         }
-        (RV::Float(lhs), RV::BigInt(rhs)) => {
+        (RV::Integer(lhs), RV::BigInt(rhs)) => Value::new_bigint(BigInt::from(lhs).mod_floor(rhs)),
+        (RV::BigInt(lhs), RV::Integer(rhs)) => {
             if rhs.is_zero() {
                 globals.err_divide_by_zero();
                 return None;
             }
-            Value::new_float(lhs.div(&rhs.to_f64().unwrap()))
+            Value::new_bigint(lhs.mod_floor(&BigInt::from(rhs)))
         }
-        (RV::Float(lhs), RV::Float(rhs)) => {
-            if rhs.is_zero() {
-                globals.err_divide_by_zero();
-                return None;
-            }
-            Value::new_float(lhs.div(&rhs))
+        (RV::BigInt(lhs), RV::BigInt(rhs)) => Value::new_bigint(lhs.mod_floor(rhs)),
+        (RV::Integer(lhs), RV::Float(rhs)) => Value::new_float(float_mod_floor(lhs as f64, rhs)),
+        (RV::BigInt(lhs), RV::Float(rhs)) => {
+            Value::new_float(float_mod_floor(lhs.to_f64().unwrap(), rhs))
+        }
+        (RV::Float(lhs), RV::Integer(rhs)) => Value::new_float(float_mod_floor(lhs, rhs as f64)),
+        (RV::Float(lhs), RV::BigInt(rhs)) => {
+            Value::new_float(float_mod_floor(lhs, rhs.to_f64().unwrap()))
         }
+        (RV::Float(lhs), RV::Float(rhs)) => Value::new_float(float_mod_floor(lhs, rhs)),
+        (RV::String(_), _) => return string_rem(globals, lhs, rhs),
         _ => {
-            globals.err_method_not_found(IdentId::_DIV);
+            if let Some(func_id) = find_user_operator(globals, lhs, IdentId::_REM) {
+                return interp.invoke_method(globals, func_id, lhs, &[rhs]);
+            }
+            globals.err_cant_coerce(rhs, lhs.class_id());
             return None;
         }
     };
     Some(v)
 }
 
+fn float_mod_floor(lhs: f64, rhs: f64) -> f64 {
+    let rem = lhs % rhs;
+    if rem != 0.0 && (rem < 0.0) != (rhs < 0.0) {
+        rem + rhs
+    } else {
+        rem
+    }
+}
+
 macro_rules! int_binop_values {
     (($op:ident, $op_str:expr)) => {
         paste! {
             pub(super) extern "C" fn [<$op _values>](
-                _interp: &mut Interp,
+                interp: &mut Interp,
                 globals: &mut Globals,
                 lhs: Value,
                 rhs: Value
@@ -178,10 +276,7 @@ macro_rules! int_binop_values {
                     (RV::Integer(lhs), RV::BigInt(rhs)) => Value::new_bigint(BigInt::from(lhs).$op(rhs)),
                     (RV::BigInt(lhs), RV::Integer(rhs)) => Value::new_bigint(lhs.$op(BigInt::from(rhs))),
                     (RV::BigInt(lhs), RV::BigInt(rhs)) => Value::new_bigint(lhs.$op(rhs)),
-                    _ => {
-                        globals.err_method_not_found($op_str);
-                    return None;
-                    }
+                    _ => return call_user_operator(interp, globals, $op_str, lhs, &[rhs]),
                 };
                 Some(v)
             }
@@ -200,7 +295,7 @@ int_binop_values!(
 );
 
 pub(super) extern "C" fn shr_values(
-    _interp: &mut Interp,
+    interp: &mut Interp,
     globals: &mut Globals,
     lhs: Value,
     rhs: Value,
@@ -208,28 +303,25 @@ pub(super) extern "C" fn shr_values(
     let v = match (lhs.unpack(), rhs.unpack()) {
         (RV::Integer(lhs), RV::Integer(rhs)) => {
             if rhs >= 0 {
-                int_shr(lhs, rhs as u64 as u32)
+                int_shr(lhs, rhs.unsigned_abs())
             } else {
-                int_shl(lhs, -rhs as u64 as u32)
+                int_shl(globals, lhs, rhs.unsigned_abs())?
             }
         }
         (RV::BigInt(lhs), RV::Integer(rhs)) => {
             if rhs >= 0 {
-                bigint_shr(lhs, rhs as u64 as u32)
+                bigint_shr(lhs, rhs.unsigned_abs())
             } else {
-                bigint_shl(lhs, -rhs as u64 as u32)
+                bigint_shl(globals, lhs, rhs.unsigned_abs())?
             }
         }
-        (_lhs, _rhs) => {
-            globals.err_method_not_found(IdentId::_SHR);
-            return None;
-        }
+        (_lhs, _rhs) => return call_user_operator(interp, globals, IdentId::_SHR, lhs, &[rhs]),
     };
     Some(v)
 }
 
 pub(super) extern "C" fn shl_values(
-    _interp: &mut Interp,
+    interp: &mut Interp,
     globals: &mut Globals,
     lhs: Value,
     rhs: Value,
@@ -237,52 +329,123 @@ pub(super) extern "C" fn shl_values(
     let v = match (lhs.unpack(), rhs.unpack()) {
         (RV::Integer(lhs), RV::Integer(rhs)) => {
             if rhs >= 0 {
-                int_shl(lhs, rhs as u64 as u32)
+                int_shl(globals, lhs, rhs.unsigned_abs())?
             } else {
-                int_shr(lhs, -rhs as u64 as u32)
+                int_shr(lhs, rhs.unsigned_abs())
             }
         }
         (RV::BigInt(lhs), RV::Integer(rhs)) => {
             if rhs >= 0 {
-                bigint_shl(lhs, rhs as u64 as u32)
+                bigint_shl(globals, lhs, rhs.unsigned_abs())?
             } else {
-                bigint_shr(lhs, -rhs as u64 as u32)
+                bigint_shr(lhs, rhs.unsigned_abs())
             }
         }
-        (_lhs, _rhs) => {
-            globals.err_method_not_found(IdentId::_SHL);
-            return None;
-        }
+        (RV::String(_), _) => return string_concat(globals, lhs, &[rhs]),
+        (_lhs, _rhs) => return call_user_operator(interp, globals, IdentId::_SHL, lhs, &[rhs]),
     };
     Some(v)
 }
 
-fn int_shr(lhs: i64, rhs: u32) -> Value {
-    Value::new_integer(
-        lhs.checked_shr(rhs)
-            .unwrap_or(if lhs >= 0 { 0 } else { -1 }),
-    )
+/// Shift widths of a left shift must not exceed this, as the result can not be allocated.
+const MAX_SHIFT_WIDTH: u64 = u32::MAX as u64;
+
+fn int_shr(lhs: i64, rhs: u64) -> Value {
+    // shifting by 64 bits or more is the same as by 63 bits.
+    Value::new_integer(lhs >> rhs.min(63))
 }
 
-fn int_shl(lhs: i64, rhs: u32) -> Value {
-    match lhs.checked_shl(rhs) {
-        Some(res) => Value::new_integer(res),
-        None => bigint_shl(&BigInt::from(lhs), rhs),
+fn int_shl(globals: &mut Globals, lhs: i64, rhs: u64) -> Option<Value> {
+    if rhs < 64 {
+        let res = lhs << rhs;
+        // no bits are lost.
+        if res >> rhs == lhs {
+            return Some(Value::new_integer(res));
+        }
     }
+    bigint_shl(globals, &BigInt::from(lhs), rhs)
 }
 
-fn bigint_shr(lhs: &BigInt, rhs: u32) -> Value {
+fn bigint_shr(lhs: &BigInt, rhs: u64) -> Value {
     Value::new_bigint(lhs.shr(rhs))
 }
 
-fn bigint_shl(lhs: &BigInt, rhs: u32) -> Value {
-    Value::new_bigint(lhs.shl(rhs))
+fn bigint_shl(globals: &mut Globals, lhs: &BigInt, rhs: u64) -> Option<Value> {
+    if lhs.is_zero() {
+        return Some(Value::new_integer(0));
+    }
+    if rhs > MAX_SHIFT_WIDTH {
+        globals.set_error(MonorubyErr::range("shift width too big".to_string()));
+        return None;
+    }
+    Some(Value::new_bigint(lhs.shl(rhs)))
+}
+
+///
+/// Compare *lhs* and *rhs*, which are not both numerics, by the operator method *name* of *lhs*.
+///
+/// Raise ArgumentError if *lhs* is a numeric, and NoMethodError if *lhs* has no such method.
+///
+fn cmp_user_operator(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    name: IdentId,
+    lhs: Value,
+    rhs: Value,
+) -> Option<Value> {
+    if let Some(func_id) = find_user_operator(globals, lhs, name) {
+        return interp.invoke_method(globals, func_id, lhs, &[rhs]);
+    }
+    match lhs.unpack() {
+        RV::Integer(_) | RV::BigInt(_) | RV::Float(_) => {
+            let msg = format!(
+                "comparison of {} with {} failed",
+                lhs.class_id().get_name(globals),
+                rhs.class_id().get_name(globals)
+            );
+            globals.err_argument(&msg);
+        }
+        _ => globals.err_method_not_found(name),
+    }
+    None
+}
+
+///
+/// Check the equality of *lhs* and *rhs*, which are not both numerics.
+///
+/// `==` defined in Ruby is used if any, and `!=` is the negation of `==` unless it is defined too.
+///
+fn eq_user_operator(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    lhs: Value,
+    rhs: Value,
+    ne: bool,
+) -> Option<Value> {
+    if ne {
+        if let Some(func_id) = find_user_operator(globals, lhs, IdentId::_NEQ) {
+            return interp.invoke_method(globals, func_id, lhs, &[rhs]);
+        }
+    }
+    let b = match find_user_operator(globals, lhs, IdentId::_EQ) {
+        Some(func_id) => interp
+            .invoke_method(globals, func_id, lhs, &[rhs])?
+            .to_bool(),
+        // Symbols are compared by their interned ids here.
+        None => Value::eq(lhs, rhs),
+    };
+    Some(Value::bool(b != ne))
 }
 
 macro_rules! cmp_values {
-    ($op:ident) => {
+    (($op:ident, $op_str:expr)) => {
         paste! {
-            pub(super) extern "C" fn [<cmp_ $op _values>](lhs: Value, rhs: Value) -> Value {
+            pub(super) extern "C" fn [<cmp_ $op _values>](
+                interp: &mut Interp,
+                globals: &mut Globals,
+                lhs: Value,
+                rhs: Value
+            ) -> Option<Value> {
                 let b = match (lhs.unpack(), rhs.unpack()) {
                     (RV::Integer(lhs), RV::Integer(rhs)) => lhs.$op(&rhs),
                     (RV::Integer(lhs), RV::BigInt(rhs)) => BigInt::from(lhs).$op(&rhs),
@@ -293,24 +456,34 @@ macro_rules! cmp_values {
                     (RV::Float(lhs), RV::Integer(rhs)) => lhs.$op(&(rhs as f64)),
                     (RV::Float(lhs), RV::BigInt(rhs)) => lhs.$op(&(rhs.to_f64().unwrap())),
                     (RV::Float(lhs), RV::Float(rhs)) => lhs.$op(&rhs),
-                    (lhs, rhs) => unreachable!("{:?} cmp {:?}", lhs, rhs),
+                    _ => return cmp_user_operator(interp, globals, $op_str, lhs, rhs),
                 };
-                Value::bool(b)
+                Some(Value::bool(b))
             }
         }
     };
-    ($op1:ident, $($op2:ident),+) => {
-        cmp_values!($op1);
-        cmp_values!($($op2),+);
+    (($op1:ident, $op_str1:expr), $(($op2:ident, $op_str2:expr)),+) => {
+        cmp_values!(($op1, $op_str1));
+        cmp_values!($(($op2, $op_str2)),+);
     };
 }
 
-cmp_values!(ge, gt, le, lt);
+cmp_values!(
+    (ge, IdentId::_GE),
+    (gt, IdentId::_GT),
+    (le, IdentId::_LE),
+    (lt, IdentId::_LT)
+);
 
 macro_rules! eq_values {
-    ($op:ident) => {
+    (($op:ident, $ne:expr)) => {
         paste! {
-            pub(super) extern "C" fn [<cmp_ $op _values>](lhs: Value, rhs: Value) -> Value {
+            pub(super) extern "C" fn [<cmp_ $op _values>](
+                interp: &mut Interp,
+                globals: &mut Globals,
+                lhs: Value,
+                rhs: Value
+            ) -> Option<Value> {
                 let b = match (lhs.unpack(), rhs.unpack()) {
                     (RV::Integer(lhs), RV::Integer(rhs)) => lhs.$op(&rhs),
                     (RV::Integer(lhs), RV::BigInt(rhs)) => BigInt::from(lhs).$op(&rhs),
@@ -322,44 +495,87 @@ macro_rules! eq_values {
                     (RV::Float(lhs), RV::BigInt(rhs)) => lhs.$op(&(rhs.to_f64().unwrap())),
                     (RV::Float(lhs), RV::Float(rhs)) => lhs.$op(&rhs),
                     (RV::Bool(lhs), RV::Bool(rhs)) => lhs.$op(&rhs),
-                    _ => unreachable!(),
+                    _ => return eq_user_operator(interp, globals, lhs, rhs, $ne),
                 };
-                Value::bool(b)
+                Some(Value::bool(b))
             }
         }
     };
-    ($op1:ident, $($op2:ident),+) => {
-        eq_values!($op1);
-        eq_values!($($op2),+);
+    (($op1:ident, $ne1:expr), $(($op2:ident, $ne2:expr)),+) => {
+        eq_values!(($op1, $ne1));
+        eq_values!($(($op2, $ne2)),+);
     };
 }
 
-eq_values!(eq, ne);
+eq_values!((eq, false), (ne, true));
 
 macro_rules! cmp_ri_values {
-    ($op:ident) => {
+    (($op:ident, $op_str:expr)) => {
         paste! {
-            pub(super) extern "C" fn [<cmp_ $op _ri_values>](lhs: Value, rhs: i64) -> Value {
+            pub(super) extern "C" fn [<cmp_ $op _ri_values>](
+                interp: &mut Interp,
+                globals: &mut Globals,
+                lhs: Value,
+                rhs: i64
+            ) -> Option<Value> {
                 let b = match lhs.unpack() {
                     RV::Integer(lhs) => lhs.$op(&rhs),
                     RV::BigInt(lhs) => lhs.$op(&BigInt::from(rhs)),
                     RV::Float(lhs) => lhs.$op(&(rhs as f64)),
-                    _ => unreachable!(),
+                    _ => {
+                        let rhs = Value::new_integer(rhs);
+                        return cmp_user_operator(interp, globals, $op_str, lhs, rhs);
+                    }
                 };
-                Value::bool(b)
+                Some(Value::bool(b))
             }
         }
     };
-    ($op1:ident, $($op2:ident),+) => {
-        cmp_ri_values!($op1);
-        cmp_ri_values!($($op2),+);
+    (($op1:ident, $op_str1:expr), $(($op2:ident, $op_str2:expr)),+) => {
+        cmp_ri_values!(($op1, $op_str1));
+        cmp_ri_values!($(($op2, $op_str2)),+);
+    };
+}
+
+cmp_ri_values!(
+    (ge, IdentId::_GE),
+    (gt, IdentId::_GT),
+    (le, IdentId::_LE),
+    (lt, IdentId::_LT)
+);
+
+macro_rules! eq_ri_values {
+    (($op:ident, $ne:expr)) => {
+        paste! {
+            pub(super) extern "C" fn [<cmp_ $op _ri_values>](
+                interp: &mut Interp,
+                globals: &mut Globals,
+                lhs: Value,
+                rhs: i64
+            ) -> Option<Value> {
+                let b = match lhs.unpack() {
+                    RV::Integer(lhs) => lhs.$op(&rhs),
+                    RV::BigInt(lhs) => lhs.$op(&BigInt::from(rhs)),
+                    RV::Float(lhs) => lhs.$op(&(rhs as f64)),
+                    _ => {
+                        let rhs = Value::new_integer(rhs);
+                        return eq_user_operator(interp, globals, lhs, rhs, $ne);
+                    }
+                };
+                Some(Value::bool(b))
+            }
+        }
+    };
+    (($op1:ident, $ne1:expr), $(($op2:ident, $ne2:expr)),+) => {
+        eq_ri_values!(($op1, $ne1));
+        eq_ri_values!($(($op2, $ne2)),+);
     };
 }
 
-cmp_ri_values!(eq, ne, ge, gt, le, lt);
+eq_ri_values!((eq, false), (ne, true));
 
 pub(super) extern "C" fn neg_value(
-    _interp: &mut Interp,
+    interp: &mut Interp,
     globals: &mut Globals,
     lhs: Value,
 ) -> Option<Value> {
@@ -370,10 +586,7 @@ pub(super) extern "C" fn neg_value(
         },
         RV::Float(lhs) => Value::new_float(-lhs),
         RV::BigInt(lhs) => Value::new_bigint(-lhs),
-        _ => {
-            globals.err_method_not_found(IdentId::_UMINUS);
-            return None;
-        }
+        _ => return call_user_operator(interp, globals, IdentId::_UMINUS, lhs, &[]),
     };
     Some(v)
 }
@@ -387,6 +600,506 @@ pub extern "C" fn concatenate_string(globals: &Globals, arg: *mut Value, len: us
     Value::new_string(res)
 }
 
+pub extern "C" fn make_array(arg: *mut Value, len: usize) -> Value {
+    let v = (0..len).map(|i| unsafe { *arg.sub(i) }).collect();
+    Value::new_array(v)
+}
+
+///
+/// Create a range object. Raise an error if *start* and *end* can not be compared.
+///
+pub extern "C" fn make_range(
+    _interp: &mut Interp,
+    globals: &mut Globals,
+    start: Value,
+    end: Value,
+    exclude_end: bool,
+) -> Option<Value> {
+    let valid = match (start.unpack(), end.unpack()) {
+        (RV::Nil, _) | (_, RV::Nil) => true,
+        (
+            RV::Integer(_) | RV::BigInt(_) | RV::Float(_),
+            RV::Integer(_) | RV::BigInt(_) | RV::Float(_),
+        ) => true,
+        (RV::String(_), RV::String(_)) => true,
+        _ => false,
+    };
+    if !valid {
+        globals.err_argument("bad value for range");
+        return None;
+    }
+    Some(Value::new_range(start, end, exclude_end))
+}
+
+pub extern "C" fn make_hash(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    arg: *mut Value,
+    len: usize,
+) -> Option<Value> {
+    let mut map = HashTable::default();
+    for i in 0..len {
+        let k = unsafe { *arg.sub(i * 2) };
+        let v = unsafe { *arg.sub(i * 2 + 1) };
+        hash_insert(interp, globals, &mut map, k, v)?;
+    }
+    Some(Value::new_hash(map))
+}
+
+///
+/// Convert *key* to a key of Hash objects.
+///
+/// If *key* has `hash` defined in Ruby, the value returned by it is used as the hash value.
+///
+pub(super) fn hash_key(interp: &mut Interp, globals: &mut Globals, key: Value) -> Option<HashKey> {
+    let name = globals.get_ident_id("hash");
+    match find_user_operator(globals, key, name) {
+        Some(func_id) => {
+            let hash = interp.invoke_method(globals, func_id, key, &[])?;
+            Some(HashKey::with_hash(key, hash.calc_hash()))
+        }
+        None => Some(HashKey::new(key)),
+    }
+}
+
+/// A key to search a Hash table, which is compared with the keys in the table by `eql?`
+/// defined in Ruby.
+struct EqlKey<'a> {
+    key: HashKey,
+    func_id: FuncId,
+    vm: std::cell::RefCell<(&'a mut Interp, &'a mut Globals)>,
+    failed: std::cell::Cell<bool>,
+}
+
+impl std::hash::Hash for EqlKey<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl indexmap::Equivalent<HashKey> for EqlKey<'_> {
+    fn equivalent(&self, key: &HashKey) -> bool {
+        if self.failed.get() || self.key.hash_value() != key.hash_value() {
+            return false;
+        }
+        if self.key == *key {
+            return true;
+        }
+        let (interp, globals) = &mut *self.vm.borrow_mut();
+        match interp.invoke_method(globals, self.func_id, self.key.0, &[key.0]) {
+            Some(res) => res.to_bool(),
+            None => {
+                self.failed.set(true);
+                false
+            }
+        }
+    }
+}
+
+///
+/// Find *key* in *map* and return its index.
+///
+/// If *key* has `eql?` defined in Ruby, it is used to compare keys with the same hash value.
+///
+pub(super) fn hash_index_of(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    map: &HashTable,
+    key: HashKey,
+) -> Option<Option<usize>> {
+    let name = globals.get_ident_id("eql?");
+    let func_id = match find_user_operator(globals, key.0, name) {
+        Some(func_id) => func_id,
+        None => return Some(map.get_index_of(&key)),
+    };
+    let key = EqlKey {
+        key,
+        func_id,
+        vm: std::cell::RefCell::new((interp, globals)),
+        failed: std::cell::Cell::new(false),
+    };
+    let index = map.get_index_of(&key);
+    match key.failed.get() {
+        true => None,
+        false => Some(index),
+    }
+}
+
+///
+/// Get the value of *key* in *map*, or None if not found.
+///
+pub(super) fn hash_get(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    map: &HashTable,
+    key: Value,
+) -> Option<Option<Value>> {
+    let key = hash_key(interp, globals, key)?;
+    let index = hash_index_of(interp, globals, map, key)?;
+    Some(index.map(|i| map[i]))
+}
+
+/// The names of the hidden instance variables of a Hash which hold the default value and
+/// the default block given to `Hash.new`. They are not valid names, so Ruby can not access them.
+pub(super) const HASH_DEFAULT_VAR: &str = "/default";
+pub(super) const HASH_DEFAULT_PROC_VAR: &str = "/default_proc";
+
+///
+/// Get the default value of the Hash *hash* for the missing *key*.
+///
+/// The default block is called with *hash* and *key* if given, or the default value is returned.
+///
+pub(super) fn hash_default(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    hash: Value,
+    key: Value,
+) -> Option<Value> {
+    let default_proc = globals.get_ident_id(HASH_DEFAULT_PROC_VAR);
+    if let Some(block) = hash.rvalue().get_var(default_proc) {
+        return interp.invoke_block(globals, block, &[hash, key]);
+    }
+    let default = globals.get_ident_id(HASH_DEFAULT_VAR);
+    Some(hash.rvalue().get_var(default).unwrap_or(Value::nil()))
+}
+
+///
+/// Set *val* to *key* in *map*.
+///
+/// The existing key is kept if found. Otherwise, an unfrozen String key is duplicated
+/// and frozen as Ruby does.
+///
+pub(super) fn hash_insert(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    map: &mut HashTable,
+    key: Value,
+    val: Value,
+) -> Option<()> {
+    let key = hash_key(interp, globals, key)?;
+    match hash_index_of(interp, globals, map, key)? {
+        Some(i) => map[i] = val,
+        None => {
+            let key = match key.0.unpack() {
+                RV::String(_) if !key.0.is_frozen() => key.map(|k| {
+                    let k = Value::dup(k);
+                    k.freeze();
+                    k
+                }),
+                _ => key,
+            };
+            map.insert(key, val);
+        }
+    }
+    Some(())
+}
+
+pub(super) extern "C" fn get_index(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    base: Value,
+    index: Value,
+) -> Option<Value> {
+    if let Some(map) = base.as_hash() {
+        return match hash_get(interp, globals, map, index)? {
+            Some(val) => Some(val),
+            None => hash_default(interp, globals, base, index),
+        };
+    }
+    if let RV::Symbol(id) = base.unpack() {
+        let name = globals.get_ident_name(id).to_string();
+        return index_chars(globals, &name, index);
+    }
+    let ary = match base.as_array() {
+        Some(ary) => ary,
+        None => {
+            let id = globals.get_ident_id("[]");
+            return call_user_operator(interp, globals, id, base, &[index]);
+        }
+    };
+    let i = match index.as_fixnum() {
+        Some(i) => i,
+        None => {
+            globals.err_no_implict_conv(index.class_id(), INTEGER_CLASS);
+            return None;
+        }
+    };
+    let i = if i < 0 { i + ary.len() as i64 } else { i };
+    if i < 0 {
+        return Some(Value::nil());
+    }
+    Some(ary.get(i as usize).cloned().unwrap_or(Value::nil()))
+}
+
+///
+/// Call the method *name* of *recv* with *args* from Rust code. (e.g. `inject(:+)`)
+///
+/// Binary operators are not defined as methods, so they are computed as in bytecode.
+///
+pub(super) fn send_values(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    recv: Value,
+    name: IdentId,
+    args: &[Value],
+) -> Option<Value> {
+    let op: extern "C" fn(&mut Interp, &mut Globals, Value, Value) -> Option<Value> =
+        match (globals.get_ident_name(name), args) {
+            ("+", [_]) => add_values,
+            ("-", [_]) => sub_values,
+            ("*", [_]) => mul_values,
+            ("/", [_]) => div_values,
+            ("%", [_]) => rem_values,
+            ("|", [_]) => bitor_values,
+            ("&", [_]) => bitand_values,
+            ("^", [_]) => bitxor_values,
+            ("==", [_]) => cmp_eq_values,
+            ("!=", [_]) => cmp_ne_values,
+            ("<", [_]) => cmp_lt_values,
+            ("<=", [_]) => cmp_le_values,
+            (">", [_]) => cmp_gt_values,
+            (">=", [_]) => cmp_ge_values,
+            _ => {
+                let func_id = globals.get_method(recv.class_id(), name, args.len())?;
+                return interp.invoke_method(globals, func_id, recv, args);
+            }
+        };
+    op(interp, globals, recv, args[0])
+}
+
+///
+/// Get the value in nested Arrays and Hashes of *base* by *keys*. (e.g. `Array#dig`)
+///
+/// Return nil as soon as a value on the way is nil.
+///
+pub(super) fn dig_values(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    mut base: Value,
+    keys: &[Value],
+) -> Option<Value> {
+    for key in keys {
+        if base.is_nil() {
+            break;
+        }
+        if base.as_array().is_none() && base.as_hash().is_none() {
+            let class_name = base.class_id().get_name(globals);
+            globals.set_error(MonorubyErr::typeerr(format!(
+                "{} does not have #dig method",
+                class_name
+            )));
+            return None;
+        }
+        base = get_index(interp, globals, base, *key)?;
+    }
+    Some(base)
+}
+
+///
+/// Get the characters of *s* at *index*, which is an Integer or a Range of Integers.
+///
+/// Return nil if *index* is out of range.
+///
+fn index_chars(globals: &mut Globals, s: &str, index: Value) -> Option<Value> {
+    let chars: Vec<char> = s.chars().collect();
+    let len = chars.len() as i64;
+    let normalize = |i: i64| if i < 0 { i + len } else { i };
+    let (start, end) = match index.unpack() {
+        RV::Integer(i) => {
+            let i = normalize(i);
+            if i < 0 || i >= len {
+                return Some(Value::nil());
+            }
+            (i, i + 1)
+        }
+        _ => match index.as_range() {
+            Some(range) => {
+                let start = match range.start.unpack() {
+                    RV::Nil => 0,
+                    RV::Integer(i) => normalize(i),
+                    _ => {
+                        globals.err_no_implict_conv(range.start.class_id(), INTEGER_CLASS);
+                        return None;
+                    }
+                };
+                let end = match range.end.unpack() {
+                    RV::Nil => len,
+                    RV::Integer(i) if range.exclude_end => normalize(i),
+                    RV::Integer(i) => normalize(i) + 1,
+                    _ => {
+                        globals.err_no_implict_conv(range.end.class_id(), INTEGER_CLASS);
+                        return None;
+                    }
+                };
+                if start < 0 || start > len {
+                    return Some(Value::nil());
+                }
+                (start, end.clamp(start, len))
+            }
+            None => {
+                globals.err_no_implict_conv(index.class_id(), INTEGER_CLASS);
+                return None;
+            }
+        },
+    };
+    let s: String = chars[start as usize..end as usize].iter().collect();
+    Some(Value::new_string(s.into_bytes()))
+}
+
+pub(super) extern "C" fn set_index(
+    interp: &mut Interp,
+    globals: &mut Globals,
+    base: Value,
+    index: Value,
+    src: Value,
+) -> Option<Value> {
+    if base.as_array().is_none() && base.as_hash().is_none() {
+        // As with other assignments, the value of `a[i] = v` is always v.
+        let id = globals.get_ident_id("[]=");
+        call_user_operator(interp, globals, id, base, &[index, src])?;
+        return Some(src);
+    }
+    if base.is_frozen() {
+        globals.err_frozen(base);
+        return None;
+    }
+    if let Some(map) = base.as_hash_mut() {
+        hash_insert(interp, globals, map, index, src)?;
+        return Some(src);
+    }
+    let ary = base.as_array_mut().unwrap();
+    let i = match index.as_fixnum() {
+        Some(i) => i,
+        None => {
+            globals.err_no_implict_conv(index.class_id(), INTEGER_CLASS);
+            return None;
+        }
+    };
+    let len = ary.len() as i64;
+    let i = if i < 0 {
+        if i + len < 0 {
+            globals.err_index_too_small(i, -len);
+            return None;
+        }
+        i + len
+    } else {
+        i
+    } as usize;
+    if i >= ary.len() {
+        ary.resize(i + 1, Value::nil());
+    }
+    ary[i] = src;
+    Some(src)
+}
+
+///
+/// Define a new class *name*, or reopen the class if it already exists,
+/// and make it the target of following method definitions.
+///
+pub extern "C" fn define_class(globals: &mut Globals, name: IdentId) -> Option<Value> {
+    let class_obj = match globals.get_constant(name) {
+        Some(val) => match val.unpack() {
+            RV::Object(rvalue) if matches!(rvalue.kind, ObjKind::Class(_)) => val,
+            _ => {
+                globals.err_not_class(name);
+                return None;
+            }
+        },
+        None => {
+            let name = globals.get_ident_name(name).to_string();
+            globals.define_class_under_obj(&name)
+        }
+    };
+    globals.push_class_context(class_obj.as_class());
+    Some(class_obj)
+}
+
+pub extern "C" fn end_class_def(globals: &mut Globals) {
+    globals.pop_class_context();
+}
+
+///
+/// Create a block object which calls *func_id* with *self_value* as `self`.
+///
+/// *outer_block* is the block of the current scope, which is called by `yield` in the block.
+///
+pub extern "C" fn make_block(func_id: FuncId, self_value: Value, outer_block: Value) -> Value {
+    Value::new_proc(func_id, self_value, outer_block)
+}
+
+///
+/// Expand *src* to *len* registers from *dst* for multiple assignment.
+///
+/// The elements of an Array are padded with nil, and other objects are regarded as an Array
+/// with only one element.
+///
+pub extern "C" fn expand_array(src: Value, dst: *mut Value, len: usize) {
+    let single = [src];
+    let elems = match src.as_array() {
+        Some(ary) => ary.as_slice(),
+        None => &single,
+    };
+    for i in 0..len {
+        unsafe { *dst.sub(i) = elems.get(i).cloned().unwrap_or(Value::nil()) };
+    }
+}
+
+///
+/// Set up the frame of a function with optional or rest parameters, which was called with *argc*
+/// arguments.
+///
+/// *reg0* points to `self` of the frame, and the registers follow it downward. Callers pass the
+/// block just after the given arguments, so move it to the hidden local next to the parameters,
+/// fill the missing parameters with nil, and store *argc* to the hidden local after the block.
+/// If *rest* is true, the arguments after *params_len* positional ones are collected into an
+/// Array, which is stored next to the positional parameters.
+///
+pub extern "C" fn init_opt_args(reg0: *mut Value, argc: usize, params_len: usize, rest: bool) {
+    unsafe {
+        let block = *reg0.sub(argc + 1);
+        let mut locals_len = params_len;
+        if rest {
+            let extra = (params_len + 1..=argc).map(|i| *reg0.sub(i)).collect();
+            locals_len += 1;
+            *reg0.sub(locals_len) = Value::new_array(extra);
+        }
+        for i in argc + 1..=params_len {
+            *reg0.sub(i) = Value::nil();
+        }
+        *reg0.sub(locals_len + 1) = block;
+        *reg0.sub(locals_len + 2) = Value::new_integer(argc as i64);
+    }
+}
+
+pub extern "C" fn get_global_var(globals: &Globals, name: IdentId) -> Value {
+    globals.get_gvar(name)
+}
+
+pub extern "C" fn set_global_var(globals: &mut Globals, name: IdentId, val: Value) {
+    globals.set_gvar(name, val);
+}
+
+pub extern "C" fn get_instance_var(self_val: Value, name: IdentId) -> Value {
+    match self_val.as_rvalue() {
+        Some(rvalue) => rvalue.get_var(name).unwrap_or(Value::nil()),
+        None => Value::nil(),
+    }
+}
+
+pub extern "C" fn set_instance_var(
+    globals: &mut Globals,
+    self_val: Value,
+    name: IdentId,
+    val: Value,
+) -> Option<Value> {
+    if self_val.is_frozen() {
+        globals.err_frozen(self_val);
+        return None;
+    }
+    self_val.rvalue_mut().set_var(name, val);
+    Some(val)
+}
+
 pub extern "C" fn vm_get_constant(
     _interp: &mut Interp,
     globals: &mut Globals,
@@ -397,20 +1110,14 @@ pub extern "C" fn vm_get_constant(
         name,
         prefix,
         toplevel,
+        ignore_undefined,
         cache: (cached_version, val),
     } = globals.func[site_id].clone();
-    assert_eq!(0, prefix.len());
     assert!(!toplevel);
     if cached_version == const_version {
         return val;
     };
-    let res = match globals.get_constant(name) {
-        Some(v) => Some(v),
-        None => {
-            globals.err_uninitialized_constant(name);
-            None
-        }
-    };
+    let res = find_constant(globals, &prefix, name, ignore_undefined);
     globals.func[site_id].cache = (const_version, res);
     res
 }
@@ -420,15 +1127,49 @@ pub extern "C" fn get_constant(
     globals: &mut Globals,
     site_id: ConstSiteId,
 ) -> Option<Value> {
-    let ConstSiteInfo { name, .. } = globals.func[site_id].clone();
-    let res = match globals.get_constant(name) {
+    let ConstSiteInfo {
+        name,
+        prefix,
+        ignore_undefined,
+        ..
+    } = globals.func[site_id].clone();
+    find_constant(globals, &prefix, name, ignore_undefined)
+}
+
+///
+/// Search the constant *name* qualified by *prefix* (e.g. `Float::INFINITY`).
+///
+fn find_constant(
+    globals: &mut Globals,
+    prefix: &[IdentId],
+    name: IdentId,
+    ignore_undefined: bool,
+) -> Option<Value> {
+    let mut parent = None;
+    for &id in prefix {
+        let val = match globals.get_scoped_constant(parent, id) {
+            Some(val) => val,
+            None => {
+                globals.err_uninitialized_constant(id);
+                return None;
+            }
+        };
+        match val.is_class() {
+            Some(class_id) => parent = Some(class_id),
+            None => {
+                globals.err_not_class(id);
+                return None;
+            }
+        }
+    }
+    match globals.get_scoped_constant(parent, name) {
         Some(v) => Some(v),
+        None if ignore_undefined => Some(Value::nil()),
         None => {
             globals.err_uninitialized_constant(name);
             None
         }
-    };
-    res
+    }
 }
 
 pub extern "C" fn set_constant(
@@ -40,6 +40,15 @@ struct CommandLineArgs {
     jit: bool,
     #[clap(short = 'W', default_value = "1")]
     warning: u8,
+    /// freeze all values assigned to constants, and all string literals.
+    #[clap(long)]
+    enable_frozen: bool,
+    /// run in the JIT, and check each compiled method against the VM on sampled arguments.
+    #[clap(long)]
+    jit_check: bool,
+    /// print the parsed AST, then exit.
+    #[clap(long)]
+    ast_dump: bool,
     /// File name.
     file: Option<String>,
 }
@@ -48,9 +57,36 @@ fn main() {
     use clap::Parser;
     let args = CommandLineArgs::parse();
 
+    if args.ast_dump {
+        let (code, path) = match args.exec.first() {
+            Some(code) => (code.clone(), std::path::PathBuf::from("REPL")),
+            None => {
+                let file_name = args.file.expect("a program file or -e is needed.");
+                let mut code = String::new();
+                File::open(&file_name)
+                    .unwrap()
+                    .read_to_string(&mut code)
+                    .unwrap();
+                (code, std::path::PathBuf::from(file_name))
+            }
+        };
+        match ast_dump(&code, &path) {
+            Ok(dump) => println!("{}", dump),
+            Err(err) => eprintln!("{:?}", err.kind),
+        }
+        return;
+    }
+
     if !args.exec.is_empty() {
         for code in args.exec {
-            exec(&code, args.jit, args.warning, std::path::Path::new("REPL"));
+            exec(
+                &code,
+                args.jit,
+                args.warning,
+                args.enable_frozen,
+                args.jit_check,
+                std::path::Path::new("REPL"),
+            );
         }
         return;
     }
@@ -64,6 +100,8 @@ fn main() {
                 &code,
                 args.jit,
                 args.warning,
+                args.enable_frozen,
+                args.jit_check,
                 &std::path::Path::new(&file_name),
             );
         }
@@ -93,8 +131,11 @@ fn main() {
     }
 }
 
-fn exec(code: &str, jit: bool, warning: u8, path: &std::path::Path) {
+fn exec(code: &str, jit: bool, warning: u8, frozen: bool, jit_check: bool, path: &std::path::Path) {
     let mut globals = Globals::new(warning);
+    if frozen {
+        globals.enable_frozen();
+    }
     match globals.compile_script(code.to_string(), path) {
         Ok(_) => {}
         Err(err) => {
@@ -104,7 +145,9 @@ fn exec(code: &str, jit: bool, warning: u8, path: &std::path::Path) {
         }
     };
 
-    match if !jit {
+    match if jit_check {
+        Interp::jit_check_toplevel(&mut globals)
+    } else if !jit {
         Interp::eval_toplevel(&mut globals)
     } else {
         Interp::jit_exec_toplevel(&mut globals)
@@ -114,12 +157,20 @@ fn exec(code: &str, jit: bool, warning: u8, path: &std::path::Path) {
             eprintln!("jit({:?}) {:?}", jit, val)
         }
         Err(err) => {
-            eprintln!("{:?}", err.kind);
+            eprintln!("{}", err.get_uncaught_message(&globals));
             err.show_loc();
         }
     };
 }
 
+/// Parse *code* and pretty-print the AST.
+fn ast_dump(code: &str, path: &std::path::Path) -> Result<String, MonorubyErr> {
+    match Parser::parse_program(code.to_string(), path.to_path_buf()) {
+        Ok(res) => Ok(format!("{:#?}", res.node)),
+        Err(err) => Err(MonorubyErr::parse(err)),
+    }
+}
+
 fn repl_exec(code: &str, jit_flag: bool, warning: u8) -> Result<(), MonorubyErr> {
     if !jit_flag {
         let mut globals = Globals::new(warning);
@@ -200,6 +251,82 @@ pub fn run_test(code: &str) {
     assert!(Value::eq(jit_val, ruby_res));
 }
 
+/// Run *code* on both the interpreter and the JIT, and check that both raise an error of
+/// the same variant as *expected*. The payload of *expected* is ignored.
+pub fn run_test_error(code: &str, expected: MonorubyErrKind) {
+    run_test_error_with(Globals::new(1), code, expected)
+}
+
+/// Same as `run_test_error`, but run *code* in *globals*. (e.g. with frozen constants)
+pub fn run_test_error_with(mut globals: Globals, code: &str, expected: MonorubyErrKind) {
+    #[cfg(debug_assertions)]
+    dbg!(code);
+    globals
+        .compile_script(code.to_string(), std::path::Path::new(""))
+        .unwrap();
+    for res in [
+        Interp::eval_toplevel(&mut globals.clone()),
+        Interp::jit_exec_toplevel(&mut globals),
+    ] {
+        let kind = res.unwrap_err().kind;
+        assert_eq!(
+            std::mem::discriminant(&expected),
+            std::mem::discriminant(&kind),
+            "unexpected error {:?}",
+            kind
+        );
+    }
+}
+
+/// Run *code* on both the interpreter and the JIT, and check that both results are inspected
+/// as *expected*. This is for behaviors which can not be compared with CRuby.
+pub fn run_test_inspect(code: &str, expected: &str) {
+    #[cfg(debug_assertions)]
+    dbg!(code);
+    let mut globals = Globals::new(1);
+    globals
+        .compile_script(code.to_string(), std::path::Path::new(""))
+        .unwrap();
+    for res in [
+        Interp::eval_toplevel(&mut globals.clone()),
+        Interp::jit_exec_toplevel(&mut globals),
+    ] {
+        assert_eq!(expected, globals.val_inspect(res.unwrap()));
+    }
+}
+
+/// Run *code* on both the interpreter and the JIT, and check that both raise an error
+/// which is reported as *expected* when it is not rescued.
+pub fn run_test_error_message(code: &str, expected: &str) {
+    #[cfg(debug_assertions)]
+    dbg!(code);
+    let mut globals = Globals::new(1);
+    globals
+        .compile_script(code.to_string(), std::path::Path::new(""))
+        .unwrap();
+    for res in [
+        Interp::eval_toplevel(&mut globals.clone()),
+        Interp::jit_exec_toplevel(&mut globals),
+    ] {
+        assert_eq!(expected, res.unwrap_err().get_uncaught_message(&globals));
+    }
+}
+
+/// Check that compiling *code* fails as an unsupported feature whose message contains *feature*.
+pub fn run_test_unsupported(code: &str, feature: &str) {
+    #[cfg(debug_assertions)]
+    dbg!(code);
+    let mut globals = Globals::new(1);
+    let kind = globals
+        .compile_script(code.to_string(), std::path::Path::new(""))
+        .unwrap_err()
+        .kind;
+    match &kind {
+        MonorubyErrKind::Unimplemented(msg) if msg.contains(feature) => {}
+        kind => panic!("unexpected error {:?}", kind),
+    }
+}
+
 fn run_ruby(code: &Vec<String>, globals: &mut Globals) -> Value {
     use std::process::Command;
     let code = code.join(";");
@@ -241,7 +368,7 @@ fn run_ruby(code: &Vec<String>, globals: &mut Globals) -> Value {
             } else if res == "nil" {
                 Value::nil()
             } else if res.starts_with('"') {
-                let s = res.trim_matches('"').to_string();
+                let s = unescape_string(&res[1..res.len() - 1]);
                 Value::new_string(s.into_bytes())
             } else if res.starts_with(':') {
                 let sym = globals.get_ident_id(res.trim_matches(':'));
@@ -263,6 +390,27 @@ fn run_ruby(code: &Vec<String>, globals: &mut Globals) -> Value {
     res
 }
 
+/// Restore a string printed by Ruby's `p` to its original contents.
+fn unescape_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => res.push('\n'),
+            Some('t') => res.push('\t'),
+            Some('r') => res.push('\r'),
+            Some('e') => res.push('\x1b'),
+            Some(c) => res.push(c),
+            None => res.push('\\'),
+        }
+    }
+    res
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -360,6 +508,108 @@ mod test {
         run_test("-4611686018400000001 - 27387904");
     }
 
+    #[test]
+    fn test_int_overflow() {
+        run_test("9223372036854775807 + 1");
+        run_test("-9223372036854775808 - 1");
+        run_test("4611686018427387903 * 4611686018427387903");
+        run_test("-4611686018427387904 * -1");
+        run_test("a = 4611686018427387903; a + a");
+        run_test("a = -4611686018427387904; a - 4611686018427387903");
+        run_test("4611686018427387904 - 1");
+        run_test("a = 4611686018427387900; i = 0; while i < 10; a += 1; i += 1; end; a");
+        run_test("a = -4611686018427387900; i = 0; while i < 10; a -= 1; i += 1; end; a");
+        run_test("a = 1; i = 0; while i < 100; a = a * 3; i += 1; end; a");
+        run_test("100000000000000000000 + 1.5");
+        run_test("1.5 + 100000000000000000000");
+        run_test("100000000000000000000 - 0.5");
+        run_test("0.5 - 100000000000000000000");
+        run_test("100000000000000000000 * 1.5");
+        run_test("1.5 * 100000000000000000000");
+    }
+
+    #[test]
+    fn test_bigint_literal() {
+        run_test("1000000000000000000000000000000");
+        run_test("-1000000000000000000000000000000");
+        run_test("1000000000000000000000000000000 == 10000000000 * 10000000000 * 10000000000");
+        run_test("-1000000000000000000000000000000 == 0 - 10000000000 * 10000000000 * 10000000000");
+        run_test("4611686018427387904 == 4611686018427387903 + 1");
+        run_test("9223372036854775807 == 4611686018427387904 * 2 - 1");
+        run_test("9223372036854775808 == 4611686018427387904 * 2");
+        run_test("18446744073709551616 - 18446744073709551615");
+        for (code, is_fixnum) in [
+            ("4611686018427387903", true),
+            ("-4611686018427387904", true),
+            ("4611686018427387904", false),
+            ("-4611686018427387905", false),
+            ("1000000000000000000000000000000", false),
+            ("18446744073709551616 - 18446744073709551615", true),
+        ] {
+            let mut globals = Globals::new(1);
+            globals
+                .compile_script(code.to_string(), std::path::Path::new(""))
+                .unwrap();
+            let val = Interp::eval_toplevel(&mut globals).unwrap();
+            assert_eq!(
+                is_fixnum,
+                matches!(val.unpack(), RV::Integer(_)),
+                "{}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_int_boundary() {
+        run_test("2147483647"); // max number of i32.
+        run_test("2147483648");
+        run_test("2147483647 + 1");
+        run_test("-2147483648"); // min number of i32.
+        run_test("-2147483649");
+        run_test("-2147483648 - 1");
+        run_test("a = 4611686018427387903; a - 4611686018427387903");
+        run_test("a = -4611686018427387904; a + 4611686018427387904");
+        run_test("a = 2147483648; a * 2147483648");
+    }
+
+    #[test]
+    fn test_int_literal_range() {
+        for lit in ["40000", "3_000_000", "2_000_000_000", "5_000_000_000"] {
+            run_test(&format!("a = 7; a + {}", lit));
+            run_test(&format!("a = 7; a - {}", lit));
+            run_test(&format!("a = 7; {} - a", lit));
+            run_test(&format!("a = 7; a * {}", lit));
+            run_test(&format!("a = 7; a < {}", lit));
+            run_test(&format!("a = 7; a == {}", lit));
+            run_test(&format!("a = {}; a == {}", lit, lit));
+            run_test(&format!("-{} + 1", lit));
+        }
+        run_test("a = 32767; a + 1");
+        run_test("a = -32768; a - 1");
+        run_test("a = 100; a + 32768");
+        run_test("a = 100; a - 32769");
+    }
+
+    #[test]
+    fn test_bitops() {
+        for lhs in [
+            "77",
+            "-77",
+            "100000000000000000000",
+            "-100000000000000000000",
+        ] {
+            for rhs in ["36", "-36", "200000000000000000000"] {
+                for op in ["|", "&", "^"] {
+                    run_test(&format!("a = {}; b = {}; a {} b", lhs, rhs, op));
+                }
+            }
+        }
+        for code in ["1.5 | 1", "1 & 1.5", "nil ^ 1"] {
+            run_test_error(code, MonorubyErrKind::MethodNotFound(IdentId::_ADD));
+        }
+    }
+
     #[test]
     fn test_shift() {
         for lhs in ["157"] {
@@ -372,6 +622,58 @@ mod test {
                 }
             }
         }
+        // bits must not be lost at the 64-bit boundary.
+        run_test("157 << 60");
+        run_test("a = -3; a << 62");
+        run_test("a = 3; a >> -61");
+        // widths beyond u32 must not be truncated.
+        run_test("a = 1; a >> 4294967296");
+        run_test("a = -1; a >> 4294967296");
+        run_test("a = 100000000000000000000; a >> 4294967296");
+        run_test("a = -100000000000000000000; a << -4294967296");
+        run_test("a = 0; a << 4294967296");
+        run_test_error(
+            "a = 1; a << 4294967296",
+            MonorubyErrKind::Range(String::new()),
+        );
+    }
+
+    #[test]
+    fn test_shift_bigint() {
+        for lhs in ["157", "100000000000000000000"] {
+            for rhs in ["0", "1", "54", "63", "64", "65"] {
+                for op in ["<<", ">>"] {
+                    run_test(&format!("a = {}; b = {}; a {} b", lhs, rhs, op));
+                    run_test(&format!("a = {}; b = -{}; a {} b", lhs, rhs, op));
+                    run_test(&format!("a = -{}; b = {}; a {} b", lhs, rhs, op));
+                    run_test(&format!("a = -{}; a {}= {}; a", lhs, op, rhs));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_logical_op() {
+        run_test("a = 1; b = 2; a && b");
+        run_test("a = nil; b = 2; a && b");
+        run_test("a = false; b = 2; a || b");
+        run_test("a = 1; b = 2; a || b");
+        run_test("1 && 2 || 3");
+        run_test("nil || false");
+        run_test("x = 5; true || (x = 1); x");
+        run_test("x = 5; false && (x = 1); x");
+        run_test("x = 5; nil || (x = 1); x");
+        run_test("x = 5; 3 && (x = 1); x");
+        run_test("a = nil; a ||= 3; a");
+        run_test("a = 4; a ||= 3; a");
+        run_test("a = 4; a &&= 3; a");
+        run_test("a = nil; a &&= 3; a");
+        run_test("a = 4; b = nil || a; b");
+        run_test("if 1 > 0 && 2 > 1 then 3 else 4 end");
+        run_test("if 1 > 2 || 2 > 3 then 3 else 4 end");
+        run_test("def f(x); x > 3 || x < -3; end; f(5)");
+        run_test("def f(x); x > 3 || x < -3; end; f(0)");
+        run_test("a = 0; i = 0; while i < 10 && a < 20; a = a + 3; i = i + 1; end; a");
     }
 
     #[test]
@@ -385,6 +687,13 @@ mod test {
         run_test("a=36; a|=77; a");
         run_test("a=36; a&=77; a");
         run_test("a=36; a^=77; a");
+        run_test("a=37; a%=7; a");
+        run_test("a=-37; a%=7; a");
+        run_test("a=37; a%=-7; a");
+        run_test("a=37.5; a%=7; a");
+        run_test("a=37; a%=-7.5; a");
+        run_test("a=100000000000000000000; a%=7; a");
+        run_test("a=-7; b=3; a % b");
     }
 
     #[test]
@@ -402,6 +711,53 @@ mod test {
         run_test("a=b=c=7; a+b+c");
     }
 
+    #[test]
+    fn test_mul_assign() {
+        run_test("a, b = [1, 2]; [a, b].inspect");
+        run_test("a, b, c = [1, 2]; [a, b, c].inspect");
+        run_test("a, b = [1, 2, 3]; [a, b].inspect");
+        run_test("a, b = 5; [a, b].inspect");
+        run_test("def f; [3, 4, 5]; end; a, b, c = f; a * b + c");
+        run_test("a, b, c = 1, 2; [a, b, c].inspect");
+        run_test("a, b = 1, 2, 3; [a, b].inspect");
+        run_test("a = 1, 2; a.inspect");
+        run_test("a = 1; b = 2; a, b = b, a; [a, b].inspect");
+        run_test("a = [1, 2]; a[0], a[1] = a[1], a[0]; a.inspect");
+        run_test("a = [1, 2]; b, a[0], a[1] = 3; [a, b].inspect");
+        run_test("a = [1, 2]; b, a[0], a[1] = 3, 4; [a, b].inspect");
+        run_test("@a, $b = [1, 2]; [@a, $b].inspect");
+        run_test("x = (a, b = 1, 2); x.inspect");
+        run_test("c = [1, 2]; x = (a, b = c); x.inspect");
+        run_test("def f; a, b = 1, 2; end; f.inspect");
+        run_test("a, *b = 1, 2, 3; [a, b].inspect");
+        run_test("*a, b = 1, 2, 3; [a, b].inspect");
+        run_test("a, *b, c = 1, 2, 3, 4, 5; [a, b, c].inspect");
+        run_test("a, *b, c = 1; [a, b, c].inspect");
+        run_test("a, *b, c, d = 1, 2; [a, b, c, d].inspect");
+        run_test("*a = 1, 2; a.inspect");
+    }
+
+    #[test]
+    fn test_multiple_return() {
+        run_test("def f(a); return a, a * 2; end; x, y = f(3); x * 10 + y");
+        run_test("def f; return 1, 2, 3; end; f.inspect");
+        run_test("def f(a); return a, 1 if a > 0; [0]; end; [f(5), f(-1)].inspect");
+        run_test("def f; return :a, nil; end; a, b, c = f; [a, b, c].inspect");
+        run_test(
+            r#"
+            def f
+              begin
+                return 1, 2
+              ensure
+                $e = 3
+              end
+            end
+            x, y = f
+            x + y + $e
+            "#,
+        );
+    }
+
     #[test]
     fn test_fibpoly() {
         run_test(
@@ -525,180 +881,1071 @@ mod test {
     }
 
     #[test]
-    fn test_while2() {
+    fn test_case() {
+        for x in ["1", "2", "3", "5", "2.0", "7.5", "100000000000000000000"] {
+            run_test(&format!(
+                r#"
+                x = {}
+                case x
+                when 1 then 10
+                when 2, 3 then 20
+                when 7.5 then 30
+                when 100000000000000000000 then 40
+                else 50
+                end
+                "#,
+                x
+            ));
+            run_test(&format!(
+                "x = {}; case x; when 1 then 10; when 2, 3 then 20; end",
+                x
+            ));
+        }
+        run_test("a = 3; y = case a + 1 when 4 then :four else :other end; y");
+        run_test(r#"case :foo when :bar then 1 when :foo then 2 end"#);
+        run_test(r#"case "foo" when "bar" then 1 when "foo" then 2 end"#);
+        run_test("a = 5; case when a < 3 then 1 when a < 7 then 2 else 3 end");
+        run_test("def f(x); case x when 0 then :zero when 1 then :one else :many end; end; f(1)");
         run_test(
             r#"
-            a=1
-            b=while a<2500 do
-                a=a+1
-                if a == 100 then break a end
+            i = 0; s = 0
+            while i < 10
+                case i % 3
+                when 0 then s = s + 1
+                when 1 then s = s + 10
+                else s = s + 100
+                end
+                i = i + 1
             end
-            b
+            s
             "#,
         );
     }
 
-    /*#[test]
-    fn test_for1() {
+    #[test]
+    fn test_array() {
+        run_test(r##""#{[1, 2, 3]}""##);
+        run_test(r#"[1, "a", :b, 2.5, nil, true].inspect"#);
+        run_test(r##"a = [[1, 2], [], [3 + 4, [5]]]; "#{a}""##);
+        run_test("[100000000000000000000, -1].inspect");
+        run_test("[].inspect");
+        run_test("[1, 2, 3] == [1, 2, 3]");
+        run_test("[1, [2, 3]] == [1, [2, 4]]");
+        run_test("[1, 2] != [1, 2, 3]");
+        run_test("def f(x); [x, x * 2]; end; f(3).inspect");
         run_test(
             r#"
-            a=1
-            b = for a in 0..300 do
+            i = 0; s = ""
+            while i < 3
+                s = s + [i, "x"].inspect
+                i = i + 1
             end
-            b # => 0..300
+            s
             "#,
         );
-    }*/
+    }
 
     #[test]
-    fn test_for2() {
+    fn test_array_index() {
+        run_test("a = [10, 20, 30]; a[-1]");
+        run_test("a = [10, 20, 30]; a[0] + a[1] + a[-3]");
+        run_test("a = [10, 20, 30]; a[3]");
+        run_test("a = [10, 20, 30]; a[-4]");
+        run_test("a = [10, 20, 30]; a[1] = 99; a.inspect");
+        run_test("a = [10, 20, 30]; a[-1] = 99");
+        run_test("a = [10, 20, 30]; a[-2] = :x; a.inspect");
+        run_test("a = []; a[3] = 1; a.inspect");
+        run_test("a = [[1, 2], [3, 4]]; a[1][0] = 7; a.inspect");
+        run_test("a = [1, 2]; a[0], a[1] = a[1], a[0]; a.inspect");
+        run_test("i = 0; a = [1, 2]; a[i] = (i = 1); a.inspect");
         run_test(
             r#"
-            b = for a in 0..300 do
-                if a == 77 then break a/7 end
+            a = [0, 0, 0, 0, 0]
+            i = 0
+            while i < 5
+                a[i] = i * i
+                i = i + 1
             end
-            b
+            a[2] + a[4]
             "#,
         );
+        run_test_error(
+            "a = [1, 2]; a[-3] = 0",
+            MonorubyErrKind::Index(String::new()),
+        );
+        run_test_error("a = [1, 2]; a[:x]", MonorubyErrKind::Type(String::new()));
+        run_test_error(
+            "a = 1; a[0]",
+            MonorubyErrKind::MethodNotFound(IdentId::_ADD),
+        );
+        run_test_error(
+            "a = [1].freeze; a[0] = 2",
+            MonorubyErrKind::Frozen(String::new()),
+        );
     }
 
     #[test]
-    fn test3() {
+    fn test_hash() {
+        run_test(r#"{ "a" => 1, :b => 2 }.inspect"#);
+        run_test(r##"h = { 1 => [2, 3], nil => 4.5 }; "#{h}""##);
+        run_test("{}.inspect");
+        run_test(r#"h = { "a" => 1, :b => 2 }; h["a"] + h[:b]"#);
+        run_test(r#"h = { "a" => 1 }; h["b"]"#);
+        run_test("h = { 1 => :one, 1.5 => :float }; h[1]");
+        run_test("h = { 100000000000000000000 => 7 }; h[100000000000000000000]");
+        run_test("h = { [1, 2] => 3 }; h[[1, 2]]");
+        run_test(r#"h = {}; h["x"] = 1; h[:y] = 2; h["x"] = 3; h.inspect"#);
+        run_test(r#"h = {}; h[:a] = 10"#);
+        run_test(r#"k = "key"; h = {}; h[k] = 1; k << "!"; h["key"]"#);
+        run_test(r#"{ :a => 1, :b => 2 } == { :b => 2, :a => 1 }"#);
         run_test(
             r#"
-        a=3;
-        if a==1;
-          3
-        else
-          4
-        end"#,
+            h = {}
+            i = 0
+            while i < 5
+                h[i] = i * i
+                i = i + 1
+            end
+            h[2] + h[4]
+            "#,
         );
     }
 
     #[test]
-    fn test4() {
+    fn test_instance_var() {
+        run_test("@a = 1; @a");
+        run_test("@b");
+        run_test("@a = 3; @a += 4; @a");
+        run_test("@a ||= 5; @a ||= 7; @a");
+        run_test("@a, @b = 1, 2; @a + @b");
+        run_test("self.inspect");
         run_test(
             r#"
-        def f(a,b)
-          a + b
-        end
-        f(5,7)
-        f(4,9)
-        "#,
+            def set; @count = 0; end
+            def inc; @count = @count + 1; end
+            set; inc; inc
+            @count
+            "#,
         );
-    }
-
-    #[test]
-    fn test5a() {
         run_test(
             r#"
-        def f(a)
-          a
-        end
-        f(7)
-        "#,
+            def get; @v; end
+            @v = :toplevel
+            i = 0; r = nil
+            while i < 3
+                r = get
+                i = i + 1
+            end
+            r
+            "#,
         );
     }
 
     #[test]
-    fn test5b() {
+    fn test_class_def() {
         run_test(
             r#"
-        def f(a); a; end
-        f(7)
-        "#,
+            class Foo
+              def bar; baz * 2; end
+              def baz; self.qux + 1; end
+              def qux; 20; end
+            end
+            Foo.new.bar
+            "#,
         );
-    }
-
-    #[test]
-    fn test5() {
         run_test(
             r#"
-        def f(a,b)
-          a + b
-        end
-        f(5.1, 7)
-        "#,
+            class Foo; def bar; 1; end; end
+            class Foo; def baz; bar + 2; end; end
+            f = Foo.new
+            f.bar + f.baz
+            "#,
+        );
+        run_test(
+            r#"
+            class Counter
+              def set; @count = 0; end
+              def inc; @count = @count + 1; end
+            end
+            c = Counter.new
+            d = Counter.new
+            c.set; d.set
+            c.inc; c.inc; d.inc
+            c.inc * 10 + d.inc
+            "#,
         );
+        run_test("class Foo; 100; end");
+        run_test("class Foo; end");
+        run_test("class Foo; self.inspect; end");
     }
 
     #[test]
-    fn test6() {
-        run_test("def f; return 5; end; f");
-        run_test("def f; return 5; end; f()");
-        run_test("def f; return 5; end; self.f");
-        run_test("def f; return 5; end; self.f()");
-        run_test("def f; a=5; return a; end; f");
-        run_test("def f; a=5; b=6; return a+b; end; f");
-        run_test("def foo; end");
+    fn test_class_def_error() {
+        run_test_error(
+            "class Foo; def bar; 1; end; end; 100.bar",
+            MonorubyErrKind::MethodNotFound(IdentId::_ADD),
+        );
+        run_test_error(
+            "Foo = 1; class Foo; end",
+            MonorubyErrKind::Type(String::new()),
+        );
     }
 
     #[test]
-    fn test7() {
+    fn test_yield() {
         run_test(
             r#"
-        def f
-          1
-        end
-        a = 0
-        i = 0
-        while i < 1000000
-          a = a + f()
-          if i == 500
-            def f
-              0
-            end
-          end
-          i = i + 1
-        end
-        a 
-        "#,
+            def each3; yield 1; yield 2; yield 3; end
+            $sum = 0
+            each3 { |x| $sum += x }
+            $sum
+            "#,
         );
-    }
-
-    #[test]
-    fn test8() {
         run_test(
             r#"
-        def f(x)
-          x * 2
-        end
-        def g(x)
-          x + 2
-        end
-        def h(x)
-          x * x
-        end
-        h g f 7
-        "#,
+            def sum3; yield(1) + yield(2) + yield(3); end
+            sum3 { |x| x * 10 }
+            "#,
         );
-    }
-
-    #[test]
-    fn test9() {
         run_test(
             r#"
-            puts 100
-        "#,
+            def pair; yield 3, 4; end
+            pair do |a, b| a * 10 + b end
+            "#,
         );
-    }
-
-    #[test]
-    fn test9a() {
         run_test(
             r#"
-            64.chr
-            a = 64.chr
-        "#,
+            def twice; yield; yield; end
+            def outer; twice { yield }; end
+            @count = 0
+            outer { @count += 1 }
+            @count
+            "#,
         );
-    }
-
-    #[test]
-    fn test10() {
         run_test(
             r#"
-            if nil then 2*5/3 else 5 end
-        "#,
+            class Foo
+              def each2; yield(1) + yield(2); end
+              def base; 100; end
+              def run; each2 { |x| x + base }; end
+            end
+            Foo.new.run
+            "#,
+        );
+        run_test(
+            r#"
+            def f(x); yield(x) * 2; end
+            i = 0; r = 0
+            while i < 10
+              r = r + f(i) { |y| y + 1 }
+              i = i + 1
+            end
+            r
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_yield_error() {
+        for code in [
+            "def f; yield; end; f",
+            "def g; yield; end; def f; g { yield }; end; f",
+        ] {
+            run_test_error(code, MonorubyErrKind::LocalJump(String::new()));
+        }
+    }
+
+    #[test]
+    fn test_optional_args() {
+        run_test("def f(a, b = 10); a + b; end; f(1)");
+        run_test("def f(a, b = 10); a + b; end; f(1, 2)");
+        run_test("def f(a = 5); a; end; f + f(7)");
+        run_test("def f(a = 5); a; end; f(nil).inspect");
+        run_test(
+            "def f(a, b = a * 2, c = b + 1); [a, b, c]; end; [f(1), f(1, 5), f(1, 5, 7)].inspect",
+        );
+        run_test("def g; 100; end; def f(a = g); a; end; f");
+        run_test("$c = 0; def f(a = ($c += 1)); a; end; f; f; f(10); $c");
+        run_test("def f(a, b = 10); yield(a + b); end; f(1) { |x| x * 2 } + f(1, 2) { |x| x }");
+        run_test("def f(a = 3); a.times { |i| $s += i }; end; $s = 0; f; f(5); $s");
+        run_test(
+            r#"
+            class C
+              def f(a, b = a + 1); @a = a; @b = b; end
+              def get; [@a, @b]; end
+            end
+            c = C.new; c.f(1); x = c.get; c.f(3, 4); [x, c.get].inspect
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_optional_args_error() {
+        for code in [
+            "def f(a, b = 10); end; f",
+            "def f(a, b = 10); end; f(1, 2, 3)",
+        ] {
+            run_test_error(code, MonorubyErrKind::WrongArguments(String::new()));
+        }
+    }
+
+    #[test]
+    fn test_rest_args() {
+        run_test("def f(*xs); xs; end; f(1, 2, 3).inspect");
+        run_test("def f(*xs); xs; end; f.inspect");
+        run_test("def f(a, *rest); [a, rest]; end; [f(1), f(1, 2), f(1, 2, 3, 4)].inspect");
+        run_test(
+            "def f(a, b = 5, *rest); [a, b, rest]; end; [f(1), f(1, 2), f(1, 2, 3), f(1, 2, 3, 4, 5)].inspect",
+        );
+        run_test("def f(a, *); a; end; f(1, 2, 3)");
+        run_test("def f(*xs); yield(xs); end; f(1, 2, 3) { |a| a.sum } + f { |a| a.sum }");
+        run_test(
+            r#"
+            def f(*xs); a = 100; b = 200; xs.sum + a + b; end
+            f(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20)
+            "#,
+        );
+        run_test(
+            r#"
+            class C
+              def f(*xs); @xs = xs; self; end
+              def get; @xs; end
+            end
+            $s = 0
+            100.times { |i| $s += C.new.f(i, i + 1, 7).get.sum }
+            $s
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_rest_args_error() {
+        for code in ["def f(a, *rest); end; f", "def f(a, b = 1, *rest); end; f"] {
+            run_test_error(code, MonorubyErrKind::WrongArguments(String::new()));
+        }
+    }
+
+    #[test]
+    fn test_keyword_args_unsupported() {
+        run_test_unsupported("def f(**opts); opts; end", "KWRest");
+        run_test_unsupported("def f(a:); a; end", "Keyword");
+        run_test_unsupported("def f(x); x; end; f(a: 1)", "keyword arguments");
+        run_test_unsupported(
+            "def f(x); x; end; h = { a: 1 }; f(**h)",
+            "hash splat arguments",
+        );
+    }
+
+    #[test]
+    fn test_arg_delegation_unsupported() {
+        run_test_unsupported("def f(...); end", "Delegate");
+        run_test_unsupported(
+            "def g(a); a; end; def f(...); g(...); end; f(1)",
+            "Delegate",
+        );
+    }
+
+    #[test]
+    fn test_operator_methods() {
+        let class_def = r#"
+            class Vec2
+              def set(x, y); @x = x; @y = y; self; end
+              def x; @x; end
+              def y; @y; end
+              def to_a; [@x, @y]; end
+              def +(o); Vec2.new.set(@x + o.x, @y + o.y); end
+              def -(o); Vec2.new.set(@x - o.x, @y - o.y); end
+              def *(k); Vec2.new.set(@x * k, @y * k); end
+              def -@; Vec2.new.set(-@x, -@y); end
+              def ==(o); @x == o.x && @y == o.y; end
+              def <(o); @x * @x + @y * @y < o.x * o.x + o.y * o.y; end
+              def <=>(o); @x - o.x; end
+              def [](i); i == 0 ? @x : @y; end
+              def []=(i, v); if i == 0 then @x = v else @y = v end; end
+            end
+            v1 = Vec2.new.set(1, 2)
+            v2 = Vec2.new.set(3, 5)
+        "#;
+        for code in [
+            "(v1 + v2).to_a",
+            "(v1 - v2 + v1).to_a",
+            "(v1 * 3).to_a",
+            "(-v1).to_a",
+            "[v1 == v2, v1 == Vec2.new.set(1, 2), v1 != v2, v1 != v1 * 1]",
+            "[v1 < v2, v2 < v1]",
+            "[v1 <=> v2, v2 <=> v1]",
+            "[v2[0], v2[1]]",
+            "a = (v1[1] = 7); [a, v1.to_a]",
+            "a = Object.new; [a == a, a == Object.new, a != a]",
+        ] {
+            run_test(&format!("{}{}.inspect", class_def, code));
+        }
+        run_test(
+            "class C; def ==(o); o == 1; end; end; c = C.new; [c == 1, c == 2, c != 1].inspect",
+        );
+        run_test("class C; def %(o); o * 2; end; end; C.new % 21");
+    }
+
+    #[test]
+    fn test_operator_methods_error() {
+        for code in [
+            "Object.new + 1",
+            "Object.new < 1",
+            "Object.new[0]",
+            "Object.new[0] = 1",
+        ] {
+            run_test_error(code, MonorubyErrKind::MethodNotFound(IdentId::_ADD));
+        }
+        for code in [r#"1 < "a""#, "1.5 >= nil"] {
+            run_test_error(code, MonorubyErrKind::Argument(String::new()));
+        }
+    }
+
+    #[test]
+    fn test_eval_script() {
+        let mut globals = Globals::new(1);
+        let val = globals.eval_script("a = [1, 2]; a.sum * 3").unwrap();
+        assert_eq!(Value::new_integer(9), val);
+        let val = globals
+            .jit_eval_script("def f(x); x * 2; end; f(21)")
+            .unwrap();
+        assert_eq!(Value::new_integer(42), val);
+        // methods and globals persist across scripts.
+        globals.eval_script("$g = f(5)").unwrap();
+        assert_eq!(
+            Value::new_integer(10),
+            globals.jit_eval_script("$g").unwrap()
+        );
+        match globals.eval_script("1 / 0").unwrap_err().kind {
+            MonorubyErrKind::DivideByZero => {}
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        match globals.jit_eval_script("1 +").unwrap_err().kind {
+            MonorubyErrKind::Syntax(_) => {}
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_unimplemented_loc() {
+        let mut globals = Globals::new(1);
+        let err = globals
+            .compile_script("a = 1\nb = 3 ** 2".to_string(), std::path::Path::new(""))
+            .unwrap_err();
+        match &err.kind {
+            MonorubyErrKind::Unimplemented(_) => {}
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        assert_eq!(Some((2, 5)), err.get_line_col());
+    }
+
+    #[test]
+    fn test_global_var() {
+        run_test("$a = 1; $a");
+        run_test("$undefined");
+        run_test("$a = 3; $a += 4; $a");
+        run_test("$a ||= 5; $a ||= 7; $a");
+        run_test("$a, $b = :x, 2.5; $b");
+        run_test(
+            r#"
+            def inc; $count = $count + 1; end
+            $count = 0
+            i = 0
+            while i < 10
+                inc
+                i = i + 1
+            end
+            $count
+            "#,
+        );
+        run_test("def set; $v = [1, 2]; end; set; $v.inspect");
+    }
+
+    #[test]
+    fn test_ternary() {
+        run_test("a = 3; b = 5; x = a > b ? a : b; x");
+        run_test("a = 7; b = 5; x = a > b ? a : b; x");
+        run_test("a = 7; b = 5; a > b ? a : b");
+        run_test("a = -1; b = 1; x = (a>0 ? 1 : (b>0 ? 2 : 3)); x");
+        run_test("a = -1; b = -1; x = (a>0 ? 1 : (b>0 ? 2 : 3)); x");
+        run_test("a = 1; b = -1; a>0 ? 1 : b>0 ? 2 : 3");
+        run_test("a = 1; x = a > 0 ? nil : 1; x");
+        run_test("a = 1; x = y = a > 0 ? 10 : 20; x + y");
+        run_test("a = 5; x = if a > 3 then a * 2 end; x");
+        run_test("a = 1; x = if a > 3 then a * 2 end; x");
+        run_test("def max(a, b); a > b ? a : b; end; max(3, 8) + max(9, 2)");
+        run_test("i = 0; s = 0; while i < 10; s = s + (i % 2 == 0 ? i : 0); i = i + 1; end; s");
+    }
+
+    #[test]
+    fn test_cond_receiver() {
+        run_test("a = 3; (if a > 2 then [1, 2] else [3] end).sum");
+        run_test("a = 1; (if a > 2 then [1, 2] else [3] end).sum");
+        run_test("a = 1; (if a > 2 then [1, 2] end).inspect");
+        run_test("a = 1; (unless a > 2 then -5 else 5 end).abs");
+        run_test("a = 3; (a > 2 ? [1, 2] : [3]).sum + 10");
+        run_test("a = 2; (case a when 1 then [1] when 2 then [2, 2] else [] end).sum");
+        run_test("a = 3; (case a when 1 then [1] when 2 then [2, 2] else [] end).sum");
+        run_test("a = 3; x = (if a > 2 then [a, 1] else [0] end).sum; x");
+        run_test(
+            r#"
+            def f(a)
+              (if a > 0 then [a, a] else [0] end).sum
+            end
+            f(3) + f(-1)
+            "#,
+        );
+        run_test(
+            r#"
+            i = 0; s = 0
+            while i < 6
+              i = i + 1
+              s = s + (if i == 3 then next else [i, 1] end).sum
+              x = (if i == 5 then break else i end).abs
+            end
+            [i, s].inspect
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_until() {
+        run_test("i=0; until i>=5; i=i+1; end; i");
+        run_test("i=10; until i>=5; i=i+1; end; i");
+        run_test("i=0; until i>=5 do i=i+1 end");
+        run_test("i=0; i=i+3 until i>20; i");
+        run_test(
+            r#"
+            a=1
+            b=until a>=2500 do
+                a=a+1
+                if a == 100 then break a end
+            end
+            b
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_unless() {
+        run_test("a = 5; unless a > 3 then 1 else 2 end");
+        run_test("a = 5; unless a < 3 then 1 else 2 end");
+        run_test("a = 5; unless a < 3; 1; end");
+        run_test("a = 5; unless a > 3; 1; end");
+        run_test("x = 7; x = 3 unless false; x");
+        run_test("x = 7; x = 3 unless true; x");
+        run_test("x = (3 unless false); x");
+        run_test("x = (3 unless true); x");
+        run_test("def f(a); unless a == 0 then 10 / a else 0 end; end; f(0) + f(5)");
+        run_test("def f(a); return 0 unless a > 0; a; end; f(-1) + f(5)");
+        run_test("i = 0; while i < 10; i = i + 1 unless i == 5; i = i + 2 unless i != 5; end; i");
+    }
+
+    #[test]
+    fn test_while2() {
+        run_test(
+            r#"
+            a=1
+            b=while a<2500 do
+                a=a+1
+                if a == 100 then break a end
+            end
+            b
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_next() {
+        run_test("i=0;s=0;while i<5;i=i+1;next if i==3;s=s+i;end;s");
+        run_test("i=0;s=0;until i>=5;i=i+1;next s=s+100 if i==3;s=s+i;end;s");
+        run_test("s=0;for i in 0..9;next if i%2==0;s=s+i;end;s");
+        run_test(
+            r#"
+            s=0
+            i=0
+            while i<10
+              i=i+1
+              j=0
+              while j<10
+                j=j+1
+                next if j<=i
+                break if j==8
+                s=s+j
+              end
+              next if i==5
+              s=s+i*100
+            end
+            s
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_nested_loops() {
+        run_test(
+            r#"
+            s=0
+            i=0
+            while i<10
+              i=i+1
+              j=0
+              r=while true
+                j=j+1
+                break j*100 if j==i
+                next if j%2==0
+                s=s+j
+              end
+              s=s+r
+              break if s>5000
+            end
+            [i, s].inspect
+            "#,
+        );
+        run_test(
+            r#"
+            s=0
+            r=for i in 0..9
+              next if i==2
+              for j in 0..9
+                break if j>i
+                s=s+j
+              end
+              break i*10 if i==7
+              s=s+100
+            end
+            [r, s].inspect
+            "#,
+        );
+        run_test(
+            r#"
+            i=0
+            a=until i>=10
+              i=i+1
+              for j in 1..3
+                next
+              end
+              break i if i==4
+            end
+            a
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_jump_outside_loop() {
+        for code in ["break", "next", "def f; break 1; end", "def f; next; end"] {
+            let mut globals = Globals::new(1);
+            match globals
+                .compile_script(code.to_string(), std::path::Path::new(""))
+                .unwrap_err()
+                .kind
+            {
+                MonorubyErrKind::Syntax2(_) | MonorubyErrKind::Syntax(_) => {}
+                kind => panic!("unexpected error {:?}", kind),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rescue() {
+        run_test("begin; 1 / 0; rescue; 5; end");
+        run_test("begin; 1 / 0; rescue => e; e.message; end");
+        run_test("begin; 3; rescue; 5; end");
+        run_test("begin; 3; rescue; 5; else; 7; end");
+        run_test("begin; 1 / 0; rescue; 5; else; 7; end");
+        run_test("x = 1; begin; x = 2; 1 / 0; x = 3; rescue; x = x * 10; end; x");
+        run_test("def f(x); begin; 10 / x; rescue => e; -1; end; end; [f(2), f(0)].inspect");
+        run_test("def g; 1 + nil; end; begin; g; rescue => e; e.class; end");
+        run_test("begin; begin; 1 / 0; rescue; 1 + nil; end; rescue => e; e.class; end");
+        run_test("begin; [1, 0].flat_map { |x| [1 / x] }; rescue => e; e.message; end");
+        run_test(
+            r#"
+            s = 0
+            i = 0
+            while i < 5
+              i += 1
+              begin
+                s += 10 / (i - 3)
+              rescue
+                s += 100
+              end
+            end
+            s
+            "#,
+        );
+        run_test(
+            r#"
+            def f(x)
+              begin
+                [1, 2].fetch_nothing if x == 0
+                x * 2
+              rescue => err
+                $e = err
+                "rescued"
+              end
+            end
+            [f(0), f(1), $e.class].inspect
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_rescue_error() {
+        run_test_error(
+            "begin; 1 / 0; rescue; 1 + nil; end",
+            MonorubyErrKind::Type(String::new()),
+        );
+        run_test_error(
+            "begin; 1; rescue; 2; else; 1 / 0; end",
+            MonorubyErrKind::DivideByZero,
+        );
+    }
+
+    #[test]
+    fn test_ensure() {
+        run_test(
+            r#"
+            $log = ""
+            def f(x)
+              begin
+                $log += "b"
+                10 / x
+              rescue
+                $log += "r"
+                -1
+              ensure
+                $log += "e"
+              end
+            end
+            [f(2), f(0), $log].inspect
+            "#,
+        );
+        run_test(r#"$log = ""; x = begin; 1; ensure; $log += "e"; 2; end; [x, $log].inspect"#);
+        run_test(
+            r#"
+            $log = ""
+            begin
+              begin; 1 / 0; ensure; $log += "e"; end
+            rescue => e
+              $log += e.class.inspect
+            end
+            $log
+            "#,
+        );
+        run_test(
+            r#"
+            $log = ""
+            def f
+              begin
+                return 1
+              ensure
+                $log += "e"
+              end
+              2
+            end
+            [f, $log].inspect
+            "#,
+        );
+        run_test(
+            r#"
+            $log = ""
+            i = 0
+            while i < 4
+              i += 1
+              begin
+                next if i == 1
+                break if i == 3
+                $log += "b"
+              ensure
+                $log += "e"
+              end
+            end
+            [i, $log].inspect
+            "#,
+        );
+        run_test(
+            r#"
+            $log = ""
+            i = 0
+            while i < 2
+              i += 1
+              begin
+                begin; 1 / 0; rescue; $log += "r"; ensure; $log += "e1"; end
+                break
+              ensure
+                $log += "e2"
+              end
+            end
+            $log
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_ensure_error() {
+        for (code, expected) in [
+            (
+                r#"$log = ""; begin; 1 / 0; ensure; $log += "e"; end"#,
+                MonorubyErrKind::DivideByZero,
+            ),
+            (
+                r#"$log = ""; begin; raise Exception; rescue; 1; ensure; $log += "e"; end"#,
+                MonorubyErrKind::Exception(EXCEPTION_CLASS, String::new()),
+            ),
+        ] {
+            run_test_error(code, expected);
+            for jit in [false, true] {
+                let mut globals = Globals::new(1);
+                globals
+                    .compile_script(code.to_string(), std::path::Path::new(""))
+                    .unwrap();
+                let res = if jit {
+                    Interp::jit_exec_toplevel(&mut globals)
+                } else {
+                    Interp::eval_toplevel(&mut globals)
+                };
+                assert!(res.is_err());
+                // the `ensure` clause has been run before the error is propagated.
+                let name = globals.get_ident_id("$log");
+                let log = globals.get_gvar(name);
+                assert_eq!(r#""e""#, globals.val_inspect(log));
+            }
+        }
+    }
+
+    #[test]
+    fn test_flip_flop() {
+        run_test(
+            r#"
+            a = 0; i = 0
+            while i < 10
+              if (i == 3)..(i == 6) then a = a + i end
+              i = i + 1
+            end
+            a
+            "#,
+        );
+        run_test(
+            r#"
+            a = 0; i = 0
+            while i < 10
+              if (i == 2)..(i >= 2) then a = a + i end
+              i = i + 1
+            end
+            a
+            "#,
+        );
+        run_test(
+            r#"
+            a = 0; i = 0
+            while i < 10
+              if (i == 2)...(i >= 2) then a = a + i end
+              i = i + 1
+            end
+            a
+            "#,
+        );
+        run_test(
+            r#"
+            a = 0; i = 0
+            while i < 10
+              a = a + i if (i == 4)..(i == 5)
+              i = i + 1
+            end
+            a
+            "#,
+        );
+    }
+
+    /*#[test]
+    fn test_for1() {
+        run_test(
+            r#"
+            a=1
+            b = for a in 0..300 do
+            end
+            b # => 0..300
+            "#,
+        );
+    }*/
+
+    #[test]
+    fn test_for2() {
+        run_test(
+            r#"
+            b = for a in 0..300 do
+                if a == 77 then break a/7 end
+            end
+            b
+            "#,
+        );
+    }
+
+    #[test]
+    fn test3() {
+        run_test(
+            r#"
+        a=3;
+        if a==1;
+          3
+        else
+          4
+        end"#,
+        );
+    }
+
+    #[test]
+    fn test4() {
+        run_test(
+            r#"
+        def f(a,b)
+          a + b
+        end
+        f(5,7)
+        f(4,9)
+        "#,
+        );
+    }
+
+    #[test]
+    fn test5a() {
+        run_test(
+            r#"
+        def f(a)
+          a
+        end
+        f(7)
+        "#,
+        );
+    }
+
+    #[test]
+    fn test5b() {
+        run_test(
+            r#"
+        def f(a); a; end
+        f(7)
+        "#,
+        );
+    }
+
+    #[test]
+    fn test5() {
+        run_test(
+            r#"
+        def f(a,b)
+          a + b
+        end
+        f(5.1, 7)
+        "#,
+        );
+    }
+
+    #[test]
+    fn test6() {
+        run_test("def f; return 5; end; f");
+        run_test("def f; return 5; end; f()");
+        run_test("def f; return 5; end; self.f");
+        run_test("def f; return 5; end; self.f()");
+        run_test("def f; a=5; return a; end; f");
+        run_test("def f; a=5; b=6; return a+b; end; f");
+        run_test("def foo; end");
+    }
+
+    #[test]
+    fn test7() {
+        run_test(
+            r#"
+        def f
+          1
+        end
+        a = 0
+        i = 0
+        while i < 1000000
+          a = a + f()
+          if i == 500
+            def f
+              0
+            end
+          end
+          i = i + 1
+        end
+        a 
+        "#,
+        );
+    }
+
+    #[test]
+    fn test8() {
+        run_test(
+            r#"
+        def f(x)
+          x * 2
+        end
+        def g(x)
+          x + 2
+        end
+        def h(x)
+          x * x
+        end
+        h g f 7
+        "#,
+        );
+    }
+
+    #[test]
+    fn test_call_forms() {
+        let def = r#"
+        def f(a, b)
+          a * 10 + b
+        end
+        def g(x)
+          x + 1
+        end
+        x = 3
+        "#;
+        for call in [
+            "f(1, 2,)",
+            "f(1,\n2)",
+            "f x, x + 1",
+            "f((x), x * 2)",
+            "f g(x), g x",
+            "f(g(x), 5) - 1",
+            "g f 1, 2",
+            "g -1",
+            "g(-x)",
+            "puts 1, 2",
+        ] {
+            run_test(&format!("{}{}", def, call));
+        }
+    }
+
+    #[test]
+    fn test9() {
+        run_test(
+            r#"
+            puts 100
+        "#,
+        );
+    }
+
+    #[test]
+    fn test9a() {
+        run_test(
+            r#"
+            64.chr
+            a = 64.chr
+        "#,
+        );
+    }
+
+    #[test]
+    fn test10() {
+        run_test(
+            r#"
+            if nil then 2*5/3 else 5 end
+        "#,
         );
     }
 
@@ -712,6 +1959,13 @@ mod test {
             Const
         "#,
         );
+        run_test("Const ||= 5; Const");
+        run_test("Const = 4; Const ||= 5; Const");
+        run_test("Const = nil; Const ||= 5; Const");
+        run_test("Const = 4; Const &&= 5; Const");
+        run_test("Const = false; Const &&= 5; Const");
+        run_test("Const = 4; Const ||= 5");
+        run_test("Const ||= 5");
     }
 
     #[test]
@@ -726,6 +1980,183 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_heredoc() {
+        run_test(
+            r#"
+        a = <<-EOS
+    foo
+      bar
+    EOS
+        a
+        "#,
+        );
+        run_test(
+            r#"
+        a = <<~EOS
+    foo
+      bar
+    EOS
+        a
+        "#,
+        );
+        run_test(
+            r#"
+        a = <<-DASH + <<~SQUIGGLY
+      dash
+      DASH
+      squiggly
+      SQUIGGLY
+        a
+        "#,
+        );
+    }
+
+    #[test]
+    fn test_data_section() {
+        let code = "x = DATA.read\n__END__\nhello\n__END__\nworld\n";
+        for jit in [false, true] {
+            let mut globals = Globals::new(1);
+            globals
+                .compile_script(code.to_string(), std::path::Path::new(""))
+                .unwrap();
+            let val = if jit {
+                Interp::jit_exec_toplevel(&mut globals)
+            } else {
+                Interp::eval_toplevel(&mut globals)
+            }
+            .unwrap();
+            let expected = Value::new_string(b"hello\n__END__\nworld\n".to_vec());
+            assert!(Value::eq(val, expected));
+        }
+        run_test_error(
+            "File.new.read",
+            MonorubyErrKind::Unimplemented(String::new()),
+        );
+    }
+
+    #[test]
+    fn test_begin_end_blocks() {
+        for code in ["BEGIN { $a = 1 }; 2", "1\nEND { puts 1 }\n2", "  END{ 1 }"] {
+            let mut globals = Globals::new(1);
+            let kind = globals
+                .compile_script(code.to_string(), std::path::Path::new(""))
+                .unwrap_err()
+                .kind;
+            assert!(
+                matches!(&kind, MonorubyErrKind::Unimplemented(msg) if msg.contains("blocks")),
+                "unexpected error {:?}",
+                kind
+            );
+        }
+        // constants named like the keywords are not affected.
+        run_test("ENDING = 1; ENDING");
+    }
+
+    #[test]
+    fn test_ast_dump() {
+        let dump = ast_dump("a = 100; a + 7", std::path::Path::new("")).unwrap();
+        assert!(dump.contains("100"));
+        assert!(dump.contains("Add"));
+        assert!(ast_dump("a = (", std::path::Path::new("")).is_err());
+    }
+
+    #[test]
+    fn test_jit_check() {
+        let code = r#"
+            def f(x, y); x % y; end
+            def g(x)
+              if x < 1
+                x
+              else
+                -x
+              end
+            end
+            [f(1.5, 0.0), g(3)]
+            "#;
+        let mut globals = Globals::new(1);
+        globals
+            .compile_script(code.to_string(), std::path::Path::new(""))
+            .unwrap();
+        let val = Interp::jit_check_toplevel(&mut globals).unwrap();
+        assert_eq!("[NaN, -3]", globals.val_inspect(val));
+
+        // inject a wrong result of the JIT.
+        fn off_by_one(res: Option<Value>) -> Option<Value> {
+            res.map(|val| match val.as_fixnum() {
+                Some(i) => Value::new_integer(i + 1),
+                None => val,
+            })
+        }
+        let mut globals = Globals::new(1);
+        globals
+            .compile_script(
+                "def f(x); x + 4; end; f(3)".to_string(),
+                std::path::Path::new(""),
+            )
+            .unwrap();
+        let err = Interp::jit_check_toplevel_with(&mut globals, off_by_one).unwrap_err();
+        assert_eq!(
+            MonorubyErrKind::JitMismatch(
+                "results of VM and JIT differ in f(0). vm:4 jit:5".to_string()
+            ),
+            err.kind
+        );
+
+        // methods with loops are not run on sampled arguments.
+        let mut globals = Globals::new(1);
+        globals
+            .compile_script(
+                "def f(x); while x > 0; x -= 1; end; x; end; f(3)".to_string(),
+                std::path::Path::new(""),
+            )
+            .unwrap();
+        let val = Interp::jit_check_toplevel_with(&mut globals, off_by_one).unwrap();
+        assert_eq!(Some(0), val.as_fixnum());
+    }
+
+    #[test]
+    fn test_int_div() {
+        run_test("-9223372036854775808 / -1");
+        run_test("-9223372036854775808 % -1");
+        run_test("-7 / 2");
+        run_test("7 / -2");
+        run_test("-7 % 2");
+    }
+
+    #[test]
+    fn test_divide_by_zero() {
+        for code in [
+            "5 / 0",
+            "5 % 0",
+            "a = 0; 5 / a",
+            "100000000000000000000 / 0",
+            "100000000000000000000 % 0",
+            "-9223372036854775808 / 0",
+        ] {
+            run_test_error(code, MonorubyErrKind::DivideByZero);
+        }
+    }
+
+    #[test]
+    fn test_symbol_eq() {
+        run_test(":foo == :foo");
+        run_test(":foo != :foo");
+        run_test(":foo == :bar");
+        run_test(":foo != :bar");
+        run_test(r#":foo == "foo""#);
+        run_test(":foo == 1");
+        run_test(":foo != 1");
+        run_test("nil == nil");
+        run_test("nil == false");
+        run_test(r#""foo" == "foo""#);
+        run_test("def f; :foo; end; a = :foo; a == f");
+        run_test(r#"a = :foo; a == eval(":foo")"#);
+        run_test(r#"a = :foo; a.equal?(eval(":foo"))"#);
+        run_test(r#"a = :foo; a.equal?("foo".to_sym)"#);
+        run_test(r#"a = :foo; a.equal?("fo".concat("o").intern)"#);
+    }
+
     #[test]
     fn test_symbol() {
         run_test(
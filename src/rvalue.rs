@@ -2,6 +2,8 @@ use crate::*;
 use num::BigInt;
 
 pub type ValueTable = HashMap<IdentId, Value>;
+/// Key-value table of Hash objects, which preserves insertion order.
+pub type HashTable = indexmap::IndexMap<HashKey, Value, fxhash::FxBuildHasher>;
 
 /// Heap-allocated objects.
 #[derive(Clone)]
@@ -32,6 +34,22 @@ impl GC<RValue> for RValue {
             Some(table) => table.values().for_each(|v| v.mark(alloc)),
             None => {}
         }
+        match &self.kind {
+            ObjKind::Array(v) => v.iter().for_each(|v| v.mark(alloc)),
+            ObjKind::Hash(map) => map.iter().for_each(|(k, v)| {
+                k.0.mark(alloc);
+                v.mark(alloc);
+            }),
+            ObjKind::Proc(block) => {
+                block.self_value.mark(alloc);
+                block.outer_block.mark(alloc);
+            }
+            ObjKind::Range(range) => {
+                range.start.mark(alloc);
+                range.end.mark(alloc);
+            }
+            _ => {}
+        }
     }
 }
 
@@ -74,6 +92,58 @@ impl RValue {
         self.flags.change_class(new_class_id);
     }
 
+    pub(crate) fn is_frozen(&self) -> bool {
+        self.flags.is_frozen()
+    }
+
+    pub(crate) fn freeze(&mut self) {
+        self.flags.set_frozen();
+    }
+
+    pub(crate) fn unfreeze(&mut self) {
+        self.flags.unset_frozen();
+    }
+
+    /// Replace each value referred to by `self` with the result of *f*.
+    pub(crate) fn map_values(&mut self, mut f: impl FnMut(Value) -> Value) {
+        if let Some(table) = &mut self.var_table {
+            table.values_mut().for_each(|v| *v = f(*v));
+        }
+        match &mut self.kind {
+            ObjKind::Array(v) => v.iter_mut().for_each(|v| *v = f(*v)),
+            ObjKind::Hash(map) => {
+                **map = map.drain(..).map(|(k, v)| (k.map(&mut f), f(v))).collect();
+            }
+            ObjKind::Range(range) => {
+                range.start = f(range.start);
+                range.end = f(range.end);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn get_var(&self, id: IdentId) -> Option<Value> {
+        self.var_table.as_ref()?.get(&id).cloned()
+    }
+
+    pub(crate) fn set_var(&mut self, id: IdentId, val: Value) {
+        self.var_table
+            .get_or_insert_with(|| Box::new(ValueTable::default()))
+            .insert(id, val);
+    }
+
+    pub(crate) fn remove_var(&mut self, id: IdentId) -> Option<Value> {
+        self.var_table.as_mut()?.remove(&id)
+    }
+
+    pub(crate) fn new_object(class_id: ClassId) -> Self {
+        RValue {
+            flags: RVFlag::new(class_id),
+            kind: ObjKind::Object,
+            var_table: None,
+        }
+    }
+
     pub(crate) fn new_bigint(bigint: BigInt) -> Self {
         RValue {
             flags: RVFlag::new(INTEGER_CLASS),
@@ -113,6 +183,54 @@ impl RValue {
             var_table: None,
         }
     }
+
+    pub(crate) fn new_array(v: Vec<Value>) -> Self {
+        RValue {
+            flags: RVFlag::new(ARRAY_CLASS),
+            kind: ObjKind::Array(v),
+            var_table: None,
+        }
+    }
+
+    pub(crate) fn new_hash(map: HashTable) -> Self {
+        RValue {
+            flags: RVFlag::new(HASH_CLASS),
+            kind: ObjKind::Hash(Box::new(map)),
+            var_table: None,
+        }
+    }
+
+    pub(crate) fn new_proc(func_id: FuncId, self_value: Value, outer_block: Value) -> Self {
+        RValue {
+            flags: RVFlag::new(PROC_CLASS),
+            kind: ObjKind::Proc(BlockData {
+                func_id,
+                self_value,
+                outer_block,
+            }),
+            var_table: None,
+        }
+    }
+
+    pub(crate) fn new_range(start: Value, end: Value, exclude_end: bool) -> Self {
+        RValue {
+            flags: RVFlag::new(RANGE_CLASS),
+            kind: ObjKind::Range(RangeInfo {
+                start,
+                end,
+                exclude_end,
+            }),
+            var_table: None,
+        }
+    }
+
+    pub(crate) fn new_exception(class_id: ClassId, err: MonorubyErr) -> Self {
+        RValue {
+            flags: RVFlag::new(class_id),
+            kind: ObjKind::Exception(Box::new(err)),
+            var_table: None,
+        }
+    }
 }
 
 impl RValue {
@@ -151,15 +269,57 @@ impl RVFlag {
         let class: u32 = new_class_id.into();
         self.flag = (class as u64) << 32 | lower_flag;
     }
+
+    fn is_frozen(&self) -> bool {
+        let flag = unsafe { self.flag };
+        assert!((flag & 0b1) == 1);
+        flag & 0b10 != 0
+    }
+
+    fn set_frozen(&mut self) {
+        self.flag = unsafe { self.flag } | 0b10;
+    }
+
+    fn unset_frozen(&mut self) {
+        self.flag = unsafe { self.flag } & !0b10;
+    }
+}
+
+/// A block passed to a method.
+///
+/// The layout is fixed because JIT-ed code reads the fields directly.
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub struct BlockData {
+    /// the function of the block body.
+    pub func_id: FuncId,
+    /// `self` in the block body.
+    pub self_value: Value,
+    /// the block of the scope where the block was created, which is called by `yield` in the block body.
+    pub outer_block: Value,
+}
+
+/// A range object. (e.g. `1..3`, `1...3`)
+#[derive(Debug, Clone)]
+pub struct RangeInfo {
+    pub start: Value,
+    pub end: Value,
+    pub exclude_end: bool,
 }
 
 #[derive(Debug, Clone)]
 pub enum ObjKind {
+    Object,
     Class(ClassId),
     Bignum(BigInt),
     Float(f64),
     Bytes(Vec<u8>),
     Time(TimeInfo),
+    Array(Vec<Value>),
+    Hash(Box<HashTable>),
+    Proc(BlockData),
+    Range(RangeInfo),
+    Exception(Box<MonorubyErr>),
     Invalid,
     Dummy(u64, u64, u64, u64, u64),
 }
@@ -1,5 +1,5 @@
 use crate::*;
-use num::BigInt;
+use num::{BigInt, ToPrimitive};
 
 use crate::alloc::{Allocator, GC};
 
@@ -33,6 +33,9 @@ impl GC<RValue> for Value {
 }
 
 impl Value {
+    /// Compare *lhs* and *rhs* by their values.
+    ///
+    /// Objects without a value semantics (e.g. user objects) are compared by their identities.
     pub fn eq(lhs: Self, rhs: Self) -> bool {
         if lhs == rhs {
             return true;
@@ -42,13 +45,104 @@ impl Value {
                 (ObjKind::Bignum(lhs), ObjKind::Bignum(rhs)) => lhs == rhs,
                 (ObjKind::Float(lhs), ObjKind::Float(rhs)) => lhs == rhs,
                 (ObjKind::Bytes(lhs), ObjKind::Bytes(rhs)) => lhs == rhs,
+                (ObjKind::Array(lhs), ObjKind::Array(rhs)) => {
+                    lhs.len() == rhs.len()
+                        && lhs.iter().zip(rhs.iter()).all(|(l, r)| Value::eq(*l, *r))
+                }
+                (ObjKind::Hash(lhs), ObjKind::Hash(rhs)) => {
+                    lhs.len() == rhs.len()
+                        && lhs.iter().all(|(k, l)| match rhs.get(k) {
+                            Some(r) => Value::eq(*l, *r),
+                            None => false,
+                        })
+                }
+                (ObjKind::Range(lhs), ObjKind::Range(rhs)) => {
+                    lhs.exclude_end == rhs.exclude_end
+                        && Value::eq(lhs.start, rhs.start)
+                        && Value::eq(lhs.end, rhs.end)
+                }
                 _ => false,
             },
             _ => false,
         }
     }
+
+    /// Calculate a hash value of *self*, which is consistent with `Value::eq`.
+    pub fn calc_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        match self.as_rvalue() {
+            Some(rvalue) => match &rvalue.kind {
+                ObjKind::Bignum(b) => b.hash(&mut hasher),
+                // 0.0 and -0.0 are eql? to each other.
+                ObjKind::Float(f) => (if *f == 0.0 { 0.0f64 } else { *f })
+                    .to_bits()
+                    .hash(&mut hasher),
+                ObjKind::Bytes(b) => b.hash(&mut hasher),
+                ObjKind::Array(v) => v.iter().for_each(|v| v.calc_hash().hash(&mut hasher)),
+                // must be independent of the insertion order.
+                ObjKind::Hash(map) => map
+                    .iter()
+                    .fold(0u64, |acc, (k, v)| {
+                        acc ^ k.hash_value().wrapping_add(v.calc_hash().rotate_left(1))
+                    })
+                    .hash(&mut hasher),
+                ObjKind::Range(range) => {
+                    range.start.calc_hash().hash(&mut hasher);
+                    range.end.calc_hash().hash(&mut hasher);
+                    range.exclude_end.hash(&mut hasher);
+                }
+                _ => self.get().hash(&mut hasher),
+            },
+            None => self.get().hash(&mut hasher),
+        }
+        hasher.finish()
+    }
+}
+
+/// A key of Hash objects, which is hashed and compared by its content.
+///
+/// The second field holds the hash value returned by `hash` defined in Ruby, if any.
+/// It is calculated when the key is made, as Ruby methods can not be called while
+/// a table is being searched.
+#[derive(Clone, Copy, Debug)]
+pub struct HashKey(pub Value, Option<u64>);
+
+impl HashKey {
+    /// Make a key which is hashed by `Value::calc_hash()`.
+    pub fn new(key: Value) -> Self {
+        HashKey(key, None)
+    }
+
+    /// Make a key with *hash* which is returned by `hash` defined in Ruby.
+    pub(crate) fn with_hash(key: Value, hash: u64) -> Self {
+        HashKey(key, Some(hash))
+    }
+
+    pub(crate) fn hash_value(&self) -> u64 {
+        self.1.unwrap_or_else(|| self.0.calc_hash())
+    }
+
+    /// Replace the key with the result of *f*, keeping the hash value given by Ruby.
+    pub(crate) fn map(self, f: impl FnOnce(Value) -> Value) -> Self {
+        HashKey(f(self.0), self.1)
+    }
 }
 
+impl std::hash::Hash for HashKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.hash_value().hash(state);
+    }
+}
+
+impl PartialEq for HashKey {
+    fn eq(&self, other: &Self) -> bool {
+        Value::eq(self.0, other.0)
+    }
+}
+
+impl Eq for HashKey {}
+
 impl Value {
     pub fn from(id: u64) -> Self {
         Value(std::num::NonZeroU64::new(id).unwrap())
@@ -85,6 +179,34 @@ impl Value {
         }
     }
 
+    /// Immediate values are always frozen.
+    pub fn is_frozen(&self) -> bool {
+        match self.as_rvalue() {
+            Some(rvalue) => rvalue.is_frozen(),
+            None => true,
+        }
+    }
+
+    pub fn freeze(&self) {
+        if self.as_rvalue().is_some() {
+            self.rvalue_mut().freeze();
+        }
+    }
+
+    /// Freeze *self* and the objects referred to by it (e.g. elements of an Array) recursively.
+    ///
+    /// Objects which are already frozen are not traversed, so reference cycles are cut.
+    pub fn deep_freeze(&self) {
+        if self.is_frozen() {
+            return;
+        }
+        self.freeze();
+        self.rvalue_mut().map_values(|v| {
+            v.deep_freeze();
+            v
+        });
+    }
+
     pub fn get_singleton(self, globals: &mut Globals) -> Value {
         let original_id = self.as_class();
         let singleton = globals.get_singleton_id(original_id);
@@ -120,6 +242,34 @@ impl Value {
         }
     }
 
+    /// Copy `self` recursively.
+    ///
+    /// Strings, arrays, hashes, ranges and instance variables are copied and the copies
+    /// are not frozen, while classes, procs and exceptions are shared.
+    /// An object referred to more than once, including by a cycle, is copied only once.
+    pub(crate) fn deep_copy(self) -> Value {
+        self.deep_copy_inner(&mut HashMap::default())
+    }
+
+    fn deep_copy_inner(self, copied: &mut HashMap<u64, Value>) -> Value {
+        let rval = match self.as_rvalue() {
+            Some(rval) => rval,
+            None => return self,
+        };
+        if let Some(copy) = copied.get(&self.get()) {
+            return *copy;
+        }
+        if let ObjKind::Class(_) | ObjKind::Proc(_) | ObjKind::Exception(_) = rval.kind {
+            return self;
+        }
+        let mut rval = rval.clone();
+        rval.unfreeze();
+        let copy = rval.pack();
+        copied.insert(self.get(), copy);
+        copy.rvalue_mut().map_values(|v| v.deep_copy_inner(copied));
+        copy
+    }
+
     /*#[inline(always)]
     pub fn from_unchecked(id: u64) -> Self {
         unsafe { Value(std::num::NonZeroU64::new_unchecked(id)) }
@@ -193,6 +343,10 @@ impl Value {
         }
     }
 
+    pub fn new_object(class_id: ClassId) -> Self {
+        RValue::new_object(class_id).pack()
+    }
+
     pub fn new_empty_class(id: ClassId) -> Self {
         RValue::new_class(id).pack()
     }
@@ -209,6 +363,26 @@ impl Value {
         RValue::new_time(time).pack()
     }
 
+    pub fn new_array(v: Vec<Value>) -> Self {
+        RValue::new_array(v).pack()
+    }
+
+    pub fn new_hash(map: HashTable) -> Self {
+        RValue::new_hash(map).pack()
+    }
+
+    pub fn new_proc(func_id: FuncId, self_value: Value, outer_block: Value) -> Self {
+        RValue::new_proc(func_id, self_value, outer_block).pack()
+    }
+
+    pub fn new_range(start: Value, end: Value, exclude_end: bool) -> Self {
+        RValue::new_range(start, end, exclude_end).pack()
+    }
+
+    pub fn new_exception(class_id: ClassId, err: MonorubyErr) -> Self {
+        RValue::new_exception(class_id, err).pack()
+    }
+
     pub fn unpack(&self) -> RV {
         if let Some(i) = self.as_fixnum() {
             RV::Integer(i)
@@ -236,14 +410,14 @@ impl Value {
 }
 
 impl Value {
-    /*fn is_nil(&self) -> bool {
+    pub fn is_nil(&self) -> bool {
         self.0.get() == NIL_VALUE
-    }*/
+    }
 
-    /*pub fn to_bool(&self) -> bool {
+    pub fn to_bool(&self) -> bool {
         let v = self.0.get();
         (v | 0x10) != 0x14
-    }*/
+    }
 
     pub fn is_packed_value(&self) -> bool {
         self.0.get() & 0b0111 != 0
@@ -302,6 +476,13 @@ impl Value {
         unsafe { &*(self.get() as *const RValue) }
     }
 
+    pub(crate) fn is_class(&self) -> Option<ClassId> {
+        match &self.as_rvalue()?.kind {
+            ObjKind::Class(id) => Some(*id),
+            _ => None,
+        }
+    }
+
     pub(crate) fn as_class(&self) -> ClassId {
         match self.unpack() {
             RV::Object(rv) => match rv.kind {
@@ -316,12 +497,140 @@ impl Value {
         unsafe { &mut *(self.get() as *mut RValue) }
     }
 
+    pub(crate) fn as_array(&self) -> Option<&Vec<Value>> {
+        match &self.as_rvalue()?.kind {
+            ObjKind::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array_mut(&self) -> Option<&mut Vec<Value>> {
+        if self.is_packed_value() {
+            return None;
+        }
+        match &mut self.rvalue_mut().kind {
+            ObjKind::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_hash(&self) -> Option<&HashTable> {
+        match &self.as_rvalue()?.kind {
+            ObjKind::Hash(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_proc(&self) -> Option<&BlockData> {
+        match &self.as_rvalue()?.kind {
+            ObjKind::Proc(block) => Some(block),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_range(&self) -> Option<&RangeInfo> {
+        match &self.as_rvalue()?.kind {
+            ObjKind::Range(range) => Some(range),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_exception(&self) -> Option<&MonorubyErr> {
+        match &self.as_rvalue()?.kind {
+            ObjKind::Exception(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_hash_mut(&self) -> Option<&mut HashTable> {
+        if self.is_packed_value() {
+            return None;
+        }
+        match &mut self.rvalue_mut().kind {
+            ObjKind::Hash(map) => Some(map),
+            _ => None,
+        }
+    }
+
     /*#[inline(always)]
     fn is_packed_num(&self) -> bool {
         self.0.get() & 0b11 != 0
     }*/
 }
 
+//
+// Conversion into Rust values for embedders.
+//
+impl Value {
+    /// Get the integer value, or None if `self` is not an Integer or does not fit in i64.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.unpack() {
+            RV::Integer(i) => Some(i),
+            RV::BigInt(b) => b.to_i64(),
+            _ => None,
+        }
+    }
+
+    /// Get the float value, or None if `self` is not a Float.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.unpack() {
+            RV::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+
+    /// Get the boolean value, or None if `self` is neither true nor false.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.unpack() {
+            RV::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Get the content of a String, or None if `self` is not a String or not valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        match self.unpack() {
+            RV::String(b) => std::str::from_utf8(b).ok(),
+            _ => None,
+        }
+    }
+}
+
+fn conversion_error(val: Value, class_name: &str) -> MonorubyErr {
+    MonorubyErr::typeerr(format!("can't convert {:?} into {}", val, class_name))
+}
+
+impl TryFrom<Value> for i64 {
+    type Error = MonorubyErr;
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        val.as_i64().ok_or_else(|| conversion_error(val, "i64"))
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = MonorubyErr;
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        val.as_f64().ok_or_else(|| conversion_error(val, "f64"))
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = MonorubyErr;
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        val.as_bool().ok_or_else(|| conversion_error(val, "bool"))
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = MonorubyErr;
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        match val.as_str() {
+            Some(s) => Ok(s.to_string()),
+            None => Err(conversion_error(val, "String")),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub enum RV<'a> {
     Nil,
@@ -351,3 +660,38 @@ impl<'a> std::fmt::Debug for RV<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_conversion() {
+        for i in [0, 1, -1, i64::MAX, i64::MIN, 1 << 62, -(1 << 62) - 1] {
+            let val = Value::new_integer(i);
+            assert_eq!(Some(i), val.as_i64());
+            assert_eq!(i, i64::try_from(val).unwrap());
+            assert!(val.as_f64().is_none());
+        }
+        assert!(Value::new_bigint(BigInt::from(i64::MAX) * 2)
+            .as_i64()
+            .is_none());
+        for f in [0.0, 1.5, -2.25, 1e300, f64::INFINITY] {
+            let val = Value::new_float(f);
+            assert_eq!(Some(f), val.as_f64());
+            assert_eq!(f, f64::try_from(val).unwrap());
+            assert!(val.as_i64().is_none());
+        }
+        assert_eq!(Some(true), Value::bool(true).as_bool());
+        assert!(!bool::try_from(Value::bool(false)).unwrap());
+        assert!(Value::nil().as_bool().is_none());
+        let val = Value::new_string(b"monoruby".to_vec());
+        assert_eq!(Some("monoruby"), val.as_str());
+        assert_eq!("monoruby", String::try_from(val).unwrap());
+        assert!(Value::new_string(vec![0xff]).as_str().is_none());
+        match String::try_from(Value::new_integer(1)).unwrap_err().kind {
+            MonorubyErrKind::Type(_) => {}
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+}